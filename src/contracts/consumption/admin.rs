@@ -0,0 +1,540 @@
+//! The owner operations: config (and its timelock), resets, migration and penalties
+
+use super::*;
+
+impl ChannelConsumptionContract {
+    /// Atomically set the validation mode and its addresses, ensuring the chosen mode has what it needs
+    pub(super) fn _set_validation_mode(
+        &mut self,
+        mode: u8,
+        platform_signer: Address,
+        registry: Address,
+    ) -> Result<(), Errors> {
+        let is_complete = match mode {
+            VALIDATION_MODE_REGISTRY => {
+                !registry.is_zero() || !self.content_registry.get().is_zero()
+            }
+            VALIDATION_MODE_SINGLE_SIGNER => !platform_signer.is_zero(),
+            VALIDATION_MODE_ALLOWLIST => true,
+            _ => false,
+        };
+        if !is_complete {
+            return Err(Errors::InvalidValidationMode(InvalidValidationMode {}));
+        }
+        self.validation_mode.set(U8::from(mode));
+        self.platform_signer.set(platform_signer);
+        // A zero registry keeps the current one, so switching modes back and forth never loses it
+        if !registry.is_zero() {
+            self._set_content_registry(registry)?;
+        }
+        evm::log(ValidationModeChanged {
+            mode,
+            platformSigner: platform_signer,
+            registry: self.content_registry.get(),
+        });
+        Ok(())
+    }
+
+    /// Set the number of distinct validators that must sign a multi signature push (at least one)
+    pub(super) fn _set_validator_quorum(&mut self, quorum: U256) -> Result<(), Errors> {
+        if quorum.is_zero() {
+            return Err(Errors::InvalidConfigParams(InvalidConfigParams {}));
+        }
+        self.validator_quorum.set(quorum);
+        evm::log(ValidatorQuorumSet { quorum });
+        Ok(())
+    }
+
+    /// Point to a new content registry, rejecting the zero address that would refuse every validator
+    pub(super) fn _set_content_registry(&mut self, new_registry: Address) -> Result<(), Errors> {
+        if new_registry.is_zero() {
+            return Err(Errors::InvalidAddress(InvalidAddress {}));
+        }
+        let previous = self.content_registry.get();
+        self.content_registry.set(new_registry);
+        evm::log(ContentRegistryUpdated {
+            previous,
+            current: new_registry,
+        });
+        Ok(())
+    }
+
+    /// Ensure a sensitive setter can be called directly, i.e. no config timelock is set
+    pub(super) fn _ensure_no_timelock(&self) -> Result<(), Errors> {
+        if self.config_timelock.get().is_zero() {
+            Ok(())
+        } else {
+            Err(Errors::TimelockRequired(TimelockRequired {}))
+        }
+    }
+
+    /// Check if a selector is one of the timelockable config setters
+    pub(super) fn _is_config_selector(selector: [u8; 4]) -> bool {
+        selector == setChannelDailyCapCall::SELECTOR
+            || selector == setConfigTimelockCall::SELECTOR
+            || selector == setMaxPushesPerEpochCall::SELECTOR
+            || selector == setCapsCall::SELECTOR
+            || selector == setValidationModeCall::SELECTOR
+            || selector == setMaxConsumptionPerPushCall::SELECTOR
+            || selector == setContentRegistryCall::SELECTOR
+            || selector == setValidatorQuorumCall::SELECTOR
+            || selector == setContentTypeMultiplierCall::SELECTOR
+            || selector == registerChannelCall::SELECTOR
+    }
+
+    /// Apply a config change encoded as `selector || abi encoded params`
+    pub(super) fn _apply_config_change(
+        &mut self,
+        selector: [u8; 4],
+        params: &[u8],
+    ) -> Result<(), Errors> {
+        if selector == setChannelDailyCapCall::SELECTOR {
+            let call = setChannelDailyCapCall::abi_decode_raw(params, true)
+                .map_err(|_| Errors::InvalidConfigParams(InvalidConfigParams {}))?;
+            self.channel_daily_cap.set(call.cap);
+        } else if selector == setConfigTimelockCall::SELECTOR {
+            let call = setConfigTimelockCall::abi_decode_raw(params, true)
+                .map_err(|_| Errors::InvalidConfigParams(InvalidConfigParams {}))?;
+            self.config_timelock.set(call.delay);
+        } else if selector == setMaxPushesPerEpochCall::SELECTOR {
+            let call = setMaxPushesPerEpochCall::abi_decode_raw(params, true)
+                .map_err(|_| Errors::InvalidConfigParams(InvalidConfigParams {}))?;
+            self.max_pushes_per_epoch.set(call.maxPushes);
+        } else if selector == setCapsCall::SELECTOR {
+            let call = setCapsCall::abi_decode_raw(params, true)
+                .map_err(|_| Errors::InvalidConfigParams(InvalidConfigParams {}))?;
+            self._set_caps(call.maxPerPush, call.maxUser, call.maxTotal, call.dailyCap);
+        } else if selector == setValidationModeCall::SELECTOR {
+            let call = setValidationModeCall::abi_decode_raw(params, true)
+                .map_err(|_| Errors::InvalidConfigParams(InvalidConfigParams {}))?;
+            self._set_validation_mode(call.mode, call.platformSigner, call.registry)?;
+        } else if selector == setMaxConsumptionPerPushCall::SELECTOR {
+            let call = setMaxConsumptionPerPushCall::abi_decode_raw(params, true)
+                .map_err(|_| Errors::InvalidConfigParams(InvalidConfigParams {}))?;
+            self._set_caps(call.maxPerPush, U256::MAX, U256::MAX, U256::MAX);
+        } else if selector == setContentRegistryCall::SELECTOR {
+            let call = setContentRegistryCall::abi_decode_raw(params, true)
+                .map_err(|_| Errors::InvalidConfigParams(InvalidConfigParams {}))?;
+            self._set_content_registry(call.newRegistry)?;
+        } else if selector == setValidatorQuorumCall::SELECTOR {
+            let call = setValidatorQuorumCall::abi_decode_raw(params, true)
+                .map_err(|_| Errors::InvalidConfigParams(InvalidConfigParams {}))?;
+            self._set_validator_quorum(call.quorum)?;
+        } else if selector == setContentTypeMultiplierCall::SELECTOR {
+            let call = setContentTypeMultiplierCall::abi_decode_raw(params, true)
+                .map_err(|_| Errors::InvalidConfigParams(InvalidConfigParams {}))?;
+            self._set_content_type_multiplier(call.contentTypes, call.multiplierBps);
+        } else if selector == registerChannelCall::SELECTOR {
+            let call = registerChannelCall::abi_decode_raw(params, true)
+                .map_err(|_| Errors::InvalidConfigParams(InvalidConfigParams {}))?;
+            self._register_channel(
+                call.channelId,
+                call.validator,
+                call.cap,
+                call.multiplierBps,
+                call.contentId,
+                &call.metadata,
+            );
+        } else {
+            return Err(Errors::UnknownConfigChange(UnknownConfigChange {}));
+        }
+        Ok(())
+    }
+
+    /// Set every consumption caps at once, a `U256::MAX` value leaving the matching cap unchanged
+    pub(super) fn _set_caps(
+        &mut self,
+        max_per_push: U256,
+        max_user: U256,
+        max_total: U256,
+        daily_cap: U256,
+    ) {
+        if max_per_push != U256::MAX {
+            self.max_consumption_per_push.set(max_per_push);
+        }
+        if max_user != U256::MAX {
+            self.max_total_per_user.set(max_user);
+        }
+        if max_total != U256::MAX {
+            self.max_total_consumption.set(max_total);
+        }
+        if daily_cap != U256::MAX {
+            self.channel_daily_cap.set(daily_cap);
+        }
+
+        evm::log(CapsUpdated {
+            maxPerPush: self.max_consumption_per_push.get(),
+            maxUser: self.max_total_per_user.get(),
+            maxTotal: self.max_total_consumption.get(),
+            dailyCap: self.channel_daily_cap.get(),
+        });
+    }
+
+    /// Build the `ValidatePenalty` struct hash signed by the validator
+    pub(super) fn _penalty_struct_hash(
+        user: Address,
+        penalty_bps: U256,
+        deadline: U256,
+        nonce: U256,
+    ) -> B256 {
+        keccak(
+            <sol! { (bytes32, address, uint256, uint256, uint256) }>::abi_encode(&(
+                keccak(b"ValidatePenalty(address user,uint256 penaltyBps,uint256 deadline,uint256 nonce)").0,
+                user,
+                penalty_bps,
+                deadline,
+                nonce,
+            )),
+        )
+    }
+
+    /// Set the weight of a content types bitmask, keeping the count of weighted content types in sync
+    pub(super) fn _set_content_type_multiplier(
+        &mut self,
+        content_types: U256,
+        multiplier_bps: U256,
+    ) {
+        let previous_bps = self.content_type_multipliers_bps.get(content_types);
+        if previous_bps.is_zero() && !multiplier_bps.is_zero() {
+            self.weighted_content_types
+                .set(self.weighted_content_types.get() + U256::from(1));
+        } else if !previous_bps.is_zero() && multiplier_bps.is_zero() {
+            self.weighted_content_types
+                .set(self.weighted_content_types.get() - U256::from(1));
+        }
+        self.content_type_multipliers_bps
+            .insert(content_types, multiplier_bps);
+        evm::log(ContentTypeMultiplierSet {
+            contentTypes: content_types,
+            multiplierBps: multiplier_bps,
+        });
+    }
+
+    /// Register a channel with its whole config (see `registerChannel`)
+    pub(super) fn _register_channel(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        validator: Address,
+        cap: U256,
+        multiplier_bps: U256,
+        content_id: U256,
+        metadata: &[u8],
+    ) {
+        self.channel_registered.insert(channel_id, true);
+        self.channel_validators.insert(channel_id, validator);
+        self.channel_caps.insert(channel_id, cap);
+        self.channel_multipliers_bps
+            .insert(channel_id, multiplier_bps);
+        self.channel_contents.insert(channel_id, content_id);
+        self.channel_bound.insert(channel_id, true);
+        self.channel_metadata.setter(channel_id).set_bytes(metadata);
+        evm::log(ChannelRegistered {
+            channelId: channel_id,
+            contentId: content_id,
+            validator,
+            cap,
+            multiplierBps: multiplier_bps,
+        });
+    }
+
+    /// Reset a user's consumption to zero, keeping the global total consistent
+    /// Slots are deleted (rather than overwritten) so they're eligible for storage refunds,
+    /// along with the per user velocity tracking slots; nonces are kept to prevent signature replays
+    pub(super) fn _reset_user_consumption(&mut self, user: Address) -> U256 {
+        let previous_amount = self.user_consumptions.get(user);
+        self._snapshot_user_consumption(user);
+        self.user_consumptions.delete(user);
+        self.total_consumption
+            .set(self.total_consumption.get().saturating_sub(previous_amount));
+        self._update_team_consumption(user, previous_amount, false);
+
+        // Clear the associated per user slots
+        self.user_first_push_at.delete(user);
+        self.user_last_push_at.delete(user);
+        self.user_velocity_window_index.delete(user);
+        self.user_velocity_window_consumption.delete(user);
+
+        evm::log(ConsumptionReset {
+            user,
+            previousAmount: previous_amount,
+        });
+        previous_amount
+    }
+
+    /// Queue a timelocked config change (see `queueConfigChange`)
+    pub(super) fn _queue_config_change(
+        &mut self,
+        selector: FixedBytes<4>,
+        params: Bytes,
+    ) -> Result<FixedBytes<32>, Errors> {
+        self._only_owner()?;
+        if !Self::_is_config_selector(selector.0) {
+            return Err(Errors::UnknownConfigChange(UnknownConfigChange {}));
+        }
+
+        // Build a unique id for this change
+        let nonce = self.config_change_nonce.get();
+        self.config_change_nonce.set(nonce + U256::from(1));
+        let mut call_data = selector.to_vec();
+        call_data.extend_from_slice(&params);
+        let mut id_input = call_data.clone();
+        id_input.extend_from_slice(&nonce.to_be_bytes::<32>());
+        let id = keccak(id_input);
+
+        // Store it
+        let eta = U256::from(block::timestamp()) + self.config_timelock.get();
+        self.queued_config_changes.setter(id).set_bytes(&call_data);
+        self.queued_config_etas.insert(id, eta);
+
+        evm::log(ConfigChangeQueued {
+            id,
+            selector,
+            params: params.to_vec().into(),
+            eta,
+        });
+
+        Ok(id)
+    }
+
+    /// Apply a queued config change once its timelock is over (see `executeConfigChange`)
+    pub(super) fn _execute_config_change(&mut self, id: FixedBytes<32>) -> Result<(), Errors> {
+        self._only_owner()?;
+
+        // Ensure the change is queued and ready
+        let eta = self.queued_config_etas.get(id);
+        if eta.is_zero() {
+            return Err(Errors::ConfigChangeNotQueued(ConfigChangeNotQueued {}));
+        }
+        if U256::from(block::timestamp()) < eta {
+            return Err(Errors::ConfigChangeNotReady(ConfigChangeNotReady {}));
+        }
+
+        // Dequeue it and apply it
+        let call_data = self.queued_config_changes.getter(id).get_bytes();
+        self.queued_config_changes.delete(id);
+        self.queued_config_etas.delete(id);
+        let (selector, params) = call_data.split_at(4);
+        let selector: [u8; 4] = selector
+            .try_into()
+            .map_err(|_| Errors::UnknownConfigChange(UnknownConfigChange {}))?;
+        self._apply_config_change(selector, params)?;
+
+        evm::log(ConfigChangeExecuted { id });
+
+        Ok(())
+    }
+
+    /// Reset the consumption of several users (see `resetUserConsumptionBatch`)
+    pub(super) fn _reset_user_consumption_batch(
+        &mut self,
+        users: Vec<Address>,
+    ) -> Result<(U256, bool), Errors> {
+        self._only_role(CONSUMPTION_ADMIN_ROLE)?;
+        for (i, user) in users.iter().enumerate() {
+            if self._is_batch_gas_exhausted() {
+                return Ok((U256::from(i), false));
+            }
+            self._reset_user_consumption(*user);
+        }
+        Ok((U256::from(users.len()), true))
+    }
+
+    /// Decrease a user's consumption (see `decreaseUserConsumption`)
+    pub(super) fn _decrease_user_consumption(
+        &mut self,
+        user: Address,
+        amount: U256,
+    ) -> Result<U256, Errors> {
+        self._only_role(CONSUMPTION_ADMIN_ROLE)?;
+        let new_total = self
+            .user_consumptions
+            .get(user)
+            .checked_sub(amount)
+            .ok_or(Errors::InsufficientConsumption(InsufficientConsumption {}))?;
+        self._snapshot_user_consumption(user);
+        self.user_consumptions.insert(user, new_total);
+        self.total_consumption
+            .set(self.total_consumption.get().saturating_sub(amount));
+        self._update_team_consumption(user, amount, false);
+
+        evm::log(ConsumptionDecreased {
+            user,
+            amount,
+            newTotal: new_total,
+        });
+        Ok(new_total)
+    }
+
+    /// Overwrite a user's consumption (see `setUserConsumption`)
+    pub(super) fn _set_user_consumption(
+        &mut self,
+        user: Address,
+        new_value: U256,
+    ) -> Result<(), Errors> {
+        self._only_role(CONSUMPTION_ADMIN_ROLE)?;
+        let old_value = self.user_consumptions.get(user);
+        let total_consumption = self.total_consumption.get();
+        let new_total = if new_value >= old_value {
+            let delta = new_value - old_value;
+            self._update_team_consumption(user, delta, true);
+            total_consumption
+                .checked_add(delta)
+                .ok_or(Errors::ConsumptionOverflow(ConsumptionOverflow {}))?
+        } else {
+            let delta = old_value - new_value;
+            self._update_team_consumption(user, delta, false);
+            total_consumption
+                .checked_sub(delta)
+                .ok_or(Errors::InsufficientConsumption(InsufficientConsumption {}))?
+        };
+        self._snapshot_user_consumption(user);
+        self.user_consumptions.insert(user, new_value);
+        self.total_consumption.set(new_total);
+
+        evm::log(ConsumptionSet {
+            user,
+            oldValue: old_value,
+            newValue: new_value,
+        });
+        Ok(())
+    }
+
+    /// Import the consumption of a previous contract (see `migrateConsumption`)
+    pub(super) fn _migrate_consumption(
+        &mut self,
+        users: Vec<Address>,
+        amounts: Vec<U256>,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        if self.migrated.get() {
+            return Err(Errors::AlreadyMigrated(AlreadyMigrated {}));
+        }
+        if users.len() != amounts.len() {
+            return Err(Errors::LengthMismatch(LengthMismatch {}));
+        }
+
+        let mut global_consumption = self.total_consumption.get();
+        let mut migrated_total = U256::ZERO;
+        for (user, amount) in users.iter().zip(amounts.iter()) {
+            let total_consumption = self
+                .user_consumptions
+                .get(*user)
+                .checked_add(*amount)
+                .ok_or(Errors::ConsumptionOverflow(ConsumptionOverflow {}))?;
+            global_consumption = global_consumption
+                .checked_add(*amount)
+                .ok_or(Errors::ConsumptionOverflow(ConsumptionOverflow {}))?;
+            migrated_total += *amount;
+
+            self._snapshot_user_consumption(*user);
+            self.user_consumptions.insert(*user, total_consumption);
+            self._update_team_consumption(*user, *amount, true);
+            if !self.user_indexed.get(*user) {
+                self.user_indexed.insert(*user, true);
+                self.users.push(*user);
+            }
+        }
+        self.total_consumption.set(global_consumption);
+
+        evm::log(ConsumptionMigrated {
+            users: U256::from(users.len()),
+            amount: migrated_total,
+        });
+        Ok(())
+    }
+
+    /// Credit consumption missed by a previous deployment (see `backfillConsumption`)
+    pub(super) fn _backfill_consumption(
+        &mut self,
+        users: Vec<Address>,
+        amounts: Vec<U256>,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        if users.len() != amounts.len() {
+            return Err(Errors::LengthMismatch(LengthMismatch {}));
+        }
+
+        let mut global_consumption = self.total_consumption.get();
+        for (user, amount) in users.iter().zip(amounts.iter()) {
+            let total_consumption = self
+                .user_consumptions
+                .get(*user)
+                .checked_add(*amount)
+                .ok_or(Errors::ConsumptionOverflow(ConsumptionOverflow {}))?;
+            global_consumption = global_consumption
+                .checked_add(*amount)
+                .ok_or(Errors::ConsumptionOverflow(ConsumptionOverflow {}))?;
+
+            self._snapshot_user_consumption(*user);
+            self.user_consumptions.insert(*user, total_consumption);
+            self._update_team_consumption(*user, *amount, true);
+            if !self.user_indexed.get(*user) {
+                self.user_indexed.insert(*user, true);
+                self.users.push(*user);
+            }
+
+            evm::log(ConsumptionBackfilled {
+                user: *user,
+                amount: *amount,
+                totalConsumption: total_consumption,
+            });
+        }
+        self.total_consumption.set(global_consumption);
+        Ok(())
+    }
+
+    /// Verify and apply a validator signed penalty (see `applyPenalty`)
+    pub(super) fn _apply_penalty(
+        &mut self,
+        user: Address,
+        penalty_bps: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._ensure_unlocked()?;
+
+        // Ensure the penalty is valid
+        if penalty_bps > U256::from(BPS_DENOMINATOR) {
+            return Err(Errors::InvalidPenalty(InvalidPenalty {}));
+        }
+        self._ensure_deadline(deadline)?;
+
+        // Rebuild the signed data, bound to the current penalty nonce
+        let nonce = self.penalty_nonces.get(user);
+        let struct_hash = Self::_penalty_struct_hash(user, penalty_bps, deadline, nonce);
+
+        // Ensure it was signed by a validator
+        let recovered_address = self
+            .eip712
+            .recover_typed_data_signer(struct_hash, v, r, s)?;
+        self._check_validator_role(recovered_address)?;
+
+        // Consume the nonce
+        self.penalty_nonces.insert(user, nonce + U256::from(1));
+
+        // Remove the penalty from the user and the global total (floored at zero)
+        self._snapshot_user_consumption(user);
+        let mut storage_ptr = self.user_consumptions.setter(user);
+        let current = storage_ptr.get();
+        let removed = if penalty_bps == U256::from(BPS_DENOMINATOR) {
+            current
+        } else {
+            current * penalty_bps / U256::from(BPS_DENOMINATOR)
+        };
+        storage_ptr.set(current - removed);
+        self.total_consumption
+            .set(self.total_consumption.get().saturating_sub(removed));
+        self._update_team_consumption(user, removed, false);
+
+        // Emit the event
+        evm::log(PenaltyApplied {
+            user,
+            penaltyBps: penalty_bps,
+            removed,
+        });
+
+        Ok(())
+    }
+}
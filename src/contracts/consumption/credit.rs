@@ -0,0 +1,946 @@
+//! Crediting a verified push, with every limit and anomaly check it goes through
+
+use super::*;
+
+impl ChannelConsumptionContract {
+    /// Credit a validated consumption to a user, updating every tracked totals
+    /// Every rule is checked (see `_check_credit`) before the first write, so a rejected push leaves no trace even
+    /// when its error is swallowed; past the checks, only an overflowing total can revert
+    /// Returns the receipt id of the push (also issued when queued or escrowed), `None` if it was dropped
+    pub(super) fn _credit_consumption(
+        &mut self,
+        user: Address,
+        content_id: U256,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+    ) -> Result<Option<U256>, Errors> {
+        // While the rewards are frozen, the push is either rejected or queued
+        if self.rewards_frozen.get() {
+            self._queue_frozen_push(user, content_id, channel_id, added_consumption)?;
+            return Ok(Some(self._issue_receipt(user)));
+        }
+
+        // Ensure the push is acceptable, getting the amount to credit (weighted, and clamped if configured)
+        let added_consumption =
+            self._check_credit(user, content_id, channel_id, added_consumption)?;
+
+        // Drop the push, pausing the contract, if the global consumption grows anomalously fast
+        // (dropped rather than reverted, so the pause sticks)
+        if self._trip_circuit_breaker(added_consumption) {
+            return Ok(None);
+        }
+
+        // Decay the consumption accumulated so far, before adding the new one
+        self._apply_decay(user);
+
+        // Count the push against the channel daily cap, the user's epoch budget and the same block rule
+        let now = U256::from(block::timestamp());
+        self._accrue_channel_daily(channel_id, added_consumption)?;
+        self._count_epoch_push(user);
+        self._record_push_block(user, channel_id);
+
+        // Book the per channel and per content figures (and rewards) right away, unless escrowed
+        let challenge_period = self.challenge_period.get();
+        if challenge_period.is_zero() {
+            let epoch = self._current_epoch();
+            self._book_channel_consumption(user, content_id, channel_id, added_consumption, epoch)?;
+        }
+
+        // Confirm any matured escrowed consumption first
+        self._confirm_pending(user)?;
+
+        // Flag the user if their recent velocity is anomalous (advisory only)
+        self._track_velocity(user, added_consumption);
+
+        // Flag a push adding more than real time allows since the last one, and a user spreading their pushes
+        // over too many channels today (both rejected by the checks instead, if configured)
+        self._flag_consumption_rate(user, added_consumption);
+        self._record_channel_spread(user, channel_id, now);
+
+        // Compute the new user and global totals (their caps are checked along with the other rules)
+        let total_consumption =
+            Self::_add_consumption(self.user_consumptions.get(user), added_consumption)?;
+        // (the global total is only read once, this being the hot path)
+        let previous_global_consumption = self.total_consumption.get();
+        let global_consumption =
+            Self::_add_consumption(previous_global_consumption, added_consumption)?;
+
+        // Index the user on their first push
+        if !self.user_indexed.get(user) {
+            self.user_indexed.insert(user, true);
+            self.users.push(user);
+        }
+
+        // Record the push time, and issue its receipt
+        self.user_last_push_at.insert(user, now);
+        self.user_channel_last_push_at
+            .setter(user)
+            .insert(channel_id, now);
+        let receipt_id = self._issue_receipt(user);
+
+        // During a challenge period, the consumption lands in escrow first, nothing being booked until confirmed
+        if !challenge_period.is_zero() {
+            self._escrow_push(user, content_id, channel_id, added_consumption)?;
+            let pending =
+                Self::_add_consumption(self.pending_consumptions.get(user), added_consumption)?;
+            let total_pending =
+                Self::_add_consumption(self.total_pending_consumption.get(), added_consumption)?;
+            self.pending_consumptions.insert(user, pending);
+            self.total_pending_consumption.set(total_pending);
+            let release_at = now + challenge_period;
+            self.pending_release_at.insert(user, release_at);
+
+            evm::log(ConsumptionEscrowed {
+                user,
+                channelId: channel_id,
+                amount: added_consumption,
+                releaseAt: release_at,
+            });
+            return Ok(Some(receipt_id));
+        }
+
+        // Emit the event, unless gated by the event mode
+        if self._should_log_push(previous_global_consumption, global_consumption) {
+            evm::log(CcuPushed {
+                user,
+                channelId: channel_id,
+                totalConsumption: total_consumption,
+            });
+        }
+
+        // Update the ccu amount
+        self._snapshot_user_consumption(user);
+        self.user_consumptions.insert(user, total_consumption);
+
+        // Track the consumption of the current UTC day
+        let day_index = now / U256::from(SECONDS_PER_DAY);
+        let mut daily_ptr = self.user_daily_consumptions.setter(user);
+        let daily_consumption =
+            Self::_add_consumption(daily_ptr.get(day_index), added_consumption)?;
+        daily_ptr.insert(day_index, daily_consumption);
+
+        // Update the whole total consumption, and the user's team total
+        self.total_consumption.set(global_consumption);
+        self._emit_milestone(previous_global_consumption, global_consumption);
+        self._update_team_consumption(user, added_consumption, true);
+
+        // Return the receipt
+        Ok(Some(receipt_id))
+    }
+
+    /// Check a push against every rule of `_credit_consumption`, without any write
+    /// Returns the amount it would credit (weighted, and clamped to the user cap if configured)
+    /// The user and global caps account for the decay and the matured escrow the credit writes first
+    pub(super) fn _check_credit(
+        &self,
+        user: Address,
+        content_id: U256,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+    ) -> Result<U256, Errors> {
+        // Apply the channel and content type weights, everything below works on the credited amount
+        let added_consumption = self._weighted_consumption(channel_id, added_consumption);
+        let added_consumption = self._content_type_weighted(content_id, added_consumption)?;
+
+        // Ensure the push itself is acceptable
+        self._check_push_rules(content_id, channel_id, added_consumption)?;
+        self._ensure_content_exists(content_id)?;
+
+        // Clamp the push to what's left under the user cap, if configured (escrowed consumption included)
+        let added_consumption = self._clamp_to_user_cap(user, added_consumption)?;
+
+        // Ensure the channel stays within its daily cap (a zero cap means no cap)
+        let accrued =
+            Self::_add_consumption(self._channel_day_accrued(channel_id), added_consumption)?;
+        let daily_cap = self.channel_daily_cap.get();
+        if !daily_cap.is_zero() && accrued > daily_cap {
+            return Err(Errors::ChannelDailyCapExceeded(ChannelDailyCapExceeded {}));
+        }
+
+        // Ensure the user stays within their push budget for the epoch (a zero max meaning unlimited pushes),
+        // and isn't cooling down
+        let now = U256::from(block::timestamp());
+        let max_pushes = self.max_pushes_per_epoch.get();
+        let pushes = self
+            .epoch_push_counts
+            .getter(self._current_epoch())
+            .get(user);
+        if !max_pushes.is_zero() && pushes >= max_pushes {
+            return Err(Errors::PushCountExceeded(PushCountExceeded {}));
+        }
+        if now < self._next_push_at(user) {
+            return Err(Errors::RateLimited(RateLimited {}));
+        }
+        if now < self._next_channel_push_at(user, channel_id) {
+            return Err(Errors::CooldownActive(CooldownActive {}));
+        }
+        if self._is_repeated_push_in_block(user, channel_id) {
+            return Err(Errors::DoublePushInBlock(DoublePushInBlock {}));
+        }
+
+        // Ensure the channel stays within its lifetime cap (escrowed consumption included)
+        let channel_cap = self.channel_caps.get(channel_id);
+        if !channel_cap.is_zero()
+            && self
+                .channel_consumptions
+                .get(channel_id)
+                .saturating_add(self.channel_pending_consumptions.get(channel_id))
+                .saturating_add(added_consumption)
+                > channel_cap
+        {
+            return Err(Errors::ChannelCapExceeded(ChannelCapExceeded {}));
+        }
+
+        // Ensure an escrowed push has room left, once the matured escrow is confirmed
+        let matured = self._matured_pending(user);
+        if !self.challenge_period.get().is_zero()
+            && matured.is_zero()
+            && self.pending_push_amounts.getter(user).len() >= MAX_PENDING_PUSHES
+        {
+            return Err(Errors::TooManyPendingPushes(TooManyPendingPushes {}));
+        }
+
+        // Reject a push adding more than real time allows since the last one, and a user spreading their pushes
+        // over too many channels today, if configured
+        if self.reject_implausible_rate.get()
+            && self._implausible_rate(user, added_consumption).is_some()
+        {
+            return Err(Errors::ImplausibleConsumptionRate(
+                ImplausibleConsumptionRate {},
+            ));
+        }
+        if self.reject_channel_spread.get()
+            && self
+                ._channel_spread(user, channel_id, now)
+                .is_some_and(|count| count > self.max_channels_per_window.get())
+        {
+            return Err(Errors::ChannelSpreadExceeded(ChannelSpreadExceeded {}));
+        }
+
+        // Ensure the user stays within their lifetime cap, and the global total within the global cap
+        // (both accounting for the consumption still in escrow)
+        let consumption = self.user_consumptions.get(user);
+        let decayed = self._decayed_consumption(user);
+        let total_consumption =
+            Self::_add_consumption(Self::_add_consumption(decayed, matured)?, added_consumption)?;
+        let max_user = self.max_total_per_user.get();
+        if !max_user.is_zero()
+            && total_consumption.saturating_add(self.pending_consumptions.get(user) - matured)
+                > max_user
+        {
+            return Err(Errors::UserCapExceeded(UserCapExceeded {}));
+        }
+        let global_consumption = Self::_add_consumption(
+            Self::_add_consumption(
+                self.total_consumption
+                    .get()
+                    .saturating_sub(consumption - decayed),
+                matured,
+            )?,
+            added_consumption,
+        )?;
+        let max_total = self.max_total_consumption.get();
+        if !max_total.is_zero()
+            && global_consumption.saturating_add(self.total_pending_consumption.get() - matured)
+                > max_total
+        {
+            return Err(Errors::GlobalCapExceeded(GlobalCapExceeded {}));
+        }
+        Ok(added_consumption)
+    }
+
+    /// Issue the next push receipt id, recorded as the user's last one
+    pub(super) fn _issue_receipt(&mut self, user: Address) -> U256 {
+        let receipt_id = self.receipt_counter.get() + U256::from(1);
+        self.receipt_counter.set(receipt_id);
+        self.user_last_receipt.insert(user, receipt_id);
+        receipt_id
+    }
+
+    /// Add two consumption figures, reverting rather than wrapping around
+    pub(super) fn _add_consumption(total: U256, added: U256) -> Result<U256, Errors> {
+        total
+            .checked_add(added)
+            .ok_or(Errors::ConsumptionOverflow(ConsumptionOverflow {}))
+    }
+
+    /// Book a credited consumption per content (accruing its rewards) and per channel, into a given epoch
+    /// Runs at push time, or once confirmed for an escrowed push (so a challenged amount is never booked)
+    /// Any total (or the accrued rewards) that would overflow reverts with `ConsumptionOverflow`
+    pub(super) fn _book_channel_consumption(
+        &mut self,
+        user: Address,
+        content_id: U256,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        epoch: U256,
+    ) -> Result<(), Errors> {
+        // Track the per content consumption, and accrue the rewards at the content rate
+        let mut content_ptr = self.content_user_consumptions.setter(content_id);
+        let mut content_user_ptr = content_ptr.setter(user);
+        let content_consumption =
+            Self::_add_consumption(content_user_ptr.get(), added_consumption)?;
+        content_user_ptr.set(content_consumption);
+        let reward_rate = self.content_reward_rates.get(content_id);
+        if !reward_rate.is_zero() {
+            let mut rewards_ptr = self.accrued_rewards.setter(user);
+            let added_rewards = added_consumption
+                .checked_mul(reward_rate)
+                .ok_or(Errors::ConsumptionOverflow(ConsumptionOverflow {}))?;
+            let rewards = Self::_add_consumption(rewards_ptr.get(), added_rewards)?;
+            rewards_ptr.set(rewards);
+        }
+
+        // Update the channel total
+        let channel_consumption =
+            Self::_add_consumption(self.channel_consumptions.get(channel_id), added_consumption)?;
+        self.channel_consumptions
+            .insert(channel_id, channel_consumption);
+        if !self.channel_seen.get(channel_id) {
+            self.channel_seen.insert(channel_id, true);
+            self.channel_count
+                .set(self.channel_count.get() + U256::from(1));
+        }
+
+        // Update the epoch total
+        let mut epoch_ptr = self.epoch_totals.setter(epoch);
+        let epoch_consumption = Self::_add_consumption(epoch_ptr.get(), added_consumption)?;
+        epoch_ptr.set(epoch_consumption);
+
+        // Record the push in the channel histogram
+        self._record_channel_histogram(channel_id, added_consumption);
+
+        // Track the user's first push on this channel
+        if !self.user_channel_seen.getter(user).get(channel_id) {
+            self.user_channel_seen.setter(user).insert(channel_id, true);
+            self.user_channels.setter(user).push(channel_id);
+            let mut count_ptr = self.user_channel_count.setter(user);
+            let count = count_ptr.get();
+            count_ptr.set(count + U256::from(1));
+        }
+
+        // Update the user total on this channel
+        let mut user_channel_ptr = self.user_channel_consumptions.setter(user);
+        let user_channel_consumption =
+            Self::_add_consumption(user_channel_ptr.get(channel_id), added_consumption)?;
+        user_channel_ptr.insert(channel_id, user_channel_consumption);
+        evm::log(ChannelConsumptionUpdated {
+            user,
+            channelId: channel_id,
+            channelTotalForUser: user_channel_consumption,
+            channelTotalAllUsers: channel_consumption,
+        });
+        Ok(())
+    }
+
+    /// Emit a `MilestoneReached` event if the total consumption crossed a multiple of the milestone step
+    /// A push crossing several milestones at once only emits the highest one, keeping the push cost bounded
+    pub(super) fn _emit_milestone(&self, previous_total: U256, new_total: U256) {
+        let step = self.milestone_step.get();
+        if step.is_zero() {
+            return;
+        }
+        let milestone = new_total / step;
+        if milestone > previous_total / step {
+            evm::log(MilestoneReached {
+                milestone: milestone * step,
+            });
+        }
+    }
+
+    /// Check if a user was already credited on a channel within this block (a replay or a bot, almost always),
+    /// when such pushes are rejected
+    pub(super) fn _is_repeated_push_in_block(
+        &self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+    ) -> bool {
+        self.reject_same_block_pushes.get()
+            && self
+                .user_channel_last_push_block
+                .getter(user)
+                .get(channel_id)
+                == U256::from(block::number())
+    }
+
+    /// Record the block of a push on a channel, when pushes within the same block are rejected
+    pub(super) fn _record_push_block(&mut self, user: Address, channel_id: FixedBytes<32>) {
+        if !self.reject_same_block_pushes.get() {
+            return;
+        }
+        self.user_channel_last_push_block
+            .setter(user)
+            .insert(channel_id, U256::from(block::number()));
+    }
+
+    /// Accumulate a push into the global velocity window, pausing the contract past the maximum increase
+    /// Returns whether the push must be dropped: when it trips the breaker, or once tripped within the same call
+    pub(super) fn _trip_circuit_breaker(&mut self, added_consumption: U256) -> bool {
+        let max_velocity = self.max_global_velocity.get();
+        if max_velocity.is_zero() {
+            return false;
+        }
+        if self.circuit_breaker_tripped.get() {
+            return true;
+        }
+
+        // The window is a single block, or a fixed time window
+        let window = self.global_velocity_window.get();
+        let window_index = if window.is_zero() {
+            U256::from(block::number())
+        } else {
+            U256::from(block::timestamp()) / window
+        };
+        let previous_increase = if self.global_velocity_window_index.get() == window_index {
+            self.global_velocity_window_increase.get()
+        } else {
+            self.global_velocity_window_index.set(window_index);
+            U256::ZERO
+        };
+        let increase = previous_increase.saturating_add(added_consumption);
+        if increase <= max_velocity {
+            self.global_velocity_window_increase.set(increase);
+            return false;
+        }
+
+        self.paused.set(true);
+        self.circuit_breaker_tripped.set(true);
+        self.global_velocity_window_increase.set(previous_increase);
+        evm::log(CircuitBreakerTripped {
+            delta: increase,
+            window,
+        });
+        true
+    }
+
+    /// Check if a push moving the total consumption between these values emits `CcuPushed`, per the event mode
+    /// In the milestone mode, without milestone step, no push crosses any
+    pub(super) fn _should_log_push(&self, previous_total: U256, new_total: U256) -> bool {
+        match self.event_mode.get().to::<u8>() {
+            EVENT_MODE_MILESTONES => {
+                let step = self.milestone_step.get();
+                !step.is_zero() && new_total / step > previous_total / step
+            }
+            EVENT_MODE_SUPPRESSED => false,
+            _ => true,
+        }
+    }
+
+    /// Check the stateless rules of a push (zero amount, denied or mismatched channel, dust floor, per push cap)
+    pub(super) fn _check_push_rules(
+        &self,
+        content_id: U256,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+    ) -> Result<(), Errors> {
+        // A zero push is almost always a client bug, reject it unless explicitly allowed
+        if added_consumption.is_zero() && !self.allow_zero_consumption.get() {
+            return Err(Errors::ZeroConsumption(ZeroConsumption {}));
+        }
+
+        // Ensure the channel isn't denied
+        if self.denied_channels.get(channel_id) {
+            return Err(Errors::ChannelDenied(ChannelDenied {}));
+        }
+
+        // Ensure the channel is registered, if required
+        if self.require_channel_registration.get() && !self.channel_registered.get(channel_id) {
+            return Err(Errors::ChannelNotRegistered(ChannelNotRegistered {}));
+        }
+
+        // Ensure the channel belongs to the content, if enforced
+        if self.enforce_channel_binding.get()
+            && (!self.channel_bound.get(channel_id)
+                || self.channel_contents.get(channel_id) != content_id)
+        {
+            return Err(Errors::ChannelContentMismatch(ChannelContentMismatch {}));
+        }
+
+        // Ensure the push isn't dust
+        if added_consumption < self.min_consumption_per_push.get() {
+            return Err(Errors::ConsumptionTooSmall(ConsumptionTooSmall {}));
+        }
+
+        // Ensure the push stays within the per push cap
+        let max_per_push = self.max_consumption_per_push.get();
+        if !max_per_push.is_zero() && added_consumption > max_per_push {
+            return Err(Errors::ConsumptionTooLarge(ConsumptionTooLarge {}));
+        }
+        Ok(())
+    }
+
+    /// Simulate crediting a push, without any write (same rules as `_credit_consumption`, see `_check_credit`)
+    /// Returns the user total, the channel total and the credited amount, as they would be after the push
+    /// With a challenge period, the credited amount lands in escrow, so the user total only moves by the matured escrow,
+    /// and the channel total doesn't move
+    pub(super) fn _simulate_credit(
+        &self,
+        user: Address,
+        content_id: U256,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+    ) -> Result<(U256, U256, U256), Errors> {
+        // Nothing is credited right away during a rewards freeze
+        if self.rewards_frozen.get() {
+            return Err(Errors::RewardsFrozen(RewardsFrozen {}));
+        }
+        let added_consumption =
+            self._check_credit(user, content_id, channel_id, added_consumption)?;
+
+        // An escrowed push isn't booked on its channel until confirmed either
+        let confirmed =
+            Self::_add_consumption(self._decayed_consumption(user), self._matured_pending(user))?;
+        let channel_total = self.channel_consumptions.get(channel_id);
+        if self.challenge_period.get().is_zero() {
+            Ok((
+                Self::_add_consumption(confirmed, added_consumption)?,
+                Self::_add_consumption(channel_total, added_consumption)?,
+                added_consumption,
+            ))
+        } else {
+            Ok((confirmed, channel_total, added_consumption))
+        }
+    }
+
+    /// Apply the channel weight to a pushed amount (unweighted without a multiplier)
+    pub(super) fn _weighted_consumption(
+        &self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+    ) -> U256 {
+        let multiplier_bps = self.channel_multipliers_bps.get(channel_id);
+        if multiplier_bps.is_zero() {
+            added_consumption
+        } else {
+            added_consumption * multiplier_bps / U256::from(BPS_DENOMINATOR)
+        }
+    }
+
+    /// Apply the weight of the content types to a pushed amount (unweighted without a multiplier)
+    /// The registry is only asked for the content types once at least one of them is weighted
+    pub(super) fn _content_type_weighted(
+        &self,
+        content_id: U256,
+        added_consumption: U256,
+    ) -> Result<U256, Errors> {
+        if self.weighted_content_types.get().is_zero() {
+            return Ok(added_consumption);
+        }
+        let multiplier_bps = self
+            .content_type_multipliers_bps
+            .get(self._get_content_types(content_id)?);
+        if multiplier_bps.is_zero() {
+            return Ok(added_consumption);
+        }
+        added_consumption
+            .checked_mul(multiplier_bps)
+            .map(|weighted| weighted / U256::from(BPS_DENOMINATOR))
+            .ok_or(Errors::ConsumptionTooLarge(ConsumptionTooLarge {}))
+    }
+
+    /// Ensure a content still exists in the registry, if checked (through a static call, like the content types)
+    pub(super) fn _ensure_content_exists(&self, content_id: U256) -> Result<(), Errors> {
+        if !self.check_content_existence.get() {
+            return Ok(());
+        }
+        let exists =
+            static_call_helper::<isExistingContentCall>(self.content_registry.get(), (content_id,))
+                .map_err(|_| Errors::CallError(CallError {}))?;
+        if exists._0 {
+            Ok(())
+        } else {
+            Err(Errors::ContentNotFound(ContentNotFound {}))
+        }
+    }
+
+    /// Get the content types bitmask of a content from the registry
+    /// Done through a static call, the registry can't modify any state (so usable from the views)
+    pub(super) fn _get_content_types(&self, content_id: U256) -> Result<U256, Errors> {
+        static_call_helper::<getContentTypesCall>(self.content_registry.get(), (content_id,))
+            .map(|content_types| content_types._0)
+            .map_err(|_| Errors::CallError(CallError {}))
+    }
+
+    /// Clamp a push to what's left under the user cap, when clamping is enabled (otherwise left as is)
+    /// The user's consumption counts once decayed, as the credit decays it first; a user already at their cap is
+    /// still rejected
+    pub(super) fn _clamp_to_user_cap(
+        &self,
+        user: Address,
+        added_consumption: U256,
+    ) -> Result<U256, Errors> {
+        let max_user = self.max_total_per_user.get();
+        if max_user.is_zero() || !self.clamp_user_cap.get() {
+            return Ok(added_consumption);
+        }
+        let used = self
+            ._decayed_consumption(user)
+            .saturating_add(self.pending_consumptions.get(user));
+        if used >= max_user {
+            return Err(Errors::UserCapExceeded(UserCapExceeded {}));
+        }
+        Ok(added_consumption.min(max_user - used))
+    }
+
+    /// Get the largest part of a requested amount that a push could credit right now, clamped by every cap
+    /// The requested amount is weighted first, as the channel and content type multipliers apply before any cap
+    /// Zero if the push would be rejected whatever the amount (denied or mismatched channel, push budget exhausted,
+    /// cooling down on the user or the channel, already pushed on the channel in this block, rewards frozen)
+    pub(super) fn _effective_credit(
+        &self,
+        user: Address,
+        content_id: U256,
+        channel_id: FixedBytes<32>,
+        requested: U256,
+    ) -> U256 {
+        if requested.is_zero()
+            || self.rewards_frozen.get()
+            || self
+                ._check_push_rules(
+                    content_id,
+                    channel_id,
+                    self.min_consumption_per_push.get().max(U256::from(1)),
+                )
+                .is_err()
+            || self._ensure_content_exists(content_id).is_err()
+        {
+            return U256::ZERO;
+        }
+        let max_pushes = self.max_pushes_per_epoch.get();
+        let pushes = self
+            .epoch_push_counts
+            .getter(self._current_epoch())
+            .get(user);
+        let now = U256::from(block::timestamp());
+        if (!max_pushes.is_zero() && pushes >= max_pushes)
+            || now < self._next_push_at(user)
+            || now < self._next_channel_push_at(user, channel_id)
+            || self._is_repeated_push_in_block(user, channel_id)
+        {
+            return U256::ZERO;
+        }
+
+        // Clamp against the per push cap, and the room left under the channel, daily, user and global caps
+        let mut credited = match self._content_type_weighted(
+            content_id,
+            self._weighted_consumption(channel_id, requested),
+        ) {
+            Ok(credited) => credited,
+            Err(_) => return U256::ZERO,
+        };
+        let max_per_push = self.max_consumption_per_push.get();
+        if !max_per_push.is_zero() {
+            credited = credited.min(max_per_push);
+        }
+        let channel_cap = self.channel_caps.get(channel_id);
+        if !channel_cap.is_zero() {
+            let channel_total = self
+                .channel_consumptions
+                .get(channel_id)
+                .saturating_add(self.channel_pending_consumptions.get(channel_id));
+            credited = credited.min(channel_cap.saturating_sub(channel_total));
+        }
+        let daily_cap = self.channel_daily_cap.get();
+        if !daily_cap.is_zero() {
+            credited =
+                credited.min(daily_cap.saturating_sub(self._channel_day_accrued(channel_id)));
+        }
+        let max_user = self.max_total_per_user.get();
+        if !max_user.is_zero() {
+            let user_total = self
+                .user_consumptions
+                .get(user)
+                .saturating_add(self.pending_consumptions.get(user));
+            credited = credited.min(max_user.saturating_sub(user_total));
+        }
+        let max_total = self.max_total_consumption.get();
+        if !max_total.is_zero() {
+            let global_total = self
+                .total_consumption
+                .get()
+                .saturating_add(self.total_pending_consumption.get());
+            credited = credited.min(max_total.saturating_sub(global_total));
+        }
+
+        // A clamped amount under the dust floor would be rejected
+        if credited < self.min_consumption_per_push.get() {
+            return U256::ZERO;
+        }
+        credited
+    }
+
+    /// Get the timestamp from which a user can push again (zero without any cooldown)
+    pub(super) fn _next_push_at(&self, user: Address) -> U256 {
+        let interval = self.min_push_interval.get();
+        let last_push_at = self.user_last_push_at.get(user);
+        if interval.is_zero() || last_push_at.is_zero() {
+            U256::ZERO
+        } else {
+            last_push_at + interval
+        }
+    }
+
+    /// Get the timestamp from which a user can push again on a channel (zero without any cooldown)
+    pub(super) fn _next_channel_push_at(&self, user: Address, channel_id: FixedBytes<32>) -> U256 {
+        let interval = self.min_channel_push_interval.get();
+        let last_push_at = self.user_channel_last_push_at.getter(user).get(channel_id);
+        if interval.is_zero() || last_push_at.is_zero() {
+            U256::ZERO
+        } else {
+            last_push_at + interval
+        }
+    }
+
+    /// Get the consumption accrued by a channel today
+    pub(super) fn _channel_day_accrued(&self, channel_id: FixedBytes<32>) -> U256 {
+        let day_index = U256::from(block::timestamp() / SECONDS_PER_DAY);
+        if self.channel_day_index.get(channel_id) == day_index {
+            self.channel_day_consumption.get(channel_id)
+        } else {
+            U256::ZERO
+        }
+    }
+
+    /// Track the consumption of a user in the current velocity window, and flag them if it exceeds
+    /// `velocity_multiple` times their historical average per window (no-op while not configured)
+    /// The historical average is their confirmed consumption divided by the windows elapsed since their first push
+    pub(super) fn _track_velocity(&mut self, user: Address, added_consumption: U256) {
+        let multiple = self.velocity_multiple.get();
+        let window = self.velocity_window.get();
+        if multiple.is_zero() || window.is_zero() {
+            return;
+        }
+
+        // Accumulate the consumption of the current window
+        let now = U256::from(block::timestamp());
+        let window_index = now / window;
+        let velocity = if self.user_velocity_window_index.get(user) == window_index {
+            self.user_velocity_window_consumption
+                .get(user)
+                .saturating_add(added_consumption)
+        } else {
+            self.user_velocity_window_index.insert(user, window_index);
+            added_consumption
+        };
+        self.user_velocity_window_consumption.insert(user, velocity);
+
+        // No history on the first push
+        let first_push_at = self.user_first_push_at.get(user);
+        if first_push_at.is_zero() {
+            self.user_first_push_at.insert(user, now);
+            return;
+        }
+
+        // Compare with the historical average
+        let elapsed_windows = (now - first_push_at) / window + U256::from(1);
+        let average = self.user_consumptions.get(user) / elapsed_windows;
+        if !average.is_zero() && velocity > average.saturating_mul(multiple) {
+            self.flagged.insert(user, true);
+            evm::log(UserFlagged { user, velocity });
+        }
+    }
+
+    /// Get the rate of a push, `added / seconds since the user's last push`, if it exceeds the maximum plausible rate
+    /// Within the same second the whole amount counts as the rate; the first push of a user is never implausible
+    pub(super) fn _implausible_rate(&self, user: Address, added_consumption: U256) -> Option<U256> {
+        let max_rate = self.max_consumption_rate.get();
+        let last_push_at = self.user_last_push_at.get(user);
+        if max_rate.is_zero() || last_push_at.is_zero() {
+            return None;
+        }
+        let elapsed = U256::from(block::timestamp())
+            .saturating_sub(last_push_at)
+            .max(U256::from(1));
+        let rate = added_consumption / elapsed;
+        (rate > max_rate).then_some(rate)
+    }
+
+    /// Flag a user pushing at an implausible rate (only reached when such pushes aren't rejected)
+    pub(super) fn _flag_consumption_rate(&mut self, user: Address, added_consumption: U256) {
+        if let Some(rate) = self._implausible_rate(user, added_consumption) {
+            self.flagged.insert(user, true);
+            evm::log(UserFlagged {
+                user,
+                velocity: rate,
+            });
+        }
+    }
+
+    /// Get the distinct channels a user pushed on today, this one included, if it's a new one for the day
+    /// `None` for a channel already pushed on today, or while the channel spread isn't tracked
+    pub(super) fn _channel_spread(
+        &self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        now: U256,
+    ) -> Option<U256> {
+        if self.max_channels_per_window.get().is_zero() {
+            return None;
+        }
+        let day_index = now / U256::from(SECONDS_PER_DAY);
+        if self
+            .user_daily_channel_seen
+            .getter(user)
+            .getter(day_index)
+            .get(channel_id)
+        {
+            return None;
+        }
+        Some(self.user_daily_channel_counts.getter(user).get(day_index) + U256::from(1))
+    }
+
+    /// Count the distinct channels a user pushes on per UTC day, flagging the user past the maximum
+    /// (only reached within the maximum when a channel spread is rejected)
+    pub(super) fn _record_channel_spread(
+        &mut self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        now: U256,
+    ) {
+        let Some(count) = self._channel_spread(user, channel_id, now) else {
+            return;
+        };
+        let day_index = now / U256::from(SECONDS_PER_DAY);
+        self.user_daily_channel_seen
+            .setter(user)
+            .setter(day_index)
+            .insert(channel_id, true);
+        self.user_daily_channel_counts
+            .setter(user)
+            .insert(day_index, count);
+        if count > self.max_channels_per_window.get() {
+            self.flagged.insert(user, true);
+            evm::log(ChannelSpreadAnomaly { user, count });
+        }
+    }
+
+    /// Add (or remove, floored at zero) consumption to the team of a user, if any
+    pub(super) fn _update_team_consumption(&mut self, user: Address, amount: U256, is_added: bool) {
+        let team_id = self.user_teams.get(user);
+        if team_id.is_zero() {
+            return;
+        }
+        let mut team_ptr = self.team_consumptions.setter(team_id);
+        let team_consumption = team_ptr.get();
+        if is_added {
+            team_ptr.set(team_consumption.saturating_add(amount));
+        } else {
+            team_ptr.set(team_consumption.saturating_sub(amount));
+        }
+    }
+
+    /// Get the consumption of a user once decayed since their last push
+    /// `decayed = consumption * (1 - decayRateBps / 10000) ^ fullDaysSinceLastPush`, computed with an 18 decimals
+    /// fixed point factor (exponentiation by squaring, rounding down), so a user loses the same share every day
+    pub(super) fn _decayed_consumption(&self, user: Address) -> U256 {
+        let consumption = self.user_consumptions.get(user);
+        let rate_bps = self.decay_rate_bps.get();
+        let last_push_at = self.user_last_push_at.get(user);
+        if rate_bps.is_zero() || last_push_at.is_zero() || consumption.is_zero() {
+            return consumption;
+        }
+        let elapsed_days = U256::from(block::timestamp()).saturating_sub(last_push_at)
+            / U256::from(SECONDS_PER_DAY);
+        if elapsed_days.is_zero() {
+            return consumption;
+        }
+
+        // Daily retention factor, raised to the number of elapsed days
+        let wad = U256::from(WAD);
+        let bps = U256::from(BPS_DENOMINATOR);
+        let mut base = (bps.saturating_sub(rate_bps)) * wad / bps;
+        let mut exponent = elapsed_days;
+        let mut factor = wad;
+        while !exponent.is_zero() && !factor.is_zero() {
+            if exponent.bit(0) {
+                factor = factor * base / wad;
+            }
+            base = base * base / wad;
+            exponent >>= 1;
+        }
+        consumption * factor / wad
+    }
+
+    /// Write the decay of a user's consumption down, keeping the global and team totals consistent
+    pub(super) fn _apply_decay(&mut self, user: Address) {
+        let consumption = self.user_consumptions.get(user);
+        let decayed = self._decayed_consumption(user);
+        if decayed == consumption {
+            return;
+        }
+        let decay = consumption - decayed;
+        self._snapshot_user_consumption(user);
+        self.user_consumptions.insert(user, decayed);
+        self.total_consumption
+            .set(self.total_consumption.get().saturating_sub(decay));
+        self._update_team_consumption(user, decay, false);
+    }
+
+    /// Compute the current epoch
+    /// Either the stored counter, or, with a duration set, the counter plus the epochs elapsed since `epoch_start`
+    /// Per epoch counters are keyed by epoch, so time based epochs need no keeper to reset them
+    pub(super) fn _current_epoch(&self) -> U256 {
+        let base_epoch = self.current_epoch.get();
+        let duration = self.epoch_duration.get();
+        if duration.is_zero() {
+            return base_epoch;
+        }
+        let elapsed = U256::from(block::timestamp()).saturating_sub(self.epoch_start.get());
+        base_epoch + elapsed / duration
+    }
+
+    /// Count a push for the user in the current epoch (the per epoch push budget is checked beforehand)
+    pub(super) fn _count_epoch_push(&mut self, user: Address) {
+        let epoch = self._current_epoch();
+        let mut counts_ptr = self.epoch_push_counts.setter(epoch);
+        let pushes = counts_ptr.get(user) + U256::from(1);
+        counts_ptr.insert(user, pushes);
+    }
+
+    /// Record a push amount in the channel histogram (no-op while no buckets are configured)
+    /// The push lands in the first bucket whose upper bound is >= the amount, or in the overflow bucket
+    pub(super) fn _record_channel_histogram(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+    ) {
+        let buckets_len = self.histogram_bounds.len();
+        if buckets_len == 0 {
+            return;
+        }
+
+        // Find the bucket of this push
+        let bucket = (0..buckets_len)
+            .find(|&i| {
+                self.histogram_bounds
+                    .get(i)
+                    .is_some_and(|bound| added_consumption <= bound)
+            })
+            .unwrap_or(buckets_len);
+        let bucket = U256::from(bucket);
+
+        // Increment the bucket and the channel push counter
+        let mut histogram_ptr = self.channel_histograms.setter(channel_id);
+        let bucket_count = histogram_ptr.get(bucket) + U256::from(1);
+        histogram_ptr.insert(bucket, bucket_count);
+        let mut pushes_ptr = self.channel_histogram_pushes.setter(channel_id);
+        let pushes = pushes_ptr.get() + U256::from(1);
+        pushes_ptr.set(pushes);
+    }
+
+    /// Accrue consumption in the channel day bucket (the channel daily cap is checked beforehand)
+    /// The bucket is reset as soon as a push lands on a new day (`timestamp / 86400`)
+    pub(super) fn _accrue_channel_daily(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+    ) -> Result<(), Errors> {
+        // Reset the accumulator if we switched to a new day
+        let accrued =
+            Self::_add_consumption(self._channel_day_accrued(channel_id), added_consumption)?;
+        self.channel_day_index
+            .insert(channel_id, U256::from(block::timestamp() / SECONDS_PER_DAY));
+        self.channel_day_consumption.insert(channel_id, accrued);
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+//! The consumption escrow of the challenge period
+
+use super::*;
+
+impl ChannelConsumptionContract {
+    /// Get the escrowed consumption of a user that's past its challenge period (zero if none)
+    pub(super) fn _matured_pending(&self, user: Address) -> U256 {
+        if U256::from(block::timestamp()) < self.pending_release_at.get(user) {
+            U256::ZERO
+        } else {
+            self.pending_consumptions.get(user)
+        }
+    }
+
+    /// Move the escrowed consumption of a user to their confirmed consumption, if the challenge period is over
+    /// Returns whether anything was confirmed
+    pub(super) fn _confirm_pending(&mut self, user: Address) -> Result<bool, Errors> {
+        let pending = self.pending_consumptions.get(user);
+        if pending.is_zero() || U256::from(block::timestamp()) < self.pending_release_at.get(user) {
+            return Ok(false);
+        }
+        let total_consumption = Self::_add_consumption(self.user_consumptions.get(user), pending)?;
+        let global_consumption = Self::_add_consumption(self.total_consumption.get(), pending)?;
+
+        // Clear the escrow
+        self.pending_consumptions.insert(user, U256::ZERO);
+        self.pending_release_at.insert(user, U256::ZERO);
+        self.total_pending_consumption
+            .set(self.total_pending_consumption.get() - pending);
+
+        // Book the escrowed pushes per channel and content, in the epoch they were pushed in
+        while let Some(amount) = self.pending_push_amounts.setter(user).pop() {
+            let channel_id = self
+                .pending_push_channels
+                .setter(user)
+                .pop()
+                .unwrap_or_default();
+            let content_id = self
+                .pending_push_contents
+                .setter(user)
+                .pop()
+                .unwrap_or_default();
+            let epoch = self
+                .pending_push_epochs
+                .setter(user)
+                .pop()
+                .unwrap_or_default();
+            let mut channel_pending_ptr = self.channel_pending_consumptions.setter(channel_id);
+            let channel_pending = channel_pending_ptr.get().saturating_sub(amount);
+            channel_pending_ptr.set(channel_pending);
+            if !amount.is_zero() {
+                self._book_channel_consumption(user, content_id, channel_id, amount, epoch)?;
+            }
+        }
+
+        // Credit the confirmed consumption
+        self._snapshot_user_consumption(user);
+        self.user_consumptions.insert(user, total_consumption);
+        self.total_consumption.set(global_consumption);
+        self._update_team_consumption(user, pending, true);
+
+        evm::log(PendingConfirmed {
+            user,
+            amount: pending,
+            totalConsumption: total_consumption,
+        });
+        Ok(true)
+    }
+
+    /// Record an escrowed push, to be booked per channel and content once confirmed
+    /// Bounded per user (see `_check_credit`), as the confirmation books every escrowed push at once
+    pub(super) fn _escrow_push(
+        &mut self,
+        user: Address,
+        content_id: U256,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+    ) -> Result<(), Errors> {
+        let epoch = self._current_epoch();
+        self.pending_push_amounts
+            .setter(user)
+            .push(added_consumption);
+        self.pending_push_channels.setter(user).push(channel_id);
+        self.pending_push_contents.setter(user).push(content_id);
+        self.pending_push_epochs.setter(user).push(epoch);
+        let mut channel_pending_ptr = self.channel_pending_consumptions.setter(channel_id);
+        let channel_pending = Self::_add_consumption(channel_pending_ptr.get(), added_consumption)?;
+        channel_pending_ptr.set(channel_pending);
+        Ok(())
+    }
+
+    /// Cancel part of a user's escrowed consumption (see `challengePending`)
+    pub(super) fn _challenge_pending(&mut self, user: Address, amount: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+
+        let pending = self.pending_consumptions.get(user);
+        let removed = amount.min(pending);
+        self.pending_consumptions.insert(user, pending - removed);
+        self.total_pending_consumption
+            .set(self.total_pending_consumption.get() - removed);
+
+        // Cancel the latest escrowed pushes first, so the challenged amount is never booked on their channels
+        let mut left = removed;
+        let mut index = self.pending_push_amounts.getter(user).len();
+        while !left.is_zero() && index > 0 {
+            index -= 1;
+            let channel_id = self
+                .pending_push_channels
+                .getter(user)
+                .get(index)
+                .unwrap_or_default();
+            let mut amounts_ptr = self.pending_push_amounts.setter(user);
+            let Some(mut amount_ptr) = amounts_ptr.setter(index) else {
+                break;
+            };
+            let amount = amount_ptr.get();
+            let cancelled = amount.min(left);
+            amount_ptr.set(amount - cancelled);
+            left -= cancelled;
+            let mut channel_pending_ptr = self.channel_pending_consumptions.setter(channel_id);
+            let channel_pending = channel_pending_ptr.get().saturating_sub(cancelled);
+            channel_pending_ptr.set(channel_pending);
+        }
+
+        evm::log(PendingChallenged {
+            user,
+            amount: removed,
+        });
+        Ok(())
+    }
+}
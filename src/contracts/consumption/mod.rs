@@ -0,0 +1,3195 @@
+use alloc::{string::String, vec::Vec};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use alloy_primitives::{Address, FixedBytes, B256, U256, U8};
+use alloy_sol_types::{SolCall, SolType};
+use openzeppelin_stylus::access::{
+    control::AccessControl,
+    ownable::{self, Ownable},
+};
+use stylus_sdk::{
+    abi::Bytes,
+    alloy_sol_types::sol,
+    block,
+    call::{call, transfer_eth, RawCall},
+    contract,
+    crypto::keccak,
+    evm, msg,
+    prelude::*,
+    storage::{
+        StorageAddress, StorageB256, StorageBool, StorageBytes, StorageMap, StorageU256, StorageU8,
+        StorageVec,
+    },
+    ArbResult,
+};
+
+use crate::utils::{
+    eip712::{Eip712, Eip712Params},
+    errors::{
+        error_code, error_selector, AlreadyInitialized, AlreadyMigrated, BatchTooLarge, CallError,
+        ChannelCapExceeded, ChannelContentMismatch, ChannelDailyCapExceeded, ChannelDenied,
+        ChannelNotRegistered, ChannelSpreadExceeded, CohortLeafAlreadyClaimed,
+        ConfigChangeNotQueued, ConfigChangeNotReady, ConsumptionOverflow, ConsumptionTooLarge,
+        ConsumptionTooSmall, ContentNotFound, ContractPaused, CooldownActive, DeadlineTooFar,
+        DelegationLoop, DoublePushInBlock, Errors, ExpiredSignature, GlobalCapExceeded,
+        ImplausibleConsumptionRate, InsufficientConsumption, InsufficientGas, InvalidAddress,
+        InvalidConfigParams, InvalidConsumptionDelta, InvalidHistogramBuckets, InvalidMerkleProof,
+        InvalidPenalty, InvalidPlatformSignature, InvalidSignatureLength, InvalidSnapshotId,
+        InvalidUserSignature, InvalidValidationMode, LengthMismatch, LimitTooLarge, NotInitialized,
+        NothingToClaim, NothingToWithdraw, PendingNotReleasable, PushCountExceeded,
+        QuorumNotReached, RangeTooLarge, RateLimited, ReentrantCall, RewardTransferFailed,
+        RewardsFrozen, SignatureCancelled, SignatureRevoked, TimelockRequired,
+        TooManyPendingPushes, UnknownConfigChange, UserCapExceeded, ValidatorHintMismatch,
+        WithdrawFailed, ZeroConsumption,
+    },
+    merkle,
+    signature::{split_compact_vs, split_packed},
+    solidity::{getContentTypesCall, isAuthorizedCall, isExistingContentCall, transferCall},
+};
+
+mod admin;
+mod credit;
+mod escrow;
+mod push;
+mod rewards;
+mod selectors;
+mod snapshots;
+#[cfg(test)]
+mod tests;
+mod validators;
+
+use selectors::{CONSUMPTION_INTERFACE_ID, ERC165_INTERFACE_ID, SUPPORTED_SELECTORS};
+
+sol! {
+    /// The per user figures of `getUserData`, in one self describing shape
+    struct UserConsumptionData {
+        uint256 total;
+        uint256 lastUpdate;
+        bool flagged;
+        uint256 nonce;
+    }
+
+    event Initialized(address owner, uint256 nuttyContentId, address contentRegistry);
+    event CcuPushed(address indexed user, bytes32 channelId, uint256 totalConsumption);
+    event ChannelConsumptionUpdated(address indexed user, bytes32 indexed channelId, uint256 channelTotalForUser, uint256 channelTotalAllUsers);
+    event CcuPushedBy(address indexed user, address indexed validator, bytes32 channelId, uint256 totalConsumption);
+    event CcuPushedWithSession(address indexed user, bytes32 channelId, bytes32 sessionId, uint256 totalConsumption);
+    event ValidatorQuorumSet(uint256 quorum);
+    event CcuRejected(address indexed user, bytes32 channelId, address recoveredSigner);
+    event PenaltyApplied(address indexed user, uint256 penaltyBps, uint256 removed);
+    event ConfigChangeQueued(bytes32 indexed id, bytes4 selector, bytes params, uint256 eta);
+    event ConfigChangeExecuted(bytes32 indexed id);
+    event EpochAdvanced(uint256 epoch);
+    event EpochDurationUpdated(uint256 duration, uint256 startEpoch, uint256 startTimestamp);
+    event SnapshotRootCommitted(uint256 indexed snapshotId, bytes32 root);
+    event ConsumptionSnapshot(uint256 indexed id, uint256 totalConsumption);
+    event ContentRewardRateUpdated(uint256 indexed contentId, uint256 rate);
+    event HistogramBucketsUpdated(uint256[] upperBounds);
+    event CapsUpdated(uint256 maxPerPush, uint256 maxUser, uint256 maxTotal, uint256 dailyCap);
+    event ValidatorRateLimitUpdated(uint256 maxPushes, uint256 window);
+    event ConsumptionEscrowed(address indexed user, bytes32 channelId, uint256 amount, uint256 releaseAt);
+    event PendingConfirmed(address indexed user, uint256 amount, uint256 totalConsumption);
+    event PendingChallenged(address indexed user, uint256 amount);
+    event RewardsFunded(address indexed funder, uint256 amount);
+    event TokenRewardsConfigured(address token, uint256 rate);
+    event TokenRewardsClaimed(address indexed user, uint256 consumption, uint256 amount);
+    event ConsumptionBurned(address indexed user, uint256 amount);
+    event RewardsClaimed(address indexed user, uint256 amount);
+    event Withdrawn(address indexed user, uint256 amount);
+    event UserFlagged(address indexed user, uint256 velocity);
+    event ChannelSpreadAnomaly(address indexed user, uint256 count);
+    event UserFlagCleared(address indexed user);
+    event VelocityAnomalyConfigUpdated(uint256 multiple, uint256 window);
+    event ChannelDeniedUpdated(bytes32 indexed channelId, bool denied);
+    event ConsumptionReset(address indexed user, uint256 previousAmount);
+    event ConsumptionDecreased(address indexed user, uint256 amount, uint256 newTotal);
+    event ConsumptionSet(address indexed user, uint256 oldValue, uint256 newValue);
+    event MilestoneReached(uint256 milestone);
+    event ConsumptionMigrated(uint256 users, uint256 amount);
+    event ConsumptionBackfilled(address indexed user, uint256 amount, uint256 totalConsumption);
+    event Paused(address indexed actor);
+    event Unpaused(address indexed actor);
+    event CircuitBreakerTripped(uint256 delta, uint256 window);
+    event ContentRegistryUpdated(address previous, address current);
+    event ContentTypeMultiplierSet(uint256 contentTypes, uint256 multiplierBps);
+    event OwnershipTransferStarted(address indexed previousOwner, address indexed newOwner);
+    event ValidatorHintMismatched(address indexed user, address expected, address recovered);
+    event CohortLeafClaimed(bytes32 indexed root, address indexed user, bytes32 channelId, uint256 amount);
+    event SignatureRevokedByUser(address indexed user, bytes32 structHash);
+    event SignatureInvalidated(bytes32 indexed digest, address indexed actor);
+    event ValidationModeChanged(uint8 mode, address platformSigner, address registry);
+    event ValidatorAllowlistUpdated(address indexed validator, bool allowed);
+    event LocalValidatorUpdated(address indexed validator, bool listed);
+    event ValidatorExpirySet(address indexed validator, uint256 expiry);
+    event ValidatorValidityPeriodUpdated(uint256 period);
+    event MinGasForPushUpdated(uint256 minGas);
+    event ChannelRegistered(bytes32 indexed channelId, uint256 indexed contentId, address validator, uint256 cap, uint256 multiplierBps);
+    event UserTeamUpdated(address indexed user, bytes32 indexed teamId);
+    event ConsumptionDelegateProposed(address indexed secondary, address indexed primary);
+    event ConsumptionDelegateSet(address indexed secondary, address indexed primary);
+    event RewardsFreezeUpdated(bool frozen);
+    event FrozenPushQueued(address indexed user, bytes32 channelId, uint256 amount);
+    event FrozenPushDropped(address indexed user, bytes32 channelId, uint256 amount);
+    event ChannelBound(bytes32 indexed channelId, uint256 indexed contentId, uint256 subId);
+}
+
+// Staging only diagnostic events
+#[cfg(feature = "debug_events")]
+sol! {
+    event DebugRecoveredSigner(address signer, bool authorized);
+}
+
+// The sensitive config setters, that must go through the timelock once it's set
+sol! {
+    function setChannelDailyCap(uint256 cap);
+    function setConfigTimelock(uint256 delay);
+    function setMaxPushesPerEpoch(uint256 maxPushes);
+    function setCaps(uint256 maxPerPush, uint256 maxUser, uint256 maxTotal, uint256 dailyCap);
+    function setValidationMode(uint8 mode, address platformSigner, address registry);
+    function setMaxConsumptionPerPush(uint256 maxPerPush);
+    function setContentRegistry(address newRegistry);
+    function setValidatorQuorum(uint256 quorum);
+    function setContentTypeMultiplier(uint256 contentTypes, uint256 multiplierBps);
+    function registerChannel(bytes32 channelId, address validator, uint256 cap, uint256 multiplierBps, uint256 contentId, bytes metadata);
+}
+
+/// Length of a consumption day bucket, in seconds
+const SECONDS_PER_DAY: u64 = 86400;
+
+/// The role managing every other role (the owner, at initialization)
+const DEFAULT_ADMIN_ROLE: [u8; 32] = [0u8; 32];
+
+/// The role allowed to pause and unpause the pushes, `keccak256("PAUSER_ROLE")`
+const PAUSER_ROLE: [u8; 32] = [
+    0x65, 0xd7, 0xa2, 0x8e, 0x32, 0x65, 0xb3, 0x7a, 0x64, 0x74, 0x92, 0x9f, 0x33, 0x65, 0x21, 0xb3,
+    0x32, 0xc1, 0x68, 0x1b, 0x93, 0x3f, 0x6c, 0xb9, 0xf3, 0x37, 0x66, 0x73, 0x44, 0x0d, 0x86, 0x2a,
+];
+
+/// The role allowed to point to a new content registry, `keccak256("REGISTRY_ADMIN_ROLE")`
+const REGISTRY_ADMIN_ROLE: [u8; 32] = [
+    0xbb, 0x28, 0xeb, 0x1a, 0x0c, 0xfa, 0xbc, 0xec, 0xf9, 0x60, 0x03, 0xfa, 0xb4, 0x66, 0x15, 0x9b,
+    0xc2, 0xe0, 0x51, 0xe4, 0x9d, 0x79, 0xba, 0xf0, 0x49, 0x89, 0x00, 0x44, 0xe9, 0x07, 0x24, 0x40,
+];
+
+/// The role allowed to correct user totals (resets, decreases and overwrites), `keccak256("CONSUMPTION_ADMIN_ROLE")`
+const CONSUMPTION_ADMIN_ROLE: [u8; 32] = [
+    0x50, 0xd0, 0x58, 0x49, 0x7b, 0x05, 0xe3, 0x2b, 0xaf, 0xca, 0xb6, 0xfa, 0x48, 0x7f, 0x12, 0x10,
+    0xf3, 0x29, 0x04, 0x14, 0xa9, 0x9e, 0x96, 0x58, 0xeb, 0xf6, 0xe8, 0xfd, 0x48, 0xb0, 0x30, 0xb6,
+];
+
+/// The roles the owner starts with, and hands over along with the ownership
+const OWNER_ROLES: [[u8; 32]; 4] = [
+    DEFAULT_ADMIN_ROLE,
+    PAUSER_ROLE,
+    REGISTRY_ADMIN_ROLE,
+    CONSUMPTION_ADMIN_ROLE,
+];
+
+/// The `ValidateConsumption` type hash signed by the validators for a push,
+/// `keccak256("ValidateConsumption(address user,bytes32 channelId,uint256 addedConsumption,uint256 deadline,uint256 nonce)")`
+pub const VALIDATE_CONSUMPTION_TYPE_HASH: [u8; 32] = [
+    0xf2, 0x4e, 0x9e, 0x0e, 0x0f, 0xb6, 0x32, 0xe9, 0x50, 0x33, 0x64, 0x82, 0x1f, 0x36, 0x0b, 0x5d,
+    0xe7, 0xe3, 0x8e, 0x28, 0x90, 0x54, 0x6c, 0x62, 0x11, 0xff, 0xc8, 0x4c, 0x7e, 0xb2, 0xba, 0x16,
+];
+
+/// Basis points denominator (100%)
+const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Fixed point unit of the decay factor computations (18 decimals)
+const WAD: u64 = 1_000_000_000_000_000_000;
+
+/// Validation modes: validators authorized by the content registry, a single platform signer, or a local allowlist
+/// (values are part of the public interface, through `setValidationMode` and `getValidationMode`)
+const VALIDATION_MODE_REGISTRY: u8 = 0;
+const VALIDATION_MODE_SINGLE_SIGNER: u8 = 1;
+const VALIDATION_MODE_ALLOWLIST: u8 = 2;
+
+/// Event modes of the pushes: `CcuPushed` on every push, only on the pushes crossing a milestone, or never
+const EVENT_MODE_EVERY_PUSH: u8 = 0;
+const EVENT_MODE_MILESTONES: u8 = 1;
+const EVENT_MODE_SUPPRESSED: u8 = 2;
+
+/// Policies for the pushes received while the rewards are frozen: rejected, or queued until the unfreeze
+const FREEZE_POLICY_REJECT: u8 = 0;
+const FREEZE_POLICY_QUEUE: u8 = 1;
+
+/// Maximum number of pushes queued during a rewards freeze, bounding the unfreeze iteration
+const MAX_FROZEN_PUSHES: usize = 256;
+
+/// Maximum number of escrowed pushes per user, bounding the confirmation iteration
+const MAX_PENDING_PUSHES: usize = 64;
+
+/// Maximum number of entries a batch view can be queried with
+const MAX_QUERY_LIMIT: usize = 100;
+
+/// Maximum number of epochs an epoch range view can span
+const MAX_EPOCH_RANGE: usize = 100;
+
+/// Maximum number of entries in a multi content push (one bit each in the returned bitmask)
+const MAX_MULTI_CONTENT_ENTRIES: usize = 256;
+
+/// Maximum number of configurable histogram buckets, bounding the per push iteration
+const MAX_HISTOGRAM_BUCKETS: usize = 32;
+
+struct ConsumptionParam;
+
+impl Eip712Params for ConsumptionParam {
+    // Static fields
+    const NAME: &'static str = "ChannelConsumption";
+    const VERSION: &'static str = "0.0.1";
+}
+
+/// The calldata length of the current call, recorded by the entrypoint
+/// (the router only hands the decoded arguments over, see `_msg_sender`)
+static CALLDATA_LEN: AtomicUsize = AtomicUsize::new(0);
+
+/// Route a call to the contract, recording its calldata length first
+#[cfg(not(feature = "mock-registry"))]
+#[entrypoint]
+fn user_main(input: Vec<u8>) -> ArbResult {
+    CALLDATA_LEN.store(input.len(), Ordering::Relaxed);
+    stylus_sdk::abi::router_entrypoint::<ChannelConsumptionContract, ChannelConsumptionContract>(
+        input,
+    )
+}
+
+/// Define the global contract storage
+#[storage]
+pub struct ChannelConsumptionContract {
+    // The user activity storage (user => UserConsumption)
+    user_consumptions: StorageMap<Address, StorageU256>,
+    // Some general configurations
+    initialized: StorageBool,
+    // Whether the consumption pushes are paused
+    paused: StorageBool,
+    // The circuit breaker: the maximum global consumption increase per window (zero to disable), the window
+    // in seconds (zero for a single block), the current window index and increase, and whether it tripped
+    max_global_velocity: StorageU256,
+    global_velocity_window: StorageU256,
+    global_velocity_window_index: StorageU256,
+    global_velocity_window_increase: StorageU256,
+    circuit_breaker_tripped: StorageBool,
+    // The lock held while we're calling out to another contract
+    call_lock: StorageBool,
+    // The minimum gas left a push must start with (zero to disable)
+    min_gas_for_push: StorageU256,
+    // The gas left under which a batch stops, to be resumed in another transaction (zero to disable)
+    batch_gas_reserve: StorageU256,
+    // The maximum entries of a push batch, for relayers to chunk their submissions (zero for no explicit limit)
+    max_batch_size: StorageU256,
+    nutty_content_id: StorageU256,
+    content_registry: StorageAddress,
+    // The validation mode, with the single platform signer and the validators allowlist
+    validation_mode: StorageU8,
+    platform_signer: StorageAddress,
+    validator_allowlist: StorageMap<Address, StorageBool>,
+    // The validators trusted locally in the registry mode, skipping the registry call (on any content)
+    local_validators: StorageMap<Address, StorageBool>,
+    // The validator keys lifetime (validity period, validator => expiry timestamp, zero if untracked)
+    validator_validity_period: StorageU256,
+    validator_expiry: StorageMap<Address, StorageU256>,
+    // The total tracked consumption
+    total_consumption: StorageU256,
+    // Which pushes emit `CcuPushed` (see the event modes)
+    event_mode: StorageU8,
+    // The rewards freeze, its policy, and the pushes queued during it (one entry per index in each vec)
+    rewards_frozen: StorageBool,
+    freeze_policy: StorageU8,
+    frozen_push_users: StorageVec<StorageAddress>,
+    frozen_push_contents: StorageVec<StorageU256>,
+    frozen_push_channels: StorageVec<StorageB256>,
+    frozen_push_amounts: StorageVec<StorageU256>,
+    // The per content consumption (content => user => consumption)
+    content_user_consumptions: StorageMap<U256, StorageMap<Address, StorageU256>>,
+    // The rewards (content => reward per consumption unit, user => accrued rewards)
+    content_reward_rates: StorageMap<U256, StorageU256>,
+    accrued_rewards: StorageMap<Address, StorageU256>,
+    // The claimed rewards, and the resulting eth withdrawable balances (pull payment)
+    claimed_rewards: StorageMap<Address, StorageU256>,
+    withdrawable: StorageMap<Address, StorageU256>,
+    // The consumption caps (zero means uncapped)
+    max_consumption_per_push: StorageU256,
+    // The minimum consumption a single push must add, against dust pushes (zero for none)
+    min_consumption_per_push: StorageU256,
+    max_total_per_user: StorageU256,
+    max_total_consumption: StorageU256,
+    // Whether a push crossing the user cap is clamped to it, rather than rejected
+    clamp_user_cap: StorageBool,
+    // The consumption escrow (user => pending consumption, user => release timestamp)
+    challenge_period: StorageU256,
+    pending_consumptions: StorageMap<Address, StorageU256>,
+    pending_release_at: StorageMap<Address, StorageU256>,
+    total_pending_consumption: StorageU256,
+    // The escrowed pushes of each user, booked per channel and content once confirmed (user => pushes),
+    // and the escrowed consumption of each channel, counted against its cap
+    pending_push_amounts: StorageMap<Address, StorageVec<StorageU256>>,
+    pending_push_channels: StorageMap<Address, StorageVec<StorageB256>>,
+    pending_push_contents: StorageMap<Address, StorageVec<StorageU256>>,
+    pending_push_epochs: StorageMap<Address, StorageVec<StorageU256>>,
+    channel_pending_consumptions: StorageMap<FixedBytes<32>, StorageU256>,
+    // Whether zero consumption pushes are accepted (rejected by default)
+    allow_zero_consumption: StorageBool,
+    // The per channel consumption (channel => consumption across every users)
+    channel_consumptions: StorageMap<FixedBytes<32>, StorageU256>,
+    // The channels that ever received consumption, and their count
+    channel_seen: StorageMap<FixedBytes<32>, StorageBool>,
+    channel_count: StorageU256,
+    // The channel to content bindings, from the canonical derivation (channel => content, channel => bound)
+    enforce_channel_binding: StorageBool,
+    channel_contents: StorageMap<FixedBytes<32>, StorageU256>,
+    channel_bound: StorageMap<FixedBytes<32>, StorageBool>,
+    // The registered channels (channel => registered, validator override, lifetime cap, weight in bps, metadata)
+    require_channel_registration: StorageBool,
+    channel_registered: StorageMap<FixedBytes<32>, StorageBool>,
+    channel_validators: StorageMap<FixedBytes<32>, StorageAddress>,
+    channel_caps: StorageMap<FixedBytes<32>, StorageU256>,
+    channel_multipliers_bps: StorageMap<FixedBytes<32>, StorageU256>,
+    channel_metadata: StorageMap<FixedBytes<32>, StorageBytes>,
+    // Whether pushes are only accepted on contents existing in the registry
+    check_content_existence: StorageBool,
+    // The weight of each content types bitmask, in basis points (zero for unweighted), and how many are weighted
+    content_type_multipliers_bps: StorageMap<U256, StorageU256>,
+    weighted_content_types: StorageU256,
+    // The channels that can never accrue consumption
+    denied_channels: StorageMap<FixedBytes<32>, StorageBool>,
+    // The per channel daily accrual (channel => day index, channel => accrued that day)
+    channel_daily_cap: StorageU256,
+    channel_day_index: StorageMap<FixedBytes<32>, StorageU256>,
+    channel_day_consumption: StorageMap<FixedBytes<32>, StorageU256>,
+    // The channels a user has pushed to (user => channel => seen, user => distinct channels count, user => channels)
+    user_channel_seen: StorageMap<Address, StorageMap<FixedBytes<32>, StorageBool>>,
+    user_channel_count: StorageMap<Address, StorageU256>,
+    user_channels: StorageMap<Address, StorageVec<StorageB256>>,
+    // The per user and channel consumption (user => channel => consumption)
+    user_channel_consumptions: StorageMap<Address, StorageMap<FixedBytes<32>, StorageU256>>,
+    // The last accepted push timestamp (user => timestamp), and the minimum interval between two pushes of a user
+    user_last_push_at: StorageMap<Address, StorageU256>,
+    min_push_interval: StorageU256,
+    // The erc20 reward token, its amount per consumption unit, and the consumption already rewarded per user
+    reward_token: StorageAddress,
+    token_reward_rate: StorageU256,
+    token_rewarded_consumptions: StorageMap<Address, StorageU256>,
+    // Whether the rewarded consumption is burned on claim, so it can't back a future reward program
+    burn_on_claim: StorageBool,
+    // The share of a user's consumption decaying every full day without push, in basis points (zero to disable)
+    decay_rate_bps: StorageU256,
+    // The total consumption step at which a milestone event is emitted (zero to disable)
+    milestone_step: StorageU256,
+    // Whether the migration from a previous contract was finalized (no more imports)
+    migrated: StorageBool,
+    // Every user that ever had a push credited, and whether a user is already in that list
+    users: StorageVec<StorageAddress>,
+    user_indexed: StorageMap<Address, StorageBool>,
+    // The consumption credited per UTC day (user => day index => consumption)
+    user_daily_consumptions: StorageMap<Address, StorageMap<U256, StorageU256>>,
+    // The last accepted push timestamp per channel (user => channel => timestamp), and the minimum interval between
+    // two pushes of a user on the same channel
+    user_channel_last_push_at: StorageMap<Address, StorageMap<FixedBytes<32>, StorageU256>>,
+    min_channel_push_interval: StorageU256,
+    // The last credited push block per channel (user => channel => block number), and whether a second push
+    // of a user on the same channel within a block is rejected
+    user_channel_last_push_block: StorageMap<Address, StorageMap<FixedBytes<32>, StorageU256>>,
+    reject_same_block_pushes: StorageBool,
+    // The push receipts counter, and the last receipt of each user (user => receipt id)
+    receipt_counter: StorageU256,
+    user_last_receipt: StorageMap<Address, StorageU256>,
+    // The epochs, and the per epoch push counts (epoch => user => pushes)
+    // With a non zero duration, epochs advance with time from `current_epoch` at `epoch_start`
+    current_epoch: StorageU256,
+    epoch_duration: StorageU256,
+    epoch_start: StorageU256,
+    max_pushes_per_epoch: StorageU256,
+    epoch_push_counts: StorageMap<U256, StorageMap<Address, StorageU256>>,
+    // The per epoch consumption (epoch => consumption across every users)
+    epoch_totals: StorageMap<U256, StorageU256>,
+    // The committed consumption snapshots merkle roots (snapshot id => root)
+    snapshot_roots: StorageMap<U256, StorageB256>,
+    // The on chain consumption snapshots: last id, total per id, and the lazily recorded user values
+    // (user => snapshot ids, and the consumption they had at each of them)
+    consumption_snapshot_id: StorageU256,
+    consumption_snapshot_totals: StorageMap<U256, StorageU256>,
+    user_snapshot_ids: StorageMap<Address, StorageVec<StorageU256>>,
+    user_snapshot_values: StorageMap<Address, StorageVec<StorageU256>>,
+    // The per validator rate limit (validator => window index, validator => accepted pushes in window)
+    validator_max_pushes: StorageU256,
+    validator_window: StorageU256,
+    validator_window_index: StorageMap<Address, StorageU256>,
+    validator_window_pushes: StorageMap<Address, StorageU256>,
+    // The accepted and silently rejected pushes counters
+    accepted_pushes: StorageU256,
+    rejected_pushes: StorageU256,
+    // The timelocked config changes (id => selector + params, id => execution timestamp)
+    config_timelock: StorageU256,
+    config_change_nonce: StorageU256,
+    queued_config_changes: StorageMap<B256, StorageBytes>,
+    queued_config_etas: StorageMap<B256, StorageU256>,
+    // The per channel push histograms (bucket upper bounds, channel => bucket => pushes, channel => pushes)
+    histogram_bounds: StorageVec<StorageU256>,
+    channel_histograms: StorageMap<FixedBytes<32>, StorageMap<U256, StorageU256>>,
+    channel_histogram_pushes: StorageMap<FixedBytes<32>, StorageU256>,
+    // The velocity anomaly detection (user => first push, user => window index, user => window consumption)
+    velocity_multiple: StorageU256,
+    velocity_window: StorageU256,
+    user_first_push_at: StorageMap<Address, StorageU256>,
+    user_velocity_window_index: StorageMap<Address, StorageU256>,
+    user_velocity_window_consumption: StorageMap<Address, StorageU256>,
+    flagged: StorageMap<Address, StorageBool>,
+    // The maximum plausible consumption per second since a user's last push (zero to disable), and whether
+    // a push above it is rejected rather than only flagged
+    max_consumption_rate: StorageU256,
+    reject_implausible_rate: StorageBool,
+    // The maximum distinct channels a user can push on per UTC day (zero to disable), and whether a push above
+    // it is rejected rather than only flagged, with the daily counts (user => day => count, and channels seen)
+    max_channels_per_window: StorageU256,
+    reject_channel_spread: StorageBool,
+    user_daily_channel_counts: StorageMap<Address, StorageMap<U256, StorageU256>>,
+    user_daily_channel_seen:
+        StorageMap<Address, StorageMap<U256, StorageMap<FixedBytes<32>, StorageBool>>>,
+    // The last cumulative reading per session (user => keccak(channel, session) => cumulative)
+    session_cumulatives: StorageMap<Address, StorageMap<B256, StorageU256>>,
+    // Whether a mismatching validator hint rejects the push
+    strict_validator_hint: StorageBool,
+    // Whether a push from an unauthorized validator reverts, rather than silently succeeding
+    strict_mode: StorageBool,
+    // The number of distinct validators that must sign a multi signature push (zero acts as one)
+    validator_quorum: StorageU256,
+    // Whether a failing registry call is treated as an unauthorized validator (silent) rather than reverting
+    silent_registry_failures: StorageBool,
+    // Whether the validator hint can be a contract signer, checked through EIP-1271
+    allow_contract_validators: StorageBool,
+    // The ERC-2771 forwarder whose calls carry the real sender in the last 20 bytes of calldata
+    trusted_forwarder: StorageAddress,
+    // The push digests already used or cancelled before use (digest => used or cancelled)
+    used_or_cancelled: StorageMap<B256, StorageBool>,
+    // The multi content entries already processed under a signature left valid by the circuit breaker
+    // (digest => bitmask of the processed entries)
+    multi_content_processed: StorageMap<B256, StorageU256>,
+    // The push struct hashes revoked by their user, e.g. after a device theft (struct hash => revoked)
+    revoked_struct_hashes: StorageMap<B256, StorageBool>,
+    // The cohort approvals (cohort struct hash => validator who signed it, keccak(root, leaf) => claimed)
+    cohort_signers: StorageMap<B256, StorageAddress>,
+    claimed_cohort_leaves: StorageMap<B256, StorageBool>,
+    // The processed idempotency keys (epoch => keccak(user, key) => receipt id)
+    idempotency_receipts: StorageMap<U256, StorageMap<B256, StorageU256>>,
+    // The teams (user => team, team => consumption across its members)
+    user_teams: StorageMap<Address, StorageB256>,
+    // The consumption delegations (secondary wallet => primary credited instead), and the ones awaiting
+    // the primary's confirmation (secondary => proposed primary)
+    consumption_delegates: StorageMap<Address, StorageAddress>,
+    pending_consumption_delegates: StorageMap<Address, StorageAddress>,
+    team_consumptions: StorageMap<B256, StorageU256>,
+    // The push nonces (user => next push nonce)
+    nonces: StorageMap<Address, StorageU256>,
+    // The seconds a signature is still accepted past its deadline, tolerating signer clock skew
+    deadline_grace: StorageU256,
+    // The penalty nonces (user => next penalty nonce)
+    penalty_nonces: StorageMap<Address, StorageU256>,
+    // The owner proposed by `transferOwnership`, until they accept it
+    pending_owner: StorageAddress,
+    // The ownable borrowing
+    #[borrow]
+    ownable: Ownable,
+    // The operational roles (pauser, registry admin), managed by the default admin
+    #[borrow]
+    access_control: AccessControl,
+    #[borrow]
+    eip712: Eip712<ConsumptionParam>,
+}
+
+// Implemented by `#[entrypoint]` on a struct, which the entrypoint function above replaces
+unsafe impl TopLevelStorage for ChannelConsumptionContract {}
+
+/// Some internal helpers
+impl ChannelConsumptionContract {
+    /// Ensure we aren't in the middle of an external call
+    /// Views stay callable during it (e.g. from a reward token or hook), only state mutations are blocked
+    pub fn _ensure_unlocked(&self) -> Result<(), Errors> {
+        if self.call_lock.get() {
+            Err(Errors::ReentrantCall(ReentrantCall {}))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Ensure the caller has a role, and that we aren't reentered
+    pub fn _only_role(&self, role: [u8; 32]) -> Result<(), Errors> {
+        self._ensure_unlocked()?;
+        self.access_control
+            ._check_role(B256::from(role), msg::sender())?;
+        Ok(())
+    }
+
+    /// Ensure the caller is the owner, and that we aren't reentered
+    pub fn _only_owner(&self) -> Result<(), Errors> {
+        self._ensure_unlocked()?;
+        self.ownable.only_owner()?;
+        Ok(())
+    }
+
+    /// Resolve the real caller, per ERC-2771: the last 20 bytes of calldata when called by the trusted forwarder
+    /// The calldata is read again at the length recorded by the entrypoint, `read_args` copying all of it
+    pub fn _msg_sender(&self) -> Address {
+        let sender = msg::sender();
+        if sender.is_zero() || sender != self.trusted_forwarder.get() {
+            return sender;
+        }
+        let calldata = contract::args(CALLDATA_LEN.load(Ordering::Relaxed));
+        Self::_forwarded_sender(sender, &calldata)
+    }
+
+    /// Get the real caller of a call relayed by the trusted forwarder: the last 20 bytes of its full calldata
+    /// A calldata too short to carry an address leaves the forwarder as the caller
+    pub fn _forwarded_sender(forwarder: Address, calldata: &[u8]) -> Address {
+        match calldata.len().checked_sub(20) {
+            Some(start) => Address::from_slice(&calldata[start..]),
+            None => forwarder,
+        }
+    }
+
+    /// Ensure the contract is initialized and the eip712 cache warm, before accepting any push
+    pub fn _ensure_initialized(&self) -> Result<(), Errors> {
+        if self.initialized.get() && self.eip712.is_initialized() {
+            Ok(())
+        } else {
+            Err(Errors::NotInitialized(NotInitialized {}))
+        }
+    }
+
+    /// Check if a signature deadline is still valid at a given timestamp
+    /// A signature is invalid from its deadline second on (plus the grace window), and a zero deadline
+    /// is always expired, the grace window never resurrecting it
+    pub fn _is_deadline_valid_at(&self, deadline: U256, timestamp: U256) -> bool {
+        !deadline.is_zero() && timestamp < deadline.saturating_add(self.deadline_grace.get())
+    }
+
+    /// Ensure a signature deadline hasn't passed yet
+    pub fn _ensure_deadline(&self, deadline: U256) -> Result<(), Errors> {
+        if self._is_deadline_valid_at(deadline, U256::from(block::timestamp())) {
+            Ok(())
+        } else {
+            Err(Errors::ExpiredSignature(ExpiredSignature {}))
+        }
+    }
+
+    /// Canonical channel id derivation, `keccak(abi.encode(contentId, subId))`
+    pub fn _compute_channel_id(content_id: U256, sub_id: U256) -> B256 {
+        keccak(<sol! { (uint256, uint256) }>::abi_encode(&(
+            content_id, sub_id,
+        )))
+    }
+}
+
+/// Declare that `ContentConsumptionContract` is a contract with the following external methods.
+#[public]
+#[inherit(Ownable, AccessControl, Eip712<ConsumptionParam>)]
+impl ChannelConsumptionContract {
+    /* -------------------------------------------------------------------------- */
+    /*                                 Constructor                                */
+    /* -------------------------------------------------------------------------- */
+
+    /// Initialize the contract with an owner.
+    /// TODO: No constructor possible atm, so going with init method called during contract creation via multicall
+    /// See: https://github.com/OffchainLabs/stylus-sdk-rs/issues/99
+    #[selector(name = "initialize")]
+    pub fn initialize(
+        &mut self,
+        owner: Address,
+        nutty_content_id: U256,
+        content_registry: Address,
+    ) -> Result<(), Errors> {
+        // Ensure that the contract has not been initialized
+        if self.initialized.get() || !self.ownable.owner().is_zero() {
+            return Err(Errors::AlreadyInitialized(AlreadyInitialized {}));
+        }
+        self.initialized.set(true);
+
+        // Init our owner, who starts with every role (and can hand them out)
+        self.ownable._transfer_ownership(owner);
+        for role in OWNER_ROLES {
+            self.access_control._grant_role(B256::from(role), owner);
+        }
+
+        // Init our global config
+        self.nutty_content_id.set(nutty_content_id);
+        self.content_registry.set(content_registry);
+
+        // Prime the eip712 domain separator cache
+        self.eip712.initialize();
+
+        evm::log(Initialized {
+            owner,
+            nuttyContentId: nutty_content_id,
+            contentRegistry: content_registry,
+        });
+
+        // Return the success
+        Ok(())
+    }
+
+    /// Override the EIP-712 domain name and version (empty strings to fall back to the built in ones)
+    /// Lets the same bytecode be branded per deployment, or its signing version bumped; every pending
+    /// signature is invalidated, as the domain separator changes
+    #[selector(name = "setEip712NameAndVersion")]
+    pub fn set_eip712_name_and_version(
+        &mut self,
+        name: String,
+        version: String,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.eip712.set_name_and_version(&name, &version);
+        Ok(())
+    }
+
+    /// Get the EIP-712 domain name and version in use
+    #[selector(name = "getEip712NameAndVersion")]
+    pub fn get_eip712_name_and_version(&self) -> Result<(String, String), Errors> {
+        Ok(self.eip712.name_and_version())
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                  Ownership                                 */
+    /* -------------------------------------------------------------------------- */
+
+    /// Start an ownership transfer, effective once the new owner calls `acceptOwnership`
+    /// Overrides the one step `Ownable` transfer, so a typo'd address can't take over the admin control
+    /// Proposing the zero address cancels a pending transfer
+    #[selector(name = "transferOwnership")]
+    pub fn transfer_ownership(&mut self, new_owner: Address) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.pending_owner.set(new_owner);
+        evm::log(OwnershipTransferStarted {
+            previousOwner: self.ownable.owner(),
+            newOwner: new_owner,
+        });
+        Ok(())
+    }
+
+    /// Accept a pending ownership transfer (pending owner only)
+    /// The owner roles still held by the previous owner move along; roles handed out to other accounts are kept
+    #[selector(name = "acceptOwnership")]
+    pub fn accept_ownership(&mut self) -> Result<(), Errors> {
+        self._ensure_unlocked()?;
+        let sender = msg::sender();
+        if sender != self.pending_owner.get() {
+            return Err(Errors::Ownable(ownable::Error::UnauthorizedAccount(
+                ownable::OwnableUnauthorizedAccount { account: sender },
+            )));
+        }
+        self.pending_owner.set(Address::ZERO);
+        let previous_owner = self.ownable.owner();
+        self.ownable._transfer_ownership(sender);
+        for role in OWNER_ROLES {
+            let role = B256::from(role);
+            if self.access_control._revoke_role(role, previous_owner) {
+                self.access_control._grant_role(role, sender);
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the owner proposed by a pending ownership transfer (zero if none)
+    #[selector(name = "pendingOwner")]
+    pub fn pending_owner(&self) -> Result<Address, Errors> {
+        Ok(self.pending_owner.get())
+    }
+
+    /// Leave the contract without owner, also dropping any pending ownership transfer
+    #[selector(name = "renounceOwnership")]
+    pub fn renounce_ownership(&mut self) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.pending_owner.set(Address::ZERO);
+        self.ownable._transfer_ownership(Address::ZERO);
+        Ok(())
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                    Pause                                   */
+    /* -------------------------------------------------------------------------- */
+
+    /// Pause every consumption push (views keep working), pauser role only
+    #[selector(name = "pause")]
+    pub fn pause(&mut self) -> Result<(), Errors> {
+        self._only_role(PAUSER_ROLE)?;
+        self.paused.set(true);
+        evm::log(Paused {
+            actor: msg::sender(),
+        });
+        Ok(())
+    }
+
+    /// Resume the consumption pushes, pauser role only (owner only after a circuit breaker trip)
+    #[selector(name = "unpause")]
+    pub fn unpause(&mut self) -> Result<(), Errors> {
+        // Once tripped by the circuit breaker, only the owner can resume
+        if self.circuit_breaker_tripped.get() {
+            self._only_owner()?;
+            self.circuit_breaker_tripped.set(false);
+        } else {
+            self._only_role(PAUSER_ROLE)?;
+        }
+        self.paused.set(false);
+        evm::log(Unpaused {
+            actor: msg::sender(),
+        });
+        Ok(())
+    }
+
+    /// Check if the consumption pushes are paused
+    #[selector(name = "isPaused")]
+    pub fn is_paused(&self) -> Result<bool, Errors> {
+        Ok(self.paused.get())
+    }
+
+    /// Configure the circuit breaker: the maximum global consumption increase per window (zero to disable),
+    /// and the window in seconds (zero for a single block); past it, the contract pauses itself
+    /// The push tripping it is dropped without consuming its signature, so it can be sent again once unpaused
+    #[selector(name = "setCircuitBreaker")]
+    pub fn set_circuit_breaker(&mut self, max_velocity: U256, window: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.max_global_velocity.set(max_velocity);
+        self.global_velocity_window.set(window);
+        Ok(())
+    }
+
+    /// Get the circuit breaker config, and whether it tripped: (max increase, window, tripped)
+    #[selector(name = "getCircuitBreaker")]
+    pub fn get_circuit_breaker(&self) -> Result<(U256, U256, bool), Errors> {
+        Ok((
+            self.max_global_velocity.get(),
+            self.global_velocity_window.get(),
+            self.circuit_breaker_tripped.get(),
+        ))
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                   Config                                   */
+    /* -------------------------------------------------------------------------- */
+
+    /// Set the maximum consumption a channel can accrue per day (zero to disable)
+    /// Sensitive: must be queued via `queueConfigChange` once a config timelock is set
+    #[selector(name = "setChannelDailyCap")]
+    pub fn set_channel_daily_cap(&mut self, cap: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self._ensure_no_timelock()?;
+        self.channel_daily_cap.set(cap);
+        Ok(())
+    }
+
+    /// Set the maximum consumption a single push can add (zero to disable), bounding the damage of a leaked validator key
+    /// Sensitive: must be queued via `queueConfigChange` once a config timelock is set
+    #[selector(name = "setMaxConsumptionPerPush")]
+    pub fn set_max_consumption_per_push(&mut self, max_per_push: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self._ensure_no_timelock()?;
+        self._set_caps(max_per_push, U256::MAX, U256::MAX, U256::MAX);
+        Ok(())
+    }
+
+    /// Set the minimum consumption a single push must add, so dust pushes can't spam the indexers (zero for none)
+    /// Zero pushes stay rejected regardless, unless explicitly allowed
+    #[selector(name = "setMinConsumptionPerPush")]
+    pub fn set_min_consumption_per_push(&mut self, min_per_push: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.min_consumption_per_push.set(min_per_push);
+        Ok(())
+    }
+
+    /// Get the minimum consumption a single push must add
+    #[selector(name = "getMinConsumptionPerPush")]
+    pub fn get_min_consumption_per_push(&self) -> Result<U256, Errors> {
+        Ok(self.min_consumption_per_push.get())
+    }
+
+    /// Set every consumption caps atomically (zero means uncapped)
+    /// Passing `type(uint256).max` for a field leaves that cap unchanged
+    /// Sensitive: must be queued via `queueConfigChange` once a config timelock is set
+    #[selector(name = "setCaps")]
+    pub fn set_caps(
+        &mut self,
+        max_per_push: U256,
+        max_user: U256,
+        max_total: U256,
+        daily_cap: U256,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self._ensure_no_timelock()?;
+        self._set_caps(max_per_push, max_user, max_total, daily_cap);
+        Ok(())
+    }
+
+    /// Get every consumption caps (max per push, max per user, max total, channel daily cap)
+    #[selector(name = "getCaps")]
+    pub fn get_caps(&self) -> Result<(U256, U256, U256, U256), Errors> {
+        Ok((
+            self.max_consumption_per_push.get(),
+            self.max_total_per_user.get(),
+            self.max_total_consumption.get(),
+            self.channel_daily_cap.get(),
+        ))
+    }
+
+    /// Set whether a push crossing the user cap is clamped to it (only the remaining part recorded) or rejected
+    #[selector(name = "setUserCapClamping")]
+    pub fn set_user_cap_clamping(&mut self, clamp: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.clamp_user_cap.set(clamp);
+        Ok(())
+    }
+
+    /// Check if a push crossing the user cap is clamped to it rather than rejected
+    #[selector(name = "isUserCapClamped")]
+    pub fn is_user_cap_clamped(&self) -> Result<bool, Errors> {
+        Ok(self.clamp_user_cap.get())
+    }
+
+    /// Set the minimum gas left a push must start with, so it never runs out of gas halfway (zero to disable)
+    #[selector(name = "setMinGasForPush")]
+    pub fn set_min_gas_for_push(&mut self, min_gas: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.min_gas_for_push.set(min_gas);
+        evm::log(MinGasForPushUpdated { minGas: min_gas });
+        Ok(())
+    }
+
+    /// Set the share of a user's consumption decaying every full day without push, in basis points (zero to disable)
+    /// The decay is written down on the user's next push, see `getUserConsumptionDecayed` for the live value
+    #[selector(name = "setDecayRate")]
+    pub fn set_decay_rate(&mut self, rate_bps: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        if rate_bps > U256::from(BPS_DENOMINATOR) {
+            return Err(Errors::InvalidConfigParams(InvalidConfigParams {}));
+        }
+        self.decay_rate_bps.set(rate_bps);
+        Ok(())
+    }
+
+    /// Get the share of a user's consumption decaying every full day without push, in basis points
+    #[selector(name = "getDecayRate")]
+    pub fn get_decay_rate(&self) -> Result<U256, Errors> {
+        Ok(self.decay_rate_bps.get())
+    }
+
+    /// Set the total consumption step at which a `MilestoneReached` event is emitted (zero to disable)
+    #[selector(name = "setMilestoneStep")]
+    pub fn set_milestone_step(&mut self, step: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.milestone_step.set(step);
+        Ok(())
+    }
+
+    /// Get the total consumption step at which a `MilestoneReached` event is emitted
+    #[selector(name = "getMilestoneStep")]
+    pub fn get_milestone_step(&self) -> Result<U256, Errors> {
+        Ok(self.milestone_step.get())
+    }
+
+    /// Get the minimum gas left a push must start with
+    #[selector(name = "getMinGasForPush")]
+    pub fn get_min_gas_for_push(&self) -> Result<U256, Errors> {
+        Ok(self.min_gas_for_push.get())
+    }
+
+    /// Set the minimum interval between two pushes of a user, in seconds (zero to disable)
+    #[selector(name = "setMinPushInterval")]
+    pub fn set_min_push_interval(&mut self, interval: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.min_push_interval.set(interval);
+        Ok(())
+    }
+
+    /// Get the minimum interval between two pushes of a user, in seconds
+    #[selector(name = "getMinPushInterval")]
+    pub fn get_min_push_interval(&self) -> Result<U256, Errors> {
+        Ok(self.min_push_interval.get())
+    }
+
+    /// Set the minimum interval between two pushes of a user on the same channel, in seconds (zero to disable)
+    /// Playback can't generate consumption faster than real time, so a faster push is rejected
+    #[selector(name = "setMinChannelPushInterval")]
+    pub fn set_min_channel_push_interval(&mut self, interval: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.min_channel_push_interval.set(interval);
+        Ok(())
+    }
+
+    /// Get the minimum interval between two pushes of a user on the same channel, in seconds
+    #[selector(name = "getMinChannelPushInterval")]
+    pub fn get_min_channel_push_interval(&self) -> Result<U256, Errors> {
+        Ok(self.min_channel_push_interval.get())
+    }
+
+    /// Set whether a second push of a user on the same channel within a block is rejected (`DoublePushInBlock`)
+    /// Cheaper than a cooldown to catch replays and bots, but can be left off for high frequency integrations
+    #[selector(name = "setRejectSameBlockPushes")]
+    pub fn set_reject_same_block_pushes(&mut self, reject: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.reject_same_block_pushes.set(reject);
+        Ok(())
+    }
+
+    /// Check if a second push of a user on the same channel within a block is rejected
+    #[selector(name = "isRejectingSameBlockPushes")]
+    pub fn is_rejecting_same_block_pushes(&self) -> Result<bool, Errors> {
+        Ok(self.reject_same_block_pushes.get())
+    }
+
+    /// Atomically switch the validation mode (0: registry, 1: single signer, 2: allowlist) and its addresses
+    /// The registry mode needs a registry (a zero one keeping the current registry), and the single signer mode a platform signer
+    /// Sensitive: must be queued via `queueConfigChange` once a config timelock is set
+    #[selector(name = "setValidationMode")]
+    pub fn set_validation_mode(
+        &mut self,
+        mode: u8,
+        platform_signer: Address,
+        registry: Address,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self._ensure_no_timelock()?;
+        self._set_validation_mode(mode, platform_signer, registry)
+    }
+
+    /// Get the active validation mode, and the address it verifies pushes against
+    /// Modes are stable: 0 registry (the registry address), 1 single signer (the platform signer), 2 allowlist (zero)
+    #[selector(name = "getValidationMode")]
+    pub fn get_validation_mode(&self) -> Result<(u8, Address), Errors> {
+        let mode = self.validation_mode.get().to::<u8>();
+        let primary_address = match mode {
+            VALIDATION_MODE_REGISTRY => self.content_registry.get(),
+            VALIDATION_MODE_SINGLE_SIGNER => self.platform_signer.get(),
+            _ => Address::ZERO,
+        };
+        Ok((mode, primary_address))
+    }
+
+    /// Get the platform signer and the registry, whatever the active validation mode
+    #[selector(name = "getValidationAddresses")]
+    pub fn get_validation_addresses(&self) -> Result<(Address, Address), Errors> {
+        Ok((self.platform_signer.get(), self.content_registry.get()))
+    }
+
+    /// Add or remove a validator from the allowlist (used in the allowlist validation mode)
+    #[selector(name = "setValidatorAllowed")]
+    pub fn set_validator_allowed(
+        &mut self,
+        validator: Address,
+        allowed: bool,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.validator_allowlist.insert(validator, allowed);
+        if allowed {
+            self._activate_validator(validator);
+        }
+        evm::log(ValidatorAllowlistUpdated { validator, allowed });
+        Ok(())
+    }
+
+    /// List a validator locally, so its pushes skip the registry call in the registry mode (e.g. high throughput channels)
+    /// Listed validators are trusted on every content, the unlisted ones still go through the registry
+    #[selector(name = "addValidator")]
+    pub fn add_validator(&mut self, validator: Address) -> Result<(), Errors> {
+        self._only_owner()?;
+        if validator.is_zero() {
+            return Err(Errors::InvalidAddress(InvalidAddress {}));
+        }
+        self.local_validators.insert(validator, true);
+        self._activate_validator(validator);
+        evm::log(LocalValidatorUpdated {
+            validator,
+            listed: true,
+        });
+        Ok(())
+    }
+
+    /// Remove a locally listed validator, its pushes going back through the registry
+    #[selector(name = "removeValidator")]
+    pub fn remove_validator(&mut self, validator: Address) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.local_validators.delete(validator);
+        evm::log(LocalValidatorUpdated {
+            validator,
+            listed: false,
+        });
+        Ok(())
+    }
+
+    /// Check if a validator is listed locally
+    #[selector(name = "isLocalValidator")]
+    pub fn is_local_validator(&self, validator: Address) -> Result<bool, Errors> {
+        Ok(self.local_validators.get(validator))
+    }
+
+    /// Set the validity period given to validator keys when activated (zero to stop tracking new ones)
+    #[selector(name = "setValidatorValidityPeriod")]
+    pub fn set_validator_validity_period(&mut self, period: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.validator_validity_period.set(period);
+        evm::log(ValidatorValidityPeriodUpdated { period });
+        Ok(())
+    }
+
+    /// Get the validity period given to validator keys when activated
+    #[selector(name = "getValidatorValidityPeriod")]
+    pub fn get_validator_validity_period(&self) -> Result<U256, Errors> {
+        Ok(self.validator_validity_period.get())
+    }
+
+    /// (Re)activate a validator key for a full validity period, e.g. one listed by the registry
+    /// Once expired, the key is rejected even if the registry still lists it, forcing a rotation
+    #[selector(name = "activateValidator")]
+    pub fn activate_validator(&mut self, validator: Address) -> Result<(), Errors> {
+        self._only_owner()?;
+        self._activate_validator(validator);
+        Ok(())
+    }
+
+    /// Get the expiry of a validator key (zero if untracked)
+    #[selector(name = "getValidatorExpiry")]
+    pub fn get_validator_expiry(&self, validator: Address) -> Result<U256, Errors> {
+        Ok(self.validator_expiry.get(validator))
+    }
+
+    /// Set the gas left under which a batch stops processing entries, returning where to resume from (zero to disable)
+    #[selector(name = "setBatchGasReserve")]
+    pub fn set_batch_gas_reserve(&mut self, reserve: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.batch_gas_reserve.set(reserve);
+        Ok(())
+    }
+
+    /// Get the gas left under which a batch stops processing entries
+    #[selector(name = "getBatchGasReserve")]
+    pub fn get_batch_gas_reserve(&self) -> Result<U256, Errors> {
+        Ok(self.batch_gas_reserve.get())
+    }
+
+    /// Set the maximum entries of a push batch (`pushCcuBatch`, `pushCcuMultiContent`), zero for no explicit limit
+    #[selector(name = "setMaxBatchSize")]
+    pub fn set_max_batch_size(&mut self, max_batch_size: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.max_batch_size.set(max_batch_size);
+        Ok(())
+    }
+
+    /// Get the maximum entries of a push batch, for relayers to chunk their submissions
+    #[selector(name = "getMaxBatchSize")]
+    pub fn get_max_batch_size(&self) -> Result<U256, Errors> {
+        Ok(self.max_batch_size.get())
+    }
+
+    /// Check if a validator is in the allowlist
+    #[selector(name = "isValidatorAllowed")]
+    pub fn is_validator_allowed(&self, validator: Address) -> Result<bool, Errors> {
+        Ok(self.validator_allowlist.get(validator))
+    }
+
+    /// Set the maximum number of pushes a user can make per epoch (zero to disable)
+    /// Sensitive: must be queued via `queueConfigChange` once a config timelock is set
+    #[selector(name = "setMaxPushesPerEpoch")]
+    pub fn set_max_pushes_per_epoch(&mut self, max_pushes: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self._ensure_no_timelock()?;
+        self.max_pushes_per_epoch.set(max_pushes);
+        Ok(())
+    }
+
+    /// Get the maximum number of pushes a user can make per epoch
+    #[selector(name = "getMaxPushesPerEpoch")]
+    pub fn get_max_pushes_per_epoch(&self) -> Result<U256, Errors> {
+        Ok(self.max_pushes_per_epoch.get())
+    }
+
+    /// Set whether zero consumption pushes are accepted (backward compatibility toggle)
+    #[selector(name = "setAllowZeroConsumption")]
+    pub fn set_allow_zero_consumption(&mut self, allowed: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.allow_zero_consumption.set(allowed);
+        Ok(())
+    }
+
+    /// Check whether zero consumption pushes are accepted
+    #[selector(name = "isZeroConsumptionAllowed")]
+    pub fn is_zero_consumption_allowed(&self) -> Result<bool, Errors> {
+        Ok(self.allow_zero_consumption.get())
+    }
+
+    /// Set whether legacy EIP-155 style signature `v` values are normalized before recovery (see `pushCcuLegacyV`)
+    #[selector(name = "setAllowEip155V")]
+    pub fn set_allow_eip155_v(&mut self, allowed: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.eip712.set_allow_eip155_v(allowed);
+        Ok(())
+    }
+
+    /// Check whether legacy EIP-155 style signature `v` values are accepted
+    #[selector(name = "isEip155VAllowed")]
+    pub fn is_eip155_v_allowed(&self) -> Result<bool, Errors> {
+        Ok(self.eip712.allows_eip155_v())
+    }
+
+    /// Set the maximum number of pushes a single validator can approve per window of `window` seconds
+    /// A zero value on either disables the limit; pushes above it are silently rejected
+    #[selector(name = "setValidatorRateLimit")]
+    pub fn set_validator_rate_limit(
+        &mut self,
+        max_pushes: U256,
+        window: U256,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.validator_max_pushes.set(max_pushes);
+        self.validator_window.set(window);
+        evm::log(ValidatorRateLimitUpdated {
+            maxPushes: max_pushes,
+            window,
+        });
+        Ok(())
+    }
+
+    /// Get the per validator rate limit (max pushes, window in seconds)
+    #[selector(name = "getValidatorRateLimit")]
+    pub fn get_validator_rate_limit(&self) -> Result<(U256, U256), Errors> {
+        Ok((self.validator_max_pushes.get(), self.validator_window.get()))
+    }
+
+    /// Configure the velocity anomaly detection: users are flagged when their consumption within a
+    /// `window` seconds window exceeds `multiple` times their historical average (zero to disable)
+    #[selector(name = "setVelocityAnomalyConfig")]
+    pub fn set_velocity_anomaly_config(
+        &mut self,
+        multiple: U256,
+        window: U256,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.velocity_multiple.set(multiple);
+        self.velocity_window.set(window);
+        evm::log(VelocityAnomalyConfigUpdated { multiple, window });
+        Ok(())
+    }
+
+    /// Configure the maximum plausible consumption per second since a user's last push (zero to disable),
+    /// and whether a push above it is rejected, or only flags the user
+    #[selector(name = "setMaxConsumptionRate")]
+    pub fn set_max_consumption_rate(&mut self, max_rate: U256, reject: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.max_consumption_rate.set(max_rate);
+        self.reject_implausible_rate.set(reject);
+        Ok(())
+    }
+
+    /// Get the maximum plausible consumption per second, and whether a push above it is rejected
+    #[selector(name = "getMaxConsumptionRate")]
+    pub fn get_max_consumption_rate(&self) -> Result<(U256, bool), Errors> {
+        Ok((
+            self.max_consumption_rate.get(),
+            self.reject_implausible_rate.get(),
+        ))
+    }
+
+    /// Configure the maximum distinct channels a user can push on per UTC day (zero to disable),
+    /// and whether a push above it is rejected, or only flags the user
+    #[selector(name = "setMaxChannelsPerWindow")]
+    pub fn set_max_channels_per_window(
+        &mut self,
+        max_channels: U256,
+        reject: bool,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.max_channels_per_window.set(max_channels);
+        self.reject_channel_spread.set(reject);
+        Ok(())
+    }
+
+    /// Get the maximum distinct channels per UTC day, and whether a push above it is rejected
+    #[selector(name = "getMaxChannelsPerWindow")]
+    pub fn get_max_channels_per_window(&self) -> Result<(U256, bool), Errors> {
+        Ok((
+            self.max_channels_per_window.get(),
+            self.reject_channel_spread.get(),
+        ))
+    }
+
+    /// Get the distinct channels a user pushed on during a UTC day (only counted while the check is enabled)
+    #[selector(name = "getUserDailyChannelCount")]
+    pub fn get_user_daily_channel_count(
+        &self,
+        user: Address,
+        day_index: U256,
+    ) -> Result<U256, Errors> {
+        Ok(self.user_daily_channel_counts.getter(user).get(day_index))
+    }
+
+    /// Clear the anomaly flag of a user, once reviewed
+    #[selector(name = "clearFlag")]
+    pub fn clear_flag(&mut self, user: Address) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.flagged.delete(user);
+        evm::log(UserFlagCleared { user });
+        Ok(())
+    }
+
+    /// Get the velocity anomaly detection config (multiple, window in seconds)
+    #[selector(name = "getVelocityAnomalyConfig")]
+    pub fn get_velocity_anomaly_config(&self) -> Result<(U256, U256), Errors> {
+        Ok((self.velocity_multiple.get(), self.velocity_window.get()))
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                   Epochs                                   */
+    /* -------------------------------------------------------------------------- */
+
+    /// Move to the next epoch, resetting the per epoch counters
+    /// With time based epochs, this skips the rest of the running epoch
+    #[selector(name = "advanceEpoch")]
+    pub fn advance_epoch(&mut self) -> Result<U256, Errors> {
+        self._only_owner()?;
+        let epoch = self._current_epoch() + U256::from(1);
+        self.current_epoch.set(epoch);
+        self.epoch_start.set(U256::from(block::timestamp()));
+        evm::log(EpochAdvanced { epoch });
+        Ok(epoch)
+    }
+
+    /// Set the epoch duration in seconds, so epochs advance automatically (zero to go back to manual epochs)
+    /// The running epoch is kept, and the next one starts `duration` seconds from now
+    #[selector(name = "setEpochDuration")]
+    pub fn set_epoch_duration(&mut self, duration: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        let epoch = self._current_epoch();
+        let start = U256::from(block::timestamp());
+        self.current_epoch.set(epoch);
+        self.epoch_start.set(start);
+        self.epoch_duration.set(duration);
+        evm::log(EpochDurationUpdated {
+            duration,
+            startEpoch: epoch,
+            startTimestamp: start,
+        });
+        Ok(())
+    }
+
+    /// Get the epoch config (duration in seconds, zero if manual, epoch at start, start timestamp)
+    #[selector(name = "getEpochConfig")]
+    pub fn get_epoch_config(&self) -> Result<(U256, U256, U256), Errors> {
+        Ok((
+            self.epoch_duration.get(),
+            self.current_epoch.get(),
+            self.epoch_start.get(),
+        ))
+    }
+
+    /// Get the current epoch
+    #[selector(name = "currentEpoch")]
+    pub fn current_epoch(&self) -> Result<U256, Errors> {
+        Ok(self._current_epoch())
+    }
+
+    /// Get the number of pushes a user made during an epoch
+    #[selector(name = "getEpochPushCount")]
+    pub fn get_epoch_push_count(&self, epoch: U256, user: Address) -> Result<U256, Errors> {
+        Ok(self.epoch_push_counts.getter(epoch).get(user))
+    }
+
+    /// Get the consumption pushed during each epoch of an inclusive range (at most `MAX_EPOCH_RANGE` epochs)
+    #[selector(name = "getEpochTotals")]
+    pub fn get_epoch_totals(&self, from_epoch: U256, to_epoch: U256) -> Result<Vec<U256>, Errors> {
+        if from_epoch > to_epoch {
+            return Ok(Vec::new());
+        }
+        if to_epoch - from_epoch >= U256::from(MAX_EPOCH_RANGE) {
+            return Err(Errors::RangeTooLarge(RangeTooLarge {}));
+        }
+        let range_len = (to_epoch - from_epoch).to::<usize>() + 1;
+        Ok((0..range_len)
+            .map(|i| self.epoch_totals.get(from_epoch + U256::from(i)))
+            .collect())
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                   Rewards                                  */
+    /* -------------------------------------------------------------------------- */
+
+    /// Freeze the rewards: no consumption is credited until the unfreeze (pushes rejected or queued per the policy)
+    #[selector(name = "freezeRewards")]
+    pub fn freeze_rewards(&mut self) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.rewards_frozen.set(true);
+        evm::log(RewardsFreezeUpdated { frozen: true });
+        Ok(())
+    }
+
+    /// Unfreeze the rewards, crediting the pushes queued meanwhile
+    /// If the circuit breaker trips on the way, the pushes left stay queued: unfreeze again once unpaused
+    #[selector(name = "unfreezeRewards")]
+    pub fn unfreeze_rewards(&mut self) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.rewards_frozen.set(false);
+        self._credit_frozen_pushes()?;
+        evm::log(RewardsFreezeUpdated { frozen: false });
+        Ok(())
+    }
+
+    /// Set which pushes emit `CcuPushed` (0: every push, 1: only the ones crossing a milestone, 2: none),
+    /// cutting the indexing costs of high frequency channels; the consumption is recorded whatever the mode,
+    /// so with fewer events, consumers must read the state directly
+    #[selector(name = "setEventMode")]
+    pub fn set_event_mode(&mut self, mode: u8) -> Result<(), Errors> {
+        self._only_owner()?;
+        if mode != EVENT_MODE_EVERY_PUSH
+            && mode != EVENT_MODE_MILESTONES
+            && mode != EVENT_MODE_SUPPRESSED
+        {
+            return Err(Errors::InvalidConfigParams(InvalidConfigParams {}));
+        }
+        self.event_mode.set(U8::from(mode));
+        Ok(())
+    }
+
+    /// Get which pushes emit `CcuPushed`
+    #[selector(name = "getEventMode")]
+    pub fn get_event_mode(&self) -> Result<u8, Errors> {
+        Ok(self.event_mode.get().to::<u8>())
+    }
+
+    /// Set the policy for the pushes received during a rewards freeze (0: reject, 1: queue)
+    #[selector(name = "setFreezePolicy")]
+    pub fn set_freeze_policy(&mut self, policy: u8) -> Result<(), Errors> {
+        self._only_owner()?;
+        if policy != FREEZE_POLICY_REJECT && policy != FREEZE_POLICY_QUEUE {
+            return Err(Errors::InvalidConfigParams(InvalidConfigParams {}));
+        }
+        self.freeze_policy.set(U8::from(policy));
+        Ok(())
+    }
+
+    /// Get the rewards freeze state (frozen, policy, queued pushes)
+    #[selector(name = "getRewardsFreeze")]
+    pub fn get_rewards_freeze(&self) -> Result<(bool, u8, U256), Errors> {
+        Ok((
+            self.rewards_frozen.get(),
+            self.freeze_policy.get().to::<u8>(),
+            U256::from(self.frozen_push_users.len()),
+        ))
+    }
+
+    /// Set the reward accrued per consumption unit on a content
+    /// Only applies to the consumption credited after the update
+    #[selector(name = "setContentRewardRate")]
+    pub fn set_content_reward_rate(&mut self, content_id: U256, rate: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.content_reward_rates.insert(content_id, rate);
+        evm::log(ContentRewardRateUpdated {
+            contentId: content_id,
+            rate,
+        });
+        Ok(())
+    }
+
+    /// Get the reward accrued per consumption unit on a content
+    #[selector(name = "getContentRewardRate")]
+    pub fn get_content_reward_rate(&self, content_id: U256) -> Result<U256, Errors> {
+        Ok(self.content_reward_rates.get(content_id))
+    }
+
+    /// Get the rewards accrued by a user across every content
+    #[selector(name = "getAccruedRewards")]
+    pub fn get_accrued_rewards(&self, user: Address) -> Result<U256, Errors> {
+        Ok(self.accrued_rewards.get(user))
+    }
+
+    /// Fund the eth rewards pool
+    #[payable]
+    #[selector(name = "fundRewards")]
+    pub fn fund_rewards(&mut self) -> Result<(), Errors> {
+        evm::log(RewardsFunded {
+            funder: msg::sender(),
+            amount: msg::value(),
+        });
+        Ok(())
+    }
+
+    /// Claim the rewards accrued by the caller, crediting them to their eth withdrawable balance
+    #[selector(name = "claimRewards")]
+    pub fn claim_rewards(&mut self) -> Result<U256, Errors> {
+        self._claim_rewards()
+    }
+
+    /// Withdraw the caller's eth withdrawable balance
+    /// Pull payment, so a reverting recipient only ever blocks its own withdrawal
+    #[selector(name = "withdraw")]
+    pub fn withdraw(&mut self) -> Result<(), Errors> {
+        self._withdraw()
+    }
+
+    /// Get the eth withdrawable balance of a user
+    #[selector(name = "getWithdrawable")]
+    pub fn get_withdrawable(&self, user: Address) -> Result<U256, Errors> {
+        Ok(self.withdrawable.get(user))
+    }
+
+    /// Set the erc20 reward token, and the amount of it rewarded per consumption unit
+    /// Only the consumption not yet rewarded is claimable, a rate change applies to it as a whole
+    #[selector(name = "setTokenRewards")]
+    pub fn set_token_rewards(&mut self, token: Address, rate: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.reward_token.set(token);
+        self.token_reward_rate.set(rate);
+        evm::log(TokenRewardsConfigured { token, rate });
+        Ok(())
+    }
+
+    /// Get the erc20 reward token, and the amount of it rewarded per consumption unit
+    #[selector(name = "getTokenRewards")]
+    pub fn get_token_rewards(&self) -> Result<(Address, U256), Errors> {
+        Ok((self.reward_token.get(), self.token_reward_rate.get()))
+    }
+
+    /// Set whether the rewarded consumption is burned on claim (removed from the user and global totals),
+    /// rather than kept and only marked as rewarded
+    #[selector(name = "setBurnOnClaim")]
+    pub fn set_burn_on_claim(&mut self, burn: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.burn_on_claim.set(burn);
+        Ok(())
+    }
+
+    /// Check if the rewarded consumption is burned on claim
+    #[selector(name = "isBurnOnClaim")]
+    pub fn is_burn_on_claim(&self) -> Result<bool, Errors> {
+        Ok(self.burn_on_claim.get())
+    }
+
+    /// Get the erc20 rewards a user could claim right now
+    #[selector(name = "getClaimableTokenRewards")]
+    pub fn get_claimable_token_rewards(&self, user: Address) -> Result<U256, Errors> {
+        let unrewarded = self
+            .user_consumptions
+            .get(user)
+            .saturating_sub(self.token_rewarded_consumptions.get(user));
+        Ok(unrewarded.saturating_mul(self.token_reward_rate.get()))
+    }
+
+    /// Claim the erc20 rewards of the caller's consumption not yet rewarded, at the current rate
+    /// The consumption is marked as rewarded before the transfer, which reverts the whole claim if it fails
+    #[selector(name = "claimTokenRewards")]
+    pub fn claim_token_rewards(&mut self) -> Result<U256, Errors> {
+        self._claim_token_rewards()
+    }
+
+    /// Get the rewards already claimed by a user
+    #[selector(name = "getClaimedRewards")]
+    pub fn get_claimed_rewards(&self, user: Address) -> Result<U256, Errors> {
+        Ok(self.claimed_rewards.get(user))
+    }
+
+    /// Get the consumption of a user on a given content
+    #[selector(name = "getUserContentConsumption")]
+    pub fn get_user_content_consumption(
+        &self,
+        content_id: U256,
+        user: Address,
+    ) -> Result<U256, Errors> {
+        Ok(self.content_user_consumptions.getter(content_id).get(user))
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                  Channels                                  */
+    /* -------------------------------------------------------------------------- */
+
+    /// Deny (or allow back) a channel, denied channels rejecting every push
+    #[selector(name = "setChannelDenied")]
+    pub fn set_channel_denied(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        denied: bool,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.denied_channels.insert(channel_id, denied);
+        evm::log(ChannelDeniedUpdated {
+            channelId: channel_id,
+            denied,
+        });
+        Ok(())
+    }
+
+    /// Compute the canonical id of a channel, `keccak(abi.encode(contentId, subId))`
+    /// Every client should derive its channel ids this way so consumption never splits
+    #[selector(name = "computeChannelId")]
+    pub fn compute_channel_id(
+        &self,
+        content_id: U256,
+        sub_id: U256,
+    ) -> Result<FixedBytes<32>, Errors> {
+        Ok(Self::_compute_channel_id(content_id, sub_id))
+    }
+
+    /// Register a channel with its whole config at once: validator override (zero for the default validation),
+    /// lifetime cap (zero for none), weight in basis points (zero for unweighted), content id and metadata
+    /// Sensitive: must be queued via `queueConfigChange` once a config timelock is set
+    #[selector(name = "registerChannel")]
+    pub fn register_channel(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        validator: Address,
+        cap: U256,
+        multiplier_bps: U256,
+        content_id: U256,
+        metadata: Bytes,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self._ensure_no_timelock()?;
+        self._register_channel(
+            channel_id,
+            validator,
+            cap,
+            multiplier_bps,
+            content_id,
+            &metadata,
+        );
+        Ok(())
+    }
+
+    /// Set whether pushes are only accepted on contents existing in the registry
+    /// Costs an extra registry call per push, so it's opt in
+    #[selector(name = "setCheckContentExistence")]
+    pub fn set_check_content_existence(&mut self, checked: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.check_content_existence.set(checked);
+        Ok(())
+    }
+
+    /// Check if pushes are only accepted on contents existing in the registry
+    #[selector(name = "isContentExistenceChecked")]
+    pub fn is_content_existence_checked(&self) -> Result<bool, Errors> {
+        Ok(self.check_content_existence.get())
+    }
+
+    /// Set the weight of a content types bitmask, in basis points (zero for unweighted, 20000 for 2x)
+    /// Applied on top of the channel weight, to every push on a content with exactly these types
+    /// Sensitive: must be queued via `queueConfigChange` once a config timelock is set
+    #[selector(name = "setContentTypeMultiplier")]
+    pub fn set_content_type_multiplier(
+        &mut self,
+        content_types: U256,
+        multiplier_bps: U256,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self._ensure_no_timelock()?;
+        self._set_content_type_multiplier(content_types, multiplier_bps);
+        Ok(())
+    }
+
+    /// Get the weight of a content types bitmask, in basis points (zero for unweighted)
+    #[selector(name = "getContentTypeMultiplier")]
+    pub fn get_content_type_multiplier(&self, content_types: U256) -> Result<U256, Errors> {
+        Ok(self.content_type_multipliers_bps.get(content_types))
+    }
+
+    /// Get the registration of a channel (registered, validator override, cap, weight in bps, content id, metadata)
+    #[selector(name = "getChannelRegistration")]
+    pub fn get_channel_registration(
+        &self,
+        channel_id: FixedBytes<32>,
+    ) -> Result<(bool, Address, U256, U256, U256, Bytes), Errors> {
+        Ok((
+            self.channel_registered.get(channel_id),
+            self.channel_validators.get(channel_id),
+            self.channel_caps.get(channel_id),
+            self.channel_multipliers_bps.get(channel_id),
+            self.channel_contents.get(channel_id),
+            self.channel_metadata.getter(channel_id).get_bytes().into(),
+        ))
+    }
+
+    /// Set whether pushes are only accepted on registered channels
+    #[selector(name = "setRequireChannelRegistration")]
+    pub fn set_require_channel_registration(&mut self, required: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.require_channel_registration.set(required);
+        Ok(())
+    }
+
+    /// Check if pushes are only accepted on registered channels
+    #[selector(name = "isChannelRegistrationRequired")]
+    pub fn is_channel_registration_required(&self) -> Result<bool, Errors> {
+        Ok(self.require_channel_registration.get())
+    }
+
+    /// Bind a channel to its content, from the canonical `computeChannelId` derivation (permissionless)
+    /// Since the channel id is the hash of the content id, a channel can't be bound to a forged content
+    #[selector(name = "bindChannel")]
+    pub fn bind_channel(
+        &mut self,
+        content_id: U256,
+        sub_id: U256,
+    ) -> Result<FixedBytes<32>, Errors> {
+        self._ensure_unlocked()?;
+        let channel_id = Self::_compute_channel_id(content_id, sub_id);
+        self.channel_contents.insert(channel_id, content_id);
+        self.channel_bound.insert(channel_id, true);
+        evm::log(ChannelBound {
+            channelId: channel_id,
+            contentId: content_id,
+            subId: sub_id,
+        });
+        Ok(channel_id)
+    }
+
+    /// Get the content a channel is bound to (and whether it's bound at all)
+    #[selector(name = "getChannelContent")]
+    pub fn get_channel_content(&self, channel_id: FixedBytes<32>) -> Result<(U256, bool), Errors> {
+        Ok((
+            self.channel_contents.get(channel_id),
+            self.channel_bound.get(channel_id),
+        ))
+    }
+
+    /// Set whether pushes must target a channel bound to the pushed content
+    #[selector(name = "setEnforceChannelBinding")]
+    pub fn set_enforce_channel_binding(&mut self, enforced: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.enforce_channel_binding.set(enforced);
+        Ok(())
+    }
+
+    /// Check if pushes must target a channel bound to the pushed content
+    #[selector(name = "isChannelBindingEnforced")]
+    pub fn is_channel_binding_enforced(&self) -> Result<bool, Errors> {
+        Ok(self.enforce_channel_binding.get())
+    }
+
+    /// Check if a channel is denied
+    #[selector(name = "isChannelDenied")]
+    pub fn is_channel_denied(&self, channel_id: FixedBytes<32>) -> Result<bool, Errors> {
+        Ok(self.denied_channels.get(channel_id))
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                 Histograms                                 */
+    /* -------------------------------------------------------------------------- */
+
+    /// Set the channel histogram buckets, as strictly ascending upper bounds (empty to disable)
+    /// A last, implicit, overflow bucket receives every push above the highest bound
+    /// Existing histograms aren't rebuilt, so buckets should only be set before the tracking starts
+    #[selector(name = "setHistogramBuckets")]
+    pub fn set_histogram_buckets(&mut self, upper_bounds: Vec<U256>) -> Result<(), Errors> {
+        self._only_owner()?;
+
+        // Ensure the buckets are bounded and strictly ascending
+        if upper_bounds.len() > MAX_HISTOGRAM_BUCKETS
+            || upper_bounds.windows(2).any(|pair| pair[0] >= pair[1])
+        {
+            return Err(Errors::InvalidHistogramBuckets(InvalidHistogramBuckets {}));
+        }
+
+        // Replace the current buckets
+        self.histogram_bounds.truncate(0);
+        for bound in upper_bounds.iter() {
+            self.histogram_bounds.push(*bound);
+        }
+
+        evm::log(HistogramBucketsUpdated {
+            upperBounds: upper_bounds,
+        });
+        Ok(())
+    }
+
+    /// Get the channel histogram buckets upper bounds
+    #[selector(name = "getHistogramBuckets")]
+    pub fn get_histogram_buckets(&self) -> Result<Vec<U256>, Errors> {
+        Ok((0..self.histogram_bounds.len())
+            .filter_map(|i| self.histogram_bounds.get(i))
+            .collect())
+    }
+
+    /// Get the number of pushes of a channel that landed in a given bucket
+    #[selector(name = "getChannelBucketCount")]
+    pub fn get_channel_bucket_count(
+        &self,
+        channel_id: FixedBytes<32>,
+        bucket: U256,
+    ) -> Result<U256, Errors> {
+        Ok(self.channel_histograms.getter(channel_id).get(bucket))
+    }
+
+    /// Get the index of the bucket containing the median push of a channel
+    /// This is an approximation: the median is only known up to its bucket bounds,
+    /// so the precision depends entirely on the configured buckets granularity
+    #[selector(name = "getChannelMedianBucket")]
+    pub fn get_channel_median_bucket(&self, channel_id: FixedBytes<32>) -> Result<U256, Errors> {
+        let pushes = self.channel_histogram_pushes.get(channel_id);
+        if pushes.is_zero() {
+            return Ok(U256::ZERO);
+        }
+
+        // Walk the buckets until we reach half of the pushes
+        let histogram = self.channel_histograms.getter(channel_id);
+        let mut cumulated = U256::ZERO;
+        for bucket in 0..=self.histogram_bounds.len() {
+            let bucket = U256::from(bucket);
+            cumulated += histogram.get(bucket);
+            if cumulated * U256::from(2) >= pushes {
+                return Ok(bucket);
+            }
+        }
+        Ok(U256::from(self.histogram_bounds.len()))
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                  Snapshots                                 */
+    /* -------------------------------------------------------------------------- */
+
+    /// Commit the merkle root of a consumption snapshot (leaves are `(user, amount)`)
+    #[selector(name = "commitSnapshotRoot")]
+    pub fn commit_snapshot_root(
+        &mut self,
+        snapshot_id: U256,
+        root: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.snapshot_roots.insert(snapshot_id, root);
+        evm::log(SnapshotRootCommitted {
+            snapshotId: snapshot_id,
+            root,
+        });
+        Ok(())
+    }
+
+    /// Get the committed merkle root of a consumption snapshot
+    #[selector(name = "getSnapshotRoot")]
+    pub fn get_snapshot_root(&self, snapshot_id: U256) -> Result<FixedBytes<32>, Errors> {
+        Ok(self.snapshot_roots.get(snapshot_id))
+    }
+
+    /// Take an on chain snapshot of the consumption (e.g. at a reward epoch boundary), returning its id
+    /// The total is recorded right away, the user values lazily on their next change
+    #[selector(name = "snapshot")]
+    pub fn snapshot(&mut self) -> Result<U256, Errors> {
+        self._only_owner()?;
+        let id = self.consumption_snapshot_id.get() + U256::from(1);
+        self.consumption_snapshot_id.set(id);
+        let total_consumption = self.total_consumption.get();
+        self.consumption_snapshot_totals
+            .insert(id, total_consumption);
+        evm::log(ConsumptionSnapshot {
+            id,
+            totalConsumption: total_consumption,
+        });
+        Ok(id)
+    }
+
+    /// Get the id of the last on chain consumption snapshot (zero if none)
+    #[selector(name = "getCurrentSnapshotId")]
+    pub fn get_current_snapshot_id(&self) -> Result<U256, Errors> {
+        Ok(self.consumption_snapshot_id.get())
+    }
+
+    /// Get the total consumption at an on chain snapshot
+    #[selector(name = "getSnapshotTotal")]
+    pub fn get_snapshot_total(&self, id: U256) -> Result<U256, Errors> {
+        self._ensure_snapshot_id(id)?;
+        Ok(self.consumption_snapshot_totals.get(id))
+    }
+
+    /// Get the consumption of a user at an on chain snapshot
+    #[selector(name = "getUserConsumptionAt")]
+    pub fn get_user_consumption_at(&self, user: Address, id: U256) -> Result<U256, Errors> {
+        self._ensure_snapshot_id(id)?;
+        Ok(self._user_consumption_at(user, id))
+    }
+
+    /// Verify a user consumption in two snapshots, and return the consumption accrued in between
+    #[selector(name = "verifyConsumptionDelta")]
+    pub fn verify_consumption_delta(
+        &self,
+        user: Address,
+        amount_a: U256,
+        proof_a: Vec<FixedBytes<32>>,
+        amount_b: U256,
+        proof_b: Vec<FixedBytes<32>>,
+        snap_a: U256,
+        snap_b: U256,
+    ) -> Result<U256, Errors> {
+        self._verify_snapshot_leaf(snap_a, user, amount_a, &proof_a)?;
+        self._verify_snapshot_leaf(snap_b, user, amount_b, &proof_b)?;
+        amount_b
+            .checked_sub(amount_a)
+            .ok_or(Errors::InvalidConsumptionDelta(InvalidConsumptionDelta {}))
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                              Config timelock                               */
+    /* -------------------------------------------------------------------------- */
+
+    /// Set the delay, in seconds, applied to sensitive config changes (zero to disable)
+    /// Once set, changing it must itself go through the timelock
+    #[selector(name = "setConfigTimelock")]
+    pub fn set_config_timelock(&mut self, delay: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self._ensure_no_timelock()?;
+        self.config_timelock.set(delay);
+        Ok(())
+    }
+
+    /// Get the delay applied to sensitive config changes
+    #[selector(name = "getConfigTimelock")]
+    pub fn get_config_timelock(&self) -> Result<U256, Errors> {
+        Ok(self.config_timelock.get())
+    }
+
+    /// Queue a sensitive config change, executable once the timelock elapsed
+    /// `params` are the abi encoded arguments of the setter identified by `selector`
+    #[selector(name = "queueConfigChange")]
+    pub fn queue_config_change(
+        &mut self,
+        selector: FixedBytes<4>,
+        params: Bytes,
+    ) -> Result<FixedBytes<32>, Errors> {
+        self._queue_config_change(selector, params)
+    }
+
+    /// Execute a queued config change whose timelock elapsed
+    #[selector(name = "executeConfigChange")]
+    pub fn execute_config_change(&mut self, id: FixedBytes<32>) -> Result<(), Errors> {
+        self._execute_config_change(id)
+    }
+
+    /// Get the timestamp from which a queued config change can be executed (zero if not queued)
+    #[selector(name = "getConfigChangeEta")]
+    pub fn get_config_change_eta(&self, id: FixedBytes<32>) -> Result<U256, Errors> {
+        Ok(self.queued_config_etas.get(id))
+    }
+
+    /// Get the maximum consumption a channel can accrue per day
+    #[selector(name = "getChannelDailyCap")]
+    pub fn get_channel_daily_cap(&self) -> Result<U256, Errors> {
+        Ok(self.channel_daily_cap.get())
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                  CCU push                                  */
+    /* -------------------------------------------------------------------------- */
+
+    /// Push a new consumption for a given platform
+    /// The validator signs a `ValidateConsumption` EIP-712 struct, bound to the user's nonce and the deadline;
+    /// once the signer is accepted, the amount is checked against the per push cap (`ConsumptionTooLarge`)
+    /// and the other consumption limits before being credited
+    #[selector(name = "pushCcu")]
+    pub fn push_ccu(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        let user = self._msg_sender();
+        self._push_ccu(
+            user,
+            channel_id,
+            added_consumption,
+            deadline,
+            Address::ZERO,
+            v,
+            r,
+            s,
+        )?;
+        Ok(())
+    }
+
+    /// Same as `pushCcu`, returning whether the consumption was recorded (`false` when the validator was silently
+    /// rejected, or the push dropped by the circuit breaker, its signature staying valid) and the user's total
+    /// consumption, so integrations can assert the outcome without parsing logs
+    #[selector(name = "pushCcuChecked")]
+    pub fn push_ccu_checked(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(bool, U256), Errors> {
+        let user = msg::sender();
+        let recorded = self._push_ccu(
+            user,
+            channel_id,
+            added_consumption,
+            deadline,
+            Address::ZERO,
+            v,
+            r,
+            s,
+        )?;
+        let user = self._consumption_delegate_of(user);
+        Ok((recorded, self.user_consumptions.get(user)))
+    }
+
+    /// Push a new consumption on behalf of a user (e.g. by a gas paying relayer)
+    /// The validator signature binds the user, whoever submits it, so the relayer can't redirect the consumption
+    #[selector(name = "pushCcuFor")]
+    pub fn push_ccu_for(
+        &mut self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._push_ccu(
+            user,
+            channel_id,
+            added_consumption,
+            deadline,
+            Address::ZERO,
+            v,
+            r,
+            s,
+        )?;
+        Ok(())
+    }
+
+    /// Push a new consumption signed by a quorum of distinct validators, all over the same `pushCcu` struct hash
+    /// Any signer refused as a single signer push would be (missing roles, channel override, expired key, rate
+    /// limit) rejects the push, while a repeated signer only counts once; each signer is logged in `CcuPushedBy`
+    #[selector(name = "pushCcuMultiSig")]
+    pub fn push_ccu_multi_sig(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        vs: Vec<u8>,
+        rs: Vec<FixedBytes<32>>,
+        ss: Vec<FixedBytes<32>>,
+    ) -> Result<(), Errors> {
+        self._push_ccu_multi_sig(channel_id, added_consumption, deadline, vs, rs, ss)
+    }
+
+    /// Same as `pushCcu`, with a packed 65 bytes `r || s || v` signature (`v` as 27 / 28 or 0 / 1)
+    #[selector(name = "pushCcuPacked")]
+    pub fn push_ccu_packed(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        signature: Bytes,
+    ) -> Result<(), Errors> {
+        let (v, r, s) = split_packed(&signature)
+            .ok_or(Errors::InvalidSignatureLength(InvalidSignatureLength {}))?;
+        self._push_ccu(
+            msg::sender(),
+            channel_id,
+            added_consumption,
+            deadline,
+            Address::ZERO,
+            v,
+            FixedBytes::from(r),
+            FixedBytes::from(s),
+        )?;
+        Ok(())
+    }
+
+    /// Same as `pushCcu`, with an EIP-2098 compact `(r, vs)` signature
+    /// Compact signatures only exist for low `s`, which the recovery enforces anyway
+    #[selector(name = "pushCcuCompact")]
+    pub fn push_ccu_compact(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        r: FixedBytes<32>,
+        vs: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        let (v, s) = split_compact_vs(&vs.0);
+        self._push_ccu(
+            msg::sender(),
+            channel_id,
+            added_consumption,
+            deadline,
+            Address::ZERO,
+            v,
+            r,
+            FixedBytes::from(s),
+        )?;
+        Ok(())
+    }
+
+    /// Same as `pushCcu`, with a raw `uint256` `v` for legacy clients producing EIP-155 style ones
+    /// (`chainId * 2 + 35 + recovery`), which don't fit a `uint8` beyond chain id 110
+    /// They're only normalized once allowed (`setAllowEip155V`), and if they embed the current chain id
+    #[selector(name = "pushCcuLegacyV")]
+    pub fn push_ccu_legacy_v(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        v: U256,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        let v = self.eip712.normalize_eip155_v(v)?;
+        let user = self._msg_sender();
+        self._push_ccu(
+            user,
+            channel_id,
+            added_consumption,
+            deadline,
+            Address::ZERO,
+            v,
+            r,
+            s,
+        )?;
+        Ok(())
+    }
+
+    /// Push a new consumption, with a hint of the validator expected to have signed it
+    /// A mismatching signer is rejected right away (before the registry call) in strict hint mode,
+    /// otherwise the hint is advisory, only surfaced through a `ValidatorHintMismatched` event
+    #[selector(name = "pushCcuWithHint")]
+    pub fn push_ccu_with_hint(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        expected_validator: Address,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._push_ccu(
+            msg::sender(),
+            channel_id,
+            added_consumption,
+            deadline,
+            expected_validator,
+            v,
+            r,
+            s,
+        )?;
+        Ok(())
+    }
+
+    /// Push several consumptions at once, each entry verified like a `pushCcu` one
+    /// Entries are signed with consecutive nonces, one per accepted entry
+    /// By default, an entry with an invalid or unauthorized signature is skipped (see the returned flags),
+    /// with `allOrNothing` it reverts the whole batch; any other failure (caps, denied channel...) always reverts it
+    /// Stops early once under the batch gas reserve, or at an entry dropped by the circuit breaker, returning
+    /// (flags, processed up to, done) so the caller can resume from the first unprocessed entry
+    #[selector(name = "pushCcuBatch")]
+    pub fn push_ccu_batch(
+        &mut self,
+        channel_ids: Vec<FixedBytes<32>>,
+        added_consumptions: Vec<U256>,
+        deadlines: Vec<U256>,
+        vs: Vec<u8>,
+        rs: Vec<FixedBytes<32>>,
+        ss: Vec<FixedBytes<32>>,
+        all_or_nothing: bool,
+    ) -> Result<(Vec<bool>, U256, bool), Errors> {
+        self._push_ccu_batch(
+            channel_ids,
+            added_consumptions,
+            deadlines,
+            vs,
+            rs,
+            ss,
+            all_or_nothing,
+        )
+    }
+
+    /// Set whether a push from an unauthorized validator reverts with `InvalidPlatformSignature` (strict),
+    /// or silently succeeds without crediting anything (the default)
+    /// The silent no op doesn't tell an attacker probing keys why a push failed, but a failed relayer submission
+    /// then pays gas while looking successful; strict mode trades that information leak for a clear failure
+    #[selector(name = "setStrictMode")]
+    pub fn set_strict_mode(&mut self, strict: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.strict_mode.set(strict);
+        Ok(())
+    }
+
+    /// Check if a push from an unauthorized validator reverts
+    #[selector(name = "isStrictMode")]
+    pub fn is_strict_mode(&self) -> Result<bool, Errors> {
+        Ok(self.strict_mode.get())
+    }
+
+    /// Set whether a mismatching validator hint rejects the push (staging) or is only advisory (production)
+    #[selector(name = "setStrictValidatorHint")]
+    pub fn set_strict_validator_hint(&mut self, strict: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.strict_validator_hint.set(strict);
+        Ok(())
+    }
+
+    /// Check if a mismatching validator hint rejects the push
+    #[selector(name = "isStrictValidatorHint")]
+    pub fn is_strict_validator_hint(&self) -> Result<bool, Errors> {
+        Ok(self.strict_validator_hint.get())
+    }
+
+    /// Set whether a failing registry call is treated as an unauthorized validator, silently skipping the push,
+    /// rather than reverting with `CallError` (easier to debug a misconfigured registry)
+    #[selector(name = "setSilentRegistryFailures")]
+    pub fn set_silent_registry_failures(&mut self, silent: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.silent_registry_failures.set(silent);
+        Ok(())
+    }
+
+    /// Check if a failing registry call is treated as an unauthorized validator
+    #[selector(name = "areRegistryFailuresSilent")]
+    pub fn are_registry_failures_silent(&self) -> Result<bool, Errors> {
+        Ok(self.silent_registry_failures.get())
+    }
+
+    /// Set the number of distinct validators that must sign a `pushCcuMultiSig` push (at least one)
+    /// Sensitive: must be queued via `queueConfigChange` once a config timelock is set
+    #[selector(name = "setValidatorQuorum")]
+    pub fn set_validator_quorum(&mut self, quorum: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self._ensure_no_timelock()?;
+        self._set_validator_quorum(quorum)
+    }
+
+    /// Get the number of distinct validators that must sign a `pushCcuMultiSig` push
+    #[selector(name = "getValidatorQuorum")]
+    pub fn get_validator_quorum(&self) -> Result<U256, Errors> {
+        Ok(self.validator_quorum.get().max(U256::from(1)))
+    }
+
+    /// Set whether the validator hint of a push can be a contract (e.g. a multisig), validating through EIP-1271
+    /// The contract still needs the validator roles, like any other signer
+    #[selector(name = "setContractValidatorsAllowed")]
+    pub fn set_contract_validators_allowed(&mut self, allowed: bool) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.allow_contract_validators.set(allowed);
+        Ok(())
+    }
+
+    /// Check if the validator hint of a push can be a contract
+    #[selector(name = "areContractValidatorsAllowed")]
+    pub fn are_contract_validators_allowed(&self) -> Result<bool, Errors> {
+        Ok(self.allow_contract_validators.get())
+    }
+
+    /// Set the ERC-2771 trusted forwarder, relaying pushes on behalf of users (zero to disable)
+    #[selector(name = "setTrustedForwarder")]
+    pub fn set_trusted_forwarder(&mut self, forwarder: Address) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.trusted_forwarder.set(forwarder);
+        Ok(())
+    }
+
+    /// Check if an address is the ERC-2771 trusted forwarder
+    #[selector(name = "isTrustedForwarder")]
+    pub fn is_trusted_forwarder(&self, forwarder: Address) -> Result<bool, Errors> {
+        Ok(!forwarder.is_zero() && forwarder == self.trusted_forwarder.get())
+    }
+
+    /// Cancel an outstanding push signature, before the user submits it (owner or validator only)
+    /// Unlike a nonce bump, only this very signature is invalidated
+    #[selector(name = "invalidateSignature")]
+    pub fn invalidate_signature(&mut self, digest: FixedBytes<32>) -> Result<(), Errors> {
+        self._ensure_unlocked()?;
+        let actor = msg::sender();
+        if actor != self.ownable.owner() {
+            self._check_validator_role(actor)?;
+        }
+        self.used_or_cancelled.insert(digest, true);
+        evm::log(SignatureInvalidated { digest, actor });
+        Ok(())
+    }
+
+    /// Revoke a push signed for the caller, before it's submitted (e.g. after a device theft)
+    /// The struct hash is rebuilt from the push fields with the caller as user, so only the user can revoke it
+    #[selector(name = "cancelSignature")]
+    pub fn cancel_signature(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        nonce: U256,
+    ) -> Result<FixedBytes<32>, Errors> {
+        self._ensure_unlocked()?;
+        let user = msg::sender();
+        let struct_hash =
+            Self::_consumption_struct_hash(user, channel_id, added_consumption, deadline, nonce);
+        self.revoked_struct_hashes.insert(struct_hash, true);
+        evm::log(SignatureRevokedByUser {
+            user,
+            structHash: struct_hash,
+        });
+        Ok(struct_hash)
+    }
+
+    /// Check if a push struct hash was revoked by its user
+    #[selector(name = "isSignatureRevoked")]
+    pub fn is_signature_revoked(&self, struct_hash: FixedBytes<32>) -> Result<bool, Errors> {
+        Ok(self.revoked_struct_hashes.get(struct_hash))
+    }
+
+    /// Check if a push digest was already used or cancelled
+    #[selector(name = "isSignatureUsedOrCancelled")]
+    pub fn is_signature_used_or_cancelled(&self, digest: FixedBytes<32>) -> Result<bool, Errors> {
+        Ok(self.used_or_cancelled.get(digest))
+    }
+
+    /// Push a new consumption co-signed by both a validator and the user
+    /// Both signatures are over the same typed data, so a compromised validator alone can't credit a user
+    #[selector(name = "pushCcuDualSig")]
+    pub fn push_ccu_dual_sig(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        validator_v: u8,
+        validator_r: FixedBytes<32>,
+        validator_s: FixedBytes<32>,
+        user_v: u8,
+        user_r: FixedBytes<32>,
+        user_s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._push_ccu_dual_sig(
+            msg::sender(),
+            channel_id,
+            added_consumption,
+            deadline,
+            validator_v,
+            validator_r,
+            validator_s,
+            user_v,
+            user_r,
+            user_s,
+        )
+    }
+
+    /// Push a new consumption signed by the validator with `personal_sign`, over a plain message hash,
+    /// for integrations that can't sign typed data: `keccak(abi.encode(address(this), chainId, structHash))`,
+    /// `structHash` being the `ValidateConsumption` one of `pushCcu`; every other push selector expects EIP-712
+    #[selector(name = "pushCcuPersonalSign")]
+    pub fn push_ccu_personal_sign(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._push_ccu_personal_sign(channel_id, added_consumption, deadline, v, r, s)
+    }
+
+    /// Push a new consumption on any content, rather than the configured one
+    /// The content id is part of the signed data, and the validator roles are checked on it
+    #[selector(name = "pushCcuForContent")]
+    pub fn push_ccu_for_content(
+        &mut self,
+        content_id: U256,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._push_ccu_for_content(content_id, channel_id, added_consumption, deadline, v, r, s)
+    }
+
+    /// Push a new consumption tied to an off-chain playback session, for analytics correlation
+    /// The session id is part of the signed data, so it can't be spoofed by the submitter
+    #[selector(name = "pushCcuWithSession")]
+    pub fn push_ccu_with_session(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        session_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._push_ccu_with_session(channel_id, session_id, added_consumption, deadline, v, r, s)
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                   Escrow                                   */
+    /* -------------------------------------------------------------------------- */
+
+    /// Set the challenge period, in seconds, during which pushed consumption stays in escrow (zero to disable)
+    /// Nothing but the rate limits is booked until confirmed: user and global totals, per channel and per content
+    /// figures, histograms and rewards all wait for the confirmation, so a challenged amount never counts
+    #[selector(name = "setChallengePeriod")]
+    pub fn set_challenge_period(&mut self, period: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.challenge_period.set(period);
+        Ok(())
+    }
+
+    /// Get the challenge period during which pushed consumption stays in escrow
+    #[selector(name = "getChallengePeriod")]
+    pub fn get_challenge_period(&self) -> Result<U256, Errors> {
+        Ok(self.challenge_period.get())
+    }
+
+    /// Confirm the escrowed consumption of a user once the challenge period is over (permissionless)
+    /// Also lazily done on the user's next push
+    #[selector(name = "confirmPending")]
+    pub fn confirm_pending(&mut self, user: Address) -> Result<(), Errors> {
+        self._ensure_unlocked()?;
+        if !self._confirm_pending(user)? {
+            return Err(Errors::PendingNotReleasable(PendingNotReleasable {}));
+        }
+        Ok(())
+    }
+
+    /// Cancel a disputed amount of a user's escrowed consumption (capped to the pending amount)
+    #[selector(name = "challengePending")]
+    pub fn challenge_pending(&mut self, user: Address, amount: U256) -> Result<(), Errors> {
+        self._challenge_pending(user, amount)
+    }
+
+    /// Get the escrowed consumption of a user, and the timestamp from which it can be confirmed
+    #[selector(name = "getPendingConsumption")]
+    pub fn get_pending_consumption(&self, user: Address) -> Result<(U256, U256), Errors> {
+        Ok((
+            self.pending_consumptions.get(user),
+            self.pending_release_at.get(user),
+        ))
+    }
+
+    /// Push a cumulative session reading (an "odometer" value) rather than a delta
+    /// Only the increase since the last reading of the same (user, channel, session) is credited,
+    /// a new session id starting back from zero
+    #[selector(name = "pushCcuCumulative")]
+    pub fn push_ccu_cumulative(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        session_id: FixedBytes<32>,
+        cumulative_consumption: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._push_ccu_cumulative(
+            channel_id,
+            session_id,
+            cumulative_consumption,
+            deadline,
+            v,
+            r,
+            s,
+        )
+    }
+
+    /// Get the last cumulative reading of a user session on a channel
+    #[selector(name = "getSessionCumulative")]
+    pub fn get_session_cumulative(
+        &self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        session_id: FixedBytes<32>,
+    ) -> Result<U256, Errors> {
+        let session_key = keccak([channel_id.0, session_id.0].concat());
+        Ok(self.session_cumulatives.getter(user).get(session_key))
+    }
+
+    /// Push consumption with a client supplied idempotency key, bound into the signed data
+    /// A retried key is a successful no-op returning the original receipt id, instead of crediting twice
+    /// Keys are remembered for the current and previous epochs only, so the deadline can't outlast the next epoch
+    #[selector(name = "pushCcuIdempotent")]
+    pub fn push_ccu_idempotent(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        idempotency_key: FixedBytes<32>,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<U256, Errors> {
+        self._push_ccu_idempotent(
+            channel_id,
+            added_consumption,
+            deadline,
+            idempotency_key,
+            v,
+            r,
+            s,
+        )
+    }
+
+    /// Push the consumption of a cohort member, approved by a single validator signature over the cohort root
+    /// Anyone (the user or a relayer) can submit a leaf with its proof, each leaf is credited once
+    /// The validator is only checked on the first push of a given root and deadline; a leaf on a channel with a
+    /// validator override is only credited if that validator signed the cohort
+    #[selector(name = "pushCcuCohort")]
+    pub fn push_ccu_cohort(
+        &mut self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        proof: Vec<FixedBytes<32>>,
+        root: FixedBytes<32>,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._push_ccu_cohort(
+            user,
+            channel_id,
+            added_consumption,
+            proof,
+            root,
+            deadline,
+            v,
+            r,
+            s,
+        )
+    }
+
+    /// Check if a cohort leaf was already credited
+    #[selector(name = "isCohortLeafClaimed")]
+    pub fn is_cohort_leaf_claimed(
+        &self,
+        root: FixedBytes<32>,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+    ) -> Result<bool, Errors> {
+        let leaf = Self::_cohort_leaf(user, channel_id, added_consumption);
+        Ok(self
+            .claimed_cohort_leaves
+            .get(keccak([root.0, leaf.0].concat())))
+    }
+
+    /// Push consumption across several contents at once, under a single validator signature bound to the user's nonce
+    /// Each entry is authorized against its own content id and channel (honoring the channel validator override),
+    /// taking a slot in the validator rate limit; unauthorized entries are skipped (not reverted)
+    /// Returns a bitmask of the entries credited by this call (bit `i` set if entry `i` was credited)
+    /// An entry dropped by the circuit breaker leaves the signature valid: pushed again once unpaused, it only credits
+    /// that entry and the ones after it
+    #[selector(name = "pushCcuMultiContent")]
+    pub fn push_ccu_multi_content(
+        &mut self,
+        content_ids: Vec<U256>,
+        channel_ids: Vec<FixedBytes<32>>,
+        added_consumptions: Vec<U256>,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<U256, Errors> {
+        self._push_ccu_multi_content(
+            content_ids,
+            channel_ids,
+            added_consumptions,
+            deadline,
+            v,
+            r,
+            s,
+        )
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                   Resets                                   */
+    /* -------------------------------------------------------------------------- */
+
+    /// Reset the consumption of a user (e.g. credited through a compromised validator key), consumption admin role only
+    /// The global total is floored at zero, should storage ever be inconsistent
+    #[selector(name = "resetUserConsumption")]
+    pub fn reset_user_consumption(&mut self, user: Address) -> Result<U256, Errors> {
+        self._only_role(CONSUMPTION_ADMIN_ROLE)?;
+        Ok(self._reset_user_consumption(user))
+    }
+
+    /// Reset the consumption of several users at once (e.g. at a season end), consumption admin role only
+    /// Stops early once under the batch gas reserve, returning (processed up to, done) to resume from there
+    #[selector(name = "resetUserConsumptionBatch")]
+    pub fn reset_user_consumption_batch(
+        &mut self,
+        users: Vec<Address>,
+    ) -> Result<(U256, bool), Errors> {
+        self._reset_user_consumption_batch(users)
+    }
+
+    /// Subtract a precise amount from a user (e.g. a partial refund once a session is invalidated), consumption admin role only
+    /// Only the user, global and team totals are corrected, the per channel and per content figures are left as booked
+    #[selector(name = "decreaseUserConsumption")]
+    pub fn decrease_user_consumption(
+        &mut self,
+        user: Address,
+        amount: U256,
+    ) -> Result<U256, Errors> {
+        self._decrease_user_consumption(user, amount)
+    }
+
+    /// Set the absolute consumption of a user (e.g. reconciling against the off-chain ledger), consumption admin role only
+    /// The global and team totals move by the same delta, so they stay exact
+    #[selector(name = "setUserConsumption")]
+    pub fn set_user_consumption(&mut self, user: Address, new_value: U256) -> Result<(), Errors> {
+        self._set_user_consumption(user, new_value)
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                  Migration                                 */
+    /* -------------------------------------------------------------------------- */
+
+    /// Import the user totals of a previous consumption contract, on top of any existing consumption
+    /// Can be split over several transactions (amounts add up, so list each user in a single one), until
+    /// `finalizeMigration` closes it
+    /// The totals can be read from the previous contract with `exportConsumption`, and checked back the same way
+    #[selector(name = "migrateConsumption")]
+    pub fn migrate_consumption(
+        &mut self,
+        users: Vec<Address>,
+        amounts: Vec<U256>,
+    ) -> Result<(), Errors> {
+        self._migrate_consumption(users, amounts)
+    }
+
+    /// Close the migration, no more consumption can be imported afterwards
+    #[selector(name = "finalizeMigration")]
+    pub fn finalize_migration(&mut self) -> Result<(), Errors> {
+        self._only_owner()?;
+        if self.migrated.get() {
+            return Err(Errors::AlreadyMigrated(AlreadyMigrated {}));
+        }
+        self.migrated.set(true);
+        Ok(())
+    }
+
+    /// Check if the migration from a previous contract was finalized
+    #[selector(name = "isMigrated")]
+    pub fn is_migrated(&self) -> Result<bool, Errors> {
+        Ok(self.migrated.get())
+    }
+
+    /// Export the total consumption of several users, aligned with the input, to feed `migrateConsumption` of a
+    /// next contract (and check the import back against it)
+    #[selector(name = "exportConsumption")]
+    pub fn export_consumption(&self, users: Vec<Address>) -> Result<Vec<U256>, Errors> {
+        self.get_user_consumption_batch(users)
+    }
+
+    /// Credit historical consumption directly, e.g. when onboarding a content partner (owner only)
+    /// Unlike the pushes, no validator signature is involved: the owner is trusted for these amounts
+    #[selector(name = "backfillConsumption")]
+    pub fn backfill_consumption(
+        &mut self,
+        users: Vec<Address>,
+        amounts: Vec<U256>,
+    ) -> Result<(), Errors> {
+        self._backfill_consumption(users, amounts)
+    }
+
+    /* -------------------------------------------------------------------------- */
+    /*                                  Penalties                                 */
+    /* -------------------------------------------------------------------------- */
+
+    /// Apply a validator signed penalty to a user, removing `penaltyBps` of their current consumption
+    #[selector(name = "applyPenalty")]
+    pub fn apply_penalty(
+        &mut self,
+        user: Address,
+        penalty_bps: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._apply_penalty(user, penalty_bps, deadline, v, r, s)
+    }
+
+    /// Get the next push nonce of a user, to bind into the signed `ValidateConsumption` data
+    #[selector(name = "getNonce")]
+    pub fn get_nonce(&self, user: Address) -> Result<U256, Errors> {
+        Ok(self.nonces.get(user))
+    }
+
+    /// Get everything a signer needs to build a push signature in one call:
+    /// (user's push nonce, current block timestamp to base the deadline on, domain separator)
+    #[selector(name = "getSigningContext")]
+    pub fn get_signing_context(
+        &self,
+        user: Address,
+    ) -> Result<(U256, U256, FixedBytes<32>), Errors> {
+        Ok((
+            self.nonces.get(user),
+            U256::from(block::timestamp()),
+            self.eip712.current_domain_separator(),
+        ))
+    }
+
+    /// Get the next penalty nonce of a user
+    #[selector(name = "getPenaltyNonce")]
+    pub fn get_penalty_nonce(&self, user: Address) -> Result<U256, Errors> {
+        Ok(self.penalty_nonces.get(user))
+    }
+
+    /// Simulate a push of the configured content, returning (new user total, new channel total, credited amount)
+    /// Applies the same caps and rules as `pushCcu`, reverting with the same errors
+    /// Pure what-if: no signature nor validator authorization is checked, so a real push may still be rejected
+    #[selector(name = "simulatePush")]
+    pub fn simulate_push(
+        &self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+    ) -> Result<(U256, U256, U256), Errors> {
+        let content_id = self.nutty_content_id.get();
+        self._simulate_credit(user, content_id, channel_id, added_consumption)
+    }
+
+    /// Get what a push of the configured content would actually credit right now for a requested amount,
+    /// once clamped against every cap (per push, channel daily, user and global)
+    /// Pushes revert rather than clamp, so clients should request (and get signed) at most this amount
+    #[selector(name = "effectiveCreditedAmount")]
+    pub fn effective_credited_amount(
+        &self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        requested: U256,
+    ) -> Result<U256, Errors> {
+        let content_id = self.nutty_content_id.get();
+        Ok(self._effective_credit(user, content_id, channel_id, requested))
+    }
+
+    /// Recover the signer of a push signature, exactly as `pushCcu` would (with the user's current nonce)
+    /// Returns the zero address if the signature can't be recovered; the signer roles aren't checked
+    #[selector(name = "verifyCcuSignature")]
+    pub fn verify_ccu_signature(
+        &self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<Address, Errors> {
+        let struct_hash = Self::_consumption_struct_hash(
+            user,
+            channel_id,
+            added_consumption,
+            deadline,
+            self.nonces.get(user),
+        );
+        Ok(self
+            .eip712
+            .recover_typed_data_signer_view(struct_hash, v, r, s)
+            .unwrap_or(Address::ZERO))
+    }
+
+    /// Set the seconds a signature is still accepted past its deadline, tolerating signer clock skew (zero for strict)
+    /// A zero deadline stays always expired, whatever the grace window
+    #[selector(name = "setDeadlineGrace")]
+    pub fn set_deadline_grace(&mut self, grace: U256) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.deadline_grace.set(grace);
+        Ok(())
+    }
+
+    /// Get the seconds a signature is still accepted past its deadline
+    #[selector(name = "getDeadlineGrace")]
+    pub fn get_deadline_grace(&self) -> Result<U256, Errors> {
+        Ok(self.deadline_grace.get())
+    }
+
+    /// Get the `ValidateConsumption` type hash, for off-chain signers to check theirs against
+    #[selector(name = "getConsumptionTypeHash")]
+    pub fn get_consumption_type_hash(&self) -> Result<FixedBytes<32>, Errors> {
+        Ok(FixedBytes::from(VALIDATE_CONSUMPTION_TYPE_HASH))
+    }
+
+    /// Compute the exact struct hash `pushCcu` rebuilds, from the given fields only (no state read)
+    /// The nonce is explicit to keep it pure, the current one being `getNonce(user)`
+    #[selector(name = "computeConsumptionStructHash")]
+    pub fn compute_consumption_struct_hash(
+        &self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        nonce: U256,
+    ) -> Result<FixedBytes<32>, Errors> {
+        Ok(Self::_consumption_struct_hash(
+            user,
+            channel_id,
+            added_consumption,
+            deadline,
+            nonce,
+        ))
+    }
+
+    /// Check if a signature deadline would still be valid `secondsAhead` seconds from now
+    /// Lets schedulers discard signatures that would expire before their transaction confirms
+    #[selector(name = "isValidIn")]
+    pub fn is_valid_in(&self, deadline: U256, seconds_ahead: U256) -> Result<bool, Errors> {
+        let at = U256::from(block::timestamp()).saturating_add(seconds_ahead);
+        Ok(self._is_deadline_valid_at(deadline, at))
+    }
+
+    /// Get the raw typed data preimage (`0x1901 || domainSeparator || structHash`) of a push
+    /// Useful for signers that hash the payload themselves
+    #[selector(name = "pushDigestPreimage")]
+    pub fn push_digest_preimage(
+        &self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+    ) -> Result<Bytes, Errors> {
+        let struct_hash = Self::_consumption_struct_hash(
+            user,
+            channel_id,
+            added_consumption,
+            deadline,
+            self.nonces.get(user),
+        );
+        Ok(self.eip712.typed_data_preimage(struct_hash).to_vec().into())
+    }
+
+    /// Get the typed data digest of a push (the one `invalidateSignature` expects)
+    #[selector(name = "pushDigest")]
+    pub fn push_digest(
+        &self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+    ) -> Result<FixedBytes<32>, Errors> {
+        let struct_hash = Self::_consumption_struct_hash(
+            user,
+            channel_id,
+            added_consumption,
+            deadline,
+            self.nonces.get(user),
+        );
+        Ok(self._push_digest(struct_hash))
+    }
+
+    /// Get the total consumption of a user
+    #[selector(name = "getUserConsumption")]
+    pub fn get_user_consumption(&self, user: Address) -> Result<U256, Errors> {
+        // Return the consumption
+        Ok(self.user_consumptions.get(user))
+    }
+
+    /// Get the consumption of a user as of now, with the decay since their last push applied
+    #[selector(name = "getUserConsumptionDecayed")]
+    pub fn get_user_consumption_decayed(&self, user: Address) -> Result<U256, Errors> {
+        Ok(self._decayed_consumption(user))
+    }
+
+    /// Get the consumption credited to a user during a UTC day (`dayIndex = timestamp / 86400`)
+    #[selector(name = "getUserConsumptionForDay")]
+    pub fn get_user_consumption_for_day(
+        &self,
+        user: Address,
+        day_index: U256,
+    ) -> Result<U256, Errors> {
+        Ok(self.user_daily_consumptions.getter(user).get(day_index))
+    }
+
+    /// Get the number of users that ever had a push credited
+    #[selector(name = "getUserCount")]
+    pub fn get_user_count(&self) -> Result<U256, Errors> {
+        Ok(U256::from(self.users.len()))
+    }
+
+    /// Get the dashboard figures of a user in one call: (user consumption, total consumption, user count)
+    /// All zeros before the contract is initialized
+    #[selector(name = "getConsumptionStats")]
+    pub fn get_consumption_stats(&self, user: Address) -> Result<(U256, U256, U256), Errors> {
+        Ok((
+            self.user_consumptions.get(user),
+            self.total_consumption.get(),
+            U256::from(self.users.len()),
+        ))
+    }
+
+    /// Get a page of the users that ever had a push credited, in first push order
+    /// Returns fewer entries (possibly none) past the end of the list
+    #[selector(name = "getUsers")]
+    pub fn get_users(&self, offset: U256, limit: U256) -> Result<Vec<Address>, Errors> {
+        if limit > U256::from(MAX_QUERY_LIMIT) {
+            return Err(Errors::LimitTooLarge(LimitTooLarge {}));
+        }
+        let count = U256::from(self.users.len());
+        let end = offset.saturating_add(limit).min(count);
+        if offset >= end {
+            return Ok(Vec::new());
+        }
+        Ok((offset.to::<usize>()..end.to::<usize>())
+            .filter_map(|i| self.users.get(i))
+            .collect())
+    }
+
+    /// Get the total consumption of several users, aligned with the input (zero for unknown users)
+    #[selector(name = "getUserConsumptionBatch")]
+    pub fn get_user_consumption_batch(&self, users: Vec<Address>) -> Result<Vec<U256>, Errors> {
+        if users.len() > MAX_QUERY_LIMIT {
+            return Err(Errors::LimitTooLarge(LimitTooLarge {}));
+        }
+        Ok(users
+            .iter()
+            .map(|user| self.user_consumptions.get(*user))
+            .collect())
+    }
+
+    /// Set the team of a user (zero to leave any team), callable by the user or the owner
+    /// Only the consumption credited from now on counts for the new team, the previous team keeps the past one
+    #[selector(name = "setUserTeam")]
+    pub fn set_user_team(&mut self, user: Address, team_id: FixedBytes<32>) -> Result<(), Errors> {
+        if msg::sender() == user {
+            self._ensure_unlocked()?;
+        } else {
+            self._only_owner()?;
+        }
+        self.user_teams.insert(user, team_id);
+        evm::log(UserTeamUpdated {
+            user,
+            teamId: team_id,
+        });
+        Ok(())
+    }
+
+    /// Get the team of a user (zero if none)
+    #[selector(name = "getUserTeam")]
+    pub fn get_user_team(&self, user: Address) -> Result<FixedBytes<32>, Errors> {
+        Ok(self.user_teams.get(user))
+    }
+
+    /// Propose to credit the caller's pushes to a primary wallet, which must confirm it (zero to stop delegating)
+    /// The caller's own consumption so far stays theirs
+    #[selector(name = "setConsumptionDelegate")]
+    pub fn set_consumption_delegate(&mut self, primary: Address) -> Result<(), Errors> {
+        self._ensure_unlocked()?;
+        let secondary = msg::sender();
+        if primary.is_zero() {
+            self.pending_consumption_delegates.delete(secondary);
+            self.consumption_delegates.delete(secondary);
+            evm::log(ConsumptionDelegateSet { secondary, primary });
+            return Ok(());
+        }
+        if primary == secondary {
+            return Err(Errors::InvalidAddress(InvalidAddress {}));
+        }
+        self.pending_consumption_delegates
+            .insert(secondary, primary);
+        evm::log(ConsumptionDelegateProposed { secondary, primary });
+        Ok(())
+    }
+
+    /// Confirm a delegation proposed by a secondary wallet to the caller, from now on crediting its pushes to the caller
+    /// Refused if the caller delegates its own pushes (`DelegationLoop`), the resolution only following one hop
+    #[selector(name = "acceptConsumptionDelegate")]
+    pub fn accept_consumption_delegate(&mut self, secondary: Address) -> Result<(), Errors> {
+        self._ensure_unlocked()?;
+        let primary = msg::sender();
+        if self.pending_consumption_delegates.get(secondary) != primary {
+            return Err(Errors::InvalidAddress(InvalidAddress {}));
+        }
+        if !self.consumption_delegates.get(primary).is_zero() {
+            return Err(Errors::DelegationLoop(DelegationLoop {}));
+        }
+        self.pending_consumption_delegates.delete(secondary);
+        self.consumption_delegates.insert(secondary, primary);
+        evm::log(ConsumptionDelegateSet { secondary, primary });
+        Ok(())
+    }
+
+    /// Get the primary wallet credited for a wallet's pushes (zero if not delegated)
+    #[selector(name = "getConsumptionDelegate")]
+    pub fn get_consumption_delegate(&self, secondary: Address) -> Result<Address, Errors> {
+        Ok(self.consumption_delegates.get(secondary))
+    }
+
+    /// Get the consumption credited to a team, across all its members
+    #[selector(name = "getTeamConsumption")]
+    pub fn get_team_consumption(&self, team_id: FixedBytes<32>) -> Result<U256, Errors> {
+        Ok(self.team_consumptions.get(team_id))
+    }
+
+    /// Get the total consumption of a user, with the timestamp and receipt id of their last push
+    /// Lets caching clients detect a stale value
+    #[selector(name = "getUserConsumptionWithMeta")]
+    pub fn get_user_consumption_with_meta(
+        &self,
+        user: Address,
+    ) -> Result<(U256, u64, U256), Errors> {
+        Ok((
+            self.user_consumptions.get(user),
+            self.user_last_push_at.get(user).to::<u64>(),
+            self.user_last_receipt.get(user),
+        ))
+    }
+
+    /// Get the main figures of a user in one structured call: total consumption, last push timestamp,
+    /// anomaly flag and next push nonce (all zero / false for a user who never pushed)
+    #[selector(name = "getUserData")]
+    pub fn get_user_data(&self, user: Address) -> Result<UserConsumptionData, Errors> {
+        Ok(UserConsumptionData {
+            total: self.user_consumptions.get(user),
+            lastUpdate: self.user_last_push_at.get(user),
+            flagged: self.flagged.get(user),
+            nonce: self.nonces.get(user),
+        })
+    }
+
+    /// Get the number of distinct channels a user has pushed to
+    #[selector(name = "getUserChannelCount")]
+    pub fn get_user_channel_count(&self, user: Address) -> Result<U256, Errors> {
+        Ok(self.user_channel_count.get(user))
+    }
+
+    /// Get the number of distinct channels that ever received consumption
+    #[selector(name = "getChannelCount")]
+    pub fn get_channel_count(&self) -> Result<U256, Errors> {
+        Ok(self.channel_count.get())
+    }
+
+    /// Get the consumption of a channel across all its users, as recorded (after any weighting)
+    #[selector(name = "getChannelTotalConsumption")]
+    pub fn get_channel_total_consumption(
+        &self,
+        channel_id: FixedBytes<32>,
+    ) -> Result<U256, Errors> {
+        Ok(self.channel_consumptions.get(channel_id))
+    }
+
+    /// Get the consumption of a user on a channel
+    #[selector(name = "getUserChannelConsumption")]
+    pub fn get_user_channel_consumption(
+        &self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+    ) -> Result<U256, Errors> {
+        Ok(self.user_channel_consumptions.getter(user).get(channel_id))
+    }
+
+    /// Get the consumption of a user on several channels, aligned with the given list (zero for untouched ones)
+    #[selector(name = "getUserChannelConsumptionBatch")]
+    pub fn get_user_channel_consumption_batch(
+        &self,
+        user: Address,
+        channel_ids: Vec<FixedBytes<32>>,
+    ) -> Result<Vec<U256>, Errors> {
+        if channel_ids.len() > MAX_QUERY_LIMIT {
+            return Err(Errors::LimitTooLarge(LimitTooLarge {}));
+        }
+        let user_channel_consumptions = self.user_channel_consumptions.getter(user);
+        Ok(channel_ids
+            .iter()
+            .map(|channel_id| user_channel_consumptions.get(*channel_id))
+            .collect())
+    }
+
+    /// Check if a user is cooling down from their last push, and when their next push would be accepted
+    #[selector(name = "isRateLimited")]
+    pub fn is_rate_limited(&self, user: Address) -> Result<(bool, u64), Errors> {
+        let next_push_at = self._next_push_at(user);
+        if U256::from(block::timestamp()) < next_push_at {
+            Ok((true, next_push_at.to::<u64>()))
+        } else {
+            Ok((false, 0))
+        }
+    }
+
+    /// Get the timestamp of the last accepted push of a user (zero if none)
+    #[selector(name = "getLastPushAt")]
+    pub fn get_last_push_at(&self, user: Address) -> Result<U256, Errors> {
+        Ok(self.user_last_push_at.get(user))
+    }
+
+    /// Get the timestamp of the last accepted push of a user (zero if none), alias of `getLastPushAt`
+    #[selector(name = "getLastUpdate")]
+    pub fn get_last_update(&self, user: Address) -> Result<U256, Errors> {
+        self.get_last_push_at(user)
+    }
+
+    /// Export everything known about a user in one call (e.g. for data export requests)
+    /// Returns (consumption, pending consumption, first push, last push, penalty nonce, flagged, channels, channel consumptions)
+    /// Only the first `MAX_QUERY_LIMIT` channels are exported, to bound the gas cost (roughly two storage reads per channel),
+    /// `getUserChannelCount` tells if the list was truncated
+    #[selector(name = "exportUserData")]
+    pub fn export_user_data(
+        &self,
+        user: Address,
+    ) -> Result<
+        (
+            U256,
+            U256,
+            U256,
+            U256,
+            U256,
+            bool,
+            Vec<FixedBytes<32>>,
+            Vec<U256>,
+        ),
+        Errors,
+    > {
+        let user_channels = self.user_channels.getter(user);
+        let channels_len = user_channels.len().min(MAX_QUERY_LIMIT);
+        let user_channel_consumptions = self.user_channel_consumptions.getter(user);
+        let mut channels = Vec::with_capacity(channels_len);
+        let mut channel_consumptions = Vec::with_capacity(channels_len);
+        for i in 0..channels_len {
+            let channel_id = user_channels.get(i).unwrap_or_default();
+            channel_consumptions.push(user_channel_consumptions.get(channel_id));
+            channels.push(channel_id);
+        }
+
+        Ok((
+            self.user_consumptions.get(user),
+            self.pending_consumptions.get(user),
+            self.user_first_push_at.get(user),
+            self.user_last_push_at.get(user),
+            self.penalty_nonces.get(user),
+            self.flagged.get(user),
+            channels,
+            channel_consumptions,
+        ))
+    }
+
+    /// Check if the caller is currently an authorized validator for the configured content
+    /// Useful for validator nodes to confirm their registration before signing
+    #[selector(name = "amIAuthorizedValidator")]
+    pub fn am_i_authorized_validator(&self) -> Result<bool, Errors> {
+        if self._is_validator_expired(msg::sender()) {
+            return Ok(false);
+        }
+        if let Some(is_authorized) = self._check_validator_locally(msg::sender()) {
+            return Ok(is_authorized);
+        }
+        let is_authorized = static_call_helper::<isAuthorizedCall>(
+            self.content_registry.get(),
+            (self.nutty_content_id.get(), msg::sender()),
+        )
+        .map_err(|_| Errors::CallError(CallError {}))?;
+        Ok(is_authorized._0)
+    }
+
+    /// Get the combined consumption of a list of channels
+    #[selector(name = "getChannelsTotalConsumption")]
+    pub fn get_channels_total_consumption(
+        &self,
+        channels: Vec<FixedBytes<32>>,
+    ) -> Result<U256, Errors> {
+        if channels.len() > MAX_QUERY_LIMIT {
+            return Err(Errors::LimitTooLarge(LimitTooLarge {}));
+        }
+        Ok(channels.iter().fold(U256::ZERO, |total, channel_id| {
+            total + self.channel_consumptions.get(*channel_id)
+        }))
+    }
+
+    /// Check if a user has been flagged by the anomaly detection
+    #[selector(name = "isFlagged")]
+    pub fn is_flagged(&self, user: Address) -> Result<bool, Errors> {
+        Ok(self.flagged.get(user))
+    }
+
+    /// Get the number of pushes silently rejected by the validator checks
+    #[selector(name = "getRejectedPushCount")]
+    pub fn get_rejected_push_count(&self) -> Result<U256, Errors> {
+        Ok(self.rejected_pushes.get())
+    }
+
+    /// Get the stable numeric code of a custom error selector (zero if unknown)
+    #[selector(name = "errorCode")]
+    pub fn error_code(&self, selector: FixedBytes<4>) -> Result<u16, Errors> {
+        Ok(error_code(selector.0))
+    }
+
+    /// Get the custom error selector of a numeric code (zero if unknown)
+    #[selector(name = "errorSelector")]
+    pub fn error_selector(&self, code: u16) -> Result<FixedBytes<4>, Errors> {
+        Ok(FixedBytes::from(error_selector(code).unwrap_or_default()))
+    }
+
+    /// Get the selectors of every callable function, for client generation and capability discovery
+    #[selector(name = "supportedSelectors")]
+    pub fn supported_selectors(&self) -> Result<Vec<FixedBytes<4>>, Errors> {
+        Ok(SUPPORTED_SELECTORS
+            .iter()
+            .map(|selector| FixedBytes::from(*selector))
+            .collect())
+    }
+
+    /// ERC-165 interface detection, for the consumption interface and ERC-165 itself
+    #[selector(name = "supportsInterface")]
+    pub fn supports_interface(&self, interface_id: FixedBytes<4>) -> Result<bool, Errors> {
+        Ok(interface_id.0 == CONSUMPTION_INTERFACE_ID || interface_id.0 == ERC165_INTERFACE_ID)
+    }
+
+    /// Get the number of pushes accepted by the validator checks
+    #[selector(name = "getAcceptedPushCount")]
+    pub fn get_accepted_push_count(&self) -> Result<U256, Errors> {
+        Ok(self.accepted_pushes.get())
+    }
+
+    /// Get the share of pushes rejected by the validator checks, in basis points (zero without any activity)
+    #[selector(name = "getRejectionRateBps")]
+    pub fn get_rejection_rate_bps(&self) -> Result<U256, Errors> {
+        let rejected = self.rejected_pushes.get();
+        let total = self.accepted_pushes.get() + rejected;
+        if total.is_zero() {
+            return Ok(U256::ZERO);
+        }
+        Ok(rejected * U256::from(BPS_DENOMINATOR) / total)
+    }
+
+    /// Get the total consumption handled by the contract
+    #[selector(name = "getTotalConsumption")]
+    pub fn get_total_consumption(&self) -> Result<U256, Errors> {
+        Ok(self.total_consumption.get())
+    }
+
+    /// Get the content id pushes are credited to (zero before initialization)
+    #[selector(name = "getNuttyContentId")]
+    pub fn get_nutty_content_id(&self) -> Result<U256, Errors> {
+        Ok(self.nutty_content_id.get())
+    }
+
+    /// Point to a new content registry (e.g. after a registry redeployment), registry admin role only
+    /// Sensitive: must be queued via `queueConfigChange` once a config timelock is set
+    #[selector(name = "setContentRegistry")]
+    pub fn set_content_registry(&mut self, new_registry: Address) -> Result<(), Errors> {
+        self._only_role(REGISTRY_ADMIN_ROLE)?;
+        self._ensure_no_timelock()?;
+        self._set_content_registry(new_registry)
+    }
+
+    /// Get the content registry checked for the validator roles (zero before initialization)
+    #[selector(name = "getContentRegistry")]
+    pub fn get_content_registry(&self) -> Result<Address, Errors> {
+        Ok(self.content_registry.get())
+    }
+}
+
+/// Simple helper to perform a static (read only) call to another smart contract
+pub fn static_call_helper<C: SolCall>(
+    address: Address,
+    args: <C::Arguments<'_> as SolType>::RustType,
+) -> Result<C::Return, Vec<u8>> {
+    let calldata = C::new(args).abi_encode();
+    // SAFETY: static call, the callee can't mutate our state
+    let res = unsafe { RawCall::new_static().call(address, &calldata)? };
+    C::abi_decode_returns(&res, false).map_err(|_| b"decoding error".to_vec())
+}
+
+/// Simple helper to perform call to another smart contract
+pub fn call_helper<C: SolCall>(
+    storage: &mut impl TopLevelStorage,
+    address: Address,
+    args: <C::Arguments<'_> as SolType>::RustType,
+) -> Result<C::Return, Vec<u8>> {
+    let calldata = C::new(args).abi_encode();
+    let res = call(storage, address, &calldata)?;
+    C::abi_decode_returns(&res, false).map_err(|_| b"decoding error".to_vec())
+}
@@ -0,0 +1,1075 @@
+//! The push verification helpers, and the push variants bodies
+
+use super::*;
+
+impl ChannelConsumptionContract {
+    /// Common checks before accepting any push: not reentered, not paused, initialized, and with enough gas
+    pub(super) fn _before_push(&self) -> Result<(), Errors> {
+        self._ensure_unlocked()?;
+        if self.paused.get() {
+            return Err(Errors::ContractPaused(ContractPaused {}));
+        }
+        self._ensure_initialized()?;
+
+        // Fail cleanly before any write or registry call, rather than running out of gas halfway
+        if U256::from(evm::gas_left()) < self.min_gas_for_push.get() {
+            return Err(Errors::InsufficientGas(InsufficientGas {}));
+        }
+        Ok(())
+    }
+
+    /// Build the `ValidateConsumption` struct hash signed by the validator
+    pub(super) fn _consumption_struct_hash(
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        nonce: U256,
+    ) -> B256 {
+        keccak(
+            <sol! { (bytes32, address, bytes32, uint256, uint256, uint256) }>::abi_encode(&(
+                VALIDATE_CONSUMPTION_TYPE_HASH,
+                user,
+                channel_id.0,
+                added_consumption,
+                deadline,
+                nonce,
+            )),
+        )
+    }
+
+    /// Build the `ValidateContentConsumption` struct hash signed by the validator, for a push on a given content
+    pub(super) fn _content_struct_hash(
+        user: Address,
+        content_id: U256,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        nonce: U256,
+    ) -> B256 {
+        keccak(
+            <sol! { (bytes32, address, uint256, bytes32, uint256, uint256, uint256) }>::abi_encode(&(
+                keccak(b"ValidateContentConsumption(address user,uint256 contentId,bytes32 channelId,uint256 addedConsumption,uint256 deadline,uint256 nonce)").0,
+                user,
+                content_id,
+                channel_id.0,
+                added_consumption,
+                deadline,
+                nonce,
+            )),
+        )
+    }
+
+    /// Build the `ValidateSessionConsumption` struct hash signed by the validator, binding the playback session
+    pub(super) fn _session_struct_hash(
+        user: Address,
+        channel_id: FixedBytes<32>,
+        session_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        nonce: U256,
+    ) -> B256 {
+        keccak(
+            <sol! { (bytes32, address, bytes32, bytes32, uint256, uint256, uint256) }>::abi_encode(&(
+                keccak(b"ValidateSessionConsumption(address user,bytes32 channelId,bytes32 sessionId,uint256 addedConsumption,uint256 deadline,uint256 nonce)").0,
+                user,
+                channel_id.0,
+                session_id.0,
+                added_consumption,
+                deadline,
+                nonce,
+            )),
+        )
+    }
+
+    /// Push a new consumption of a user for the configured content, with an optional expected validator hint
+    /// (zero for none); the user is part of the signed data, so anyone can submit it on their behalf
+    /// Returns whether the consumption was recorded, `false` if the validator was silently rejected
+    pub(super) fn _push_ccu(
+        &mut self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        expected_validator: Address,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<bool, Errors> {
+        // No need to check that te platform exists, as the consumption will be rejected
+        //  if the recovered address is zero (before any registry call), or doesn't have the validator roles
+        self._before_push()?;
+
+        // Verify the push
+        let verified = self._verify_push(
+            user,
+            channel_id,
+            added_consumption,
+            deadline,
+            expected_validator,
+            v,
+            r,
+            s,
+            &mut Vec::new(),
+        )?;
+        let (digest, validator) = match verified {
+            Some(verified) => verified,
+            None => {
+                // Early exit cause it's failing otherwise (or revert, in strict mode)
+                // Always passing the same error to avoid leaking information
+                self._reject_push()?;
+                return Ok(false);
+            }
+        };
+
+        // Credit the consumption (to the user's primary wallet, if delegated), and consume the signature,
+        // unless dropped by the circuit breaker (then left valid, to be pushed again once unpaused)
+        let content_id = self.nutty_content_id.get();
+        let credited_user = self._consumption_delegate_of(user);
+        if self
+            ._credit_consumption(credited_user, content_id, channel_id, added_consumption)?
+            .is_none()
+        {
+            return Ok(false);
+        }
+        self._consume_push(user, digest);
+        self._log_pushed_by(credited_user, validator, channel_id);
+        Ok(true)
+    }
+
+    /// Verify a signed push, bound to the user's current nonce (consumed by the caller, once credited)
+    /// Returns the push digest and its validator, or `None` if the validator was silently rejected
+    /// Nothing but the validator counters is written, so a failure can be skipped safely
+    /// The validators already authorized by the registry within the call are skipped (see `_accept_channel_validator_cached`)
+    pub(super) fn _verify_push(
+        &mut self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        expected_validator: Address,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+        authorized_validators: &mut Vec<Address>,
+    ) -> Result<Option<(B256, Address)>, Errors> {
+        self._ensure_deadline(deadline)?;
+
+        // Rebuild the signed data, bound to the user's current nonce
+        let nonce = self.nonces.get(user);
+        let struct_hash =
+            Self::_consumption_struct_hash(user, channel_id, added_consumption, deadline, nonce);
+        self._ensure_not_revoked(struct_hash)?;
+
+        // Ensure the signature wasn't already used, or cancelled by the platform
+        let digest = self._push_digest(struct_hash);
+        if self.used_or_cancelled.get(digest) {
+            return Err(Errors::SignatureCancelled(SignatureCancelled {}));
+        }
+
+        // Do an ecdsa recovery check on the signature, or ask the hinted contract validator if allowed
+        let contract_signer = if self.allow_contract_validators.get() {
+            expected_validator
+        } else {
+            Address::ZERO
+        };
+        // An unrecoverable signature goes through the same rejection as an unauthorized signer (zero address),
+        // malformed `v` / `r` / `s` values still revert
+        let recovered_address =
+            match self
+                .eip712
+                .recover_or_verify_1271(struct_hash, v, r, s, contract_signer)
+            {
+                Ok(recovered_address) => recovered_address,
+                Err(Errors::EcRecoverError(_) | Errors::EcRecoverFailed(_)) => Address::ZERO,
+                Err(error) => return Err(error),
+            };
+
+        // Compare with the expected validator hint, if any, before calling the registry
+        if !expected_validator.is_zero() && expected_validator != recovered_address {
+            if self.strict_validator_hint.get() {
+                return Err(Errors::ValidatorHintMismatch(ValidatorHintMismatch {}));
+            }
+            evm::log(ValidatorHintMismatched {
+                user,
+                expected: expected_validator,
+                recovered: recovered_address,
+            });
+        }
+
+        // Ensure the signer has the interaction validator roles for this content), within its rate limit
+        let accepted = self._accept_channel_validator_cached(
+            channel_id,
+            recovered_address,
+            authorized_validators,
+        )?;
+
+        // Surface the recovered signer on staging builds
+        #[cfg(feature = "debug_events")]
+        evm::log(DebugRecoveredSigner {
+            signer: recovered_address,
+            authorized: accepted,
+        });
+
+        if !accepted {
+            // Same event whatever the failure, to avoid leaking information
+            evm::log(CcuRejected {
+                user,
+                channelId: channel_id,
+                recoveredSigner: recovered_address,
+            });
+            return Ok(None);
+        }
+
+        Ok(Some((digest, recovered_address)))
+    }
+
+    /// Consume a credited push signature: the user's nonce it was bound to, and its digest
+    /// Only done once credited, so a push dropped by the circuit breaker can be submitted again
+    pub(super) fn _consume_push(&mut self, user: Address, digest: B256) {
+        self.nonces
+            .insert(user, self.nonces.get(user) + U256::from(1));
+        self.used_or_cancelled.insert(digest, true);
+    }
+
+    /// Handle a push from an unauthorized validator: a silent no op by default, a revert in strict mode
+    /// Shared by every push variant, so none leaks whether the validator was authorized
+    pub(super) fn _reject_push(&self) -> Result<(), Errors> {
+        if self.strict_mode.get() {
+            return Err(Errors::InvalidPlatformSignature(
+                InvalidPlatformSignature {},
+            ));
+        }
+        Ok(())
+    }
+
+    /// Record which validator credited a push, indexed by both the user and the validator for audits
+    /// The total is the user's confirmed consumption, so excludes anything still in escrow
+    pub(super) fn _log_pushed_by(
+        &self,
+        user: Address,
+        validator: Address,
+        channel_id: FixedBytes<32>,
+    ) {
+        evm::log(CcuPushedBy {
+            user,
+            validator,
+            channelId: channel_id,
+            totalConsumption: self.user_consumptions.get(user),
+        });
+    }
+
+    /// Ensure a push struct hash wasn't revoked by its user
+    pub(super) fn _ensure_not_revoked(&self, struct_hash: B256) -> Result<(), Errors> {
+        if self.revoked_struct_hashes.get(struct_hash) {
+            return Err(Errors::SignatureRevoked(SignatureRevoked {}));
+        }
+        Ok(())
+    }
+
+    /// Compute the typed data digest of a push, as signed by the validator
+    pub(super) fn _push_digest(&self, struct_hash: B256) -> B256 {
+        keccak(self.eip712.typed_data_preimage(struct_hash))
+    }
+
+    /// Build the `ValidateMultiContentConsumption` struct hash signed by the validator
+    /// Entries are `ContentConsumption` structs, encoded as an EIP-712 array (hash of the entries hashes)
+    pub(super) fn _multi_content_struct_hash(
+        user: Address,
+        content_ids: &[U256],
+        channel_ids: &[FixedBytes<32>],
+        added_consumptions: &[U256],
+        deadline: U256,
+        nonce: U256,
+    ) -> B256 {
+        let entry_type_hash = keccak(
+            b"ContentConsumption(uint256 contentId,bytes32 channelId,uint256 addedConsumption)",
+        );
+        let mut entries_hashes = Vec::with_capacity(content_ids.len() * 32);
+        for ((content_id, channel_id), added_consumption) in content_ids
+            .iter()
+            .zip(channel_ids.iter())
+            .zip(added_consumptions.iter())
+        {
+            let entry_hash = keccak(<sol! { (bytes32, uint256, bytes32, uint256) }>::abi_encode(
+                &(
+                    entry_type_hash.0,
+                    *content_id,
+                    channel_id.0,
+                    *added_consumption,
+                ),
+            ));
+            entries_hashes.extend_from_slice(&entry_hash[..]);
+        }
+
+        keccak(
+            <sol! { (bytes32, address, bytes32, uint256, uint256) }>::abi_encode(&(
+                keccak(b"ValidateMultiContentConsumption(address user,ContentConsumption[] entries,uint256 deadline,uint256 nonce)ContentConsumption(uint256 contentId,bytes32 channelId,uint256 addedConsumption)").0,
+                user,
+                keccak(entries_hashes).0,
+                deadline,
+                nonce,
+            )),
+        )
+    }
+
+    /// Build the `ValidateIdempotentConsumption` struct hash signed by the validator
+    pub(super) fn _idempotent_struct_hash(
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        idempotency_key: FixedBytes<32>,
+    ) -> B256 {
+        keccak(
+            <sol! { (bytes32, address, bytes32, uint256, uint256, bytes32) }>::abi_encode(&(
+                keccak(b"ValidateIdempotentConsumption(address user,bytes32 channelId,uint256 addedConsumption,uint256 deadline,bytes32 idempotencyKey)").0,
+                user,
+                channel_id.0,
+                added_consumption,
+                deadline,
+                idempotency_key.0,
+            )),
+        )
+    }
+
+    /// Get the receipt of an already processed idempotency key (zero if not processed)
+    /// Keys are only remembered for the current and previous epochs, bounding the lookups
+    pub(super) fn _idempotent_receipt(
+        &self,
+        user: Address,
+        idempotency_key: FixedBytes<32>,
+    ) -> U256 {
+        let key = keccak([user.into_word().0, idempotency_key.0].concat());
+        let epoch = self._current_epoch();
+        let receipt_id = self.idempotency_receipts.getter(epoch).get(key);
+        if !receipt_id.is_zero() || epoch.is_zero() {
+            return receipt_id;
+        }
+        self.idempotency_receipts
+            .getter(epoch - U256::from(1))
+            .get(key)
+    }
+
+    /// Ensure an idempotent push expires while its key is still retained (current and previous epochs),
+    /// so it can't be replayed once the key is forgotten (an `advanceEpoch` forgets keys sooner)
+    pub(super) fn _ensure_within_key_retention(&self, deadline: U256) -> Result<(), Errors> {
+        let duration = self.epoch_duration.get();
+        if duration.is_zero() {
+            return Ok(());
+        }
+        let elapsed = U256::from(block::timestamp()).saturating_sub(self.epoch_start.get());
+        let retained_until = self
+            .epoch_start
+            .get()
+            .saturating_add((elapsed / duration + U256::from(2)).saturating_mul(duration));
+        if deadline.saturating_add(self.deadline_grace.get()) > retained_until {
+            return Err(Errors::DeadlineTooFar(DeadlineTooFar {}));
+        }
+        Ok(())
+    }
+
+    /// Build the `ValidateCohort` struct hash signed by the validator, over a root of cohort leaves
+    pub(super) fn _cohort_struct_hash(root: B256, deadline: U256) -> B256 {
+        keccak(<sol! { (bytes32, bytes32, uint256) }>::abi_encode(&(
+            keccak(b"ValidateCohort(bytes32 root,uint256 deadline)").0,
+            root.0,
+            deadline,
+        )))
+    }
+
+    /// Build a cohort leaf, `keccak(keccak(abi.encode(user, channelId, added)))`
+    /// (OpenZeppelin `StandardMerkleTree` compatible)
+    pub(super) fn _cohort_leaf(
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+    ) -> B256 {
+        keccak(keccak(<sol! { (address, bytes32, uint256) }>::abi_encode(
+            &(user, channel_id.0, added_consumption),
+        )))
+    }
+
+    /// Build the `ValidateCumulativeConsumption` struct hash signed by the validator
+    pub(super) fn _cumulative_struct_hash(
+        user: Address,
+        channel_id: FixedBytes<32>,
+        session_id: FixedBytes<32>,
+        cumulative_consumption: U256,
+        deadline: U256,
+    ) -> B256 {
+        keccak(
+            <sol! { (bytes32, address, bytes32, bytes32, uint256, uint256) }>::abi_encode(&(
+                keccak(b"ValidateCumulativeConsumption(address user,bytes32 channelId,bytes32 sessionId,uint256 cumulativeConsumption,uint256 deadline)").0,
+                user,
+                channel_id.0,
+                session_id.0,
+                cumulative_consumption,
+                deadline,
+            )),
+        )
+    }
+
+    /// Resolve the wallet credited for a user's pushes: their primary wallet if delegated, otherwise themselves
+    /// A single hop only, delegating to a wallet that delegates itself being refused anyway
+    pub(super) fn _consumption_delegate_of(&self, user: Address) -> Address {
+        let primary = self.consumption_delegates.get(user);
+        if primary.is_zero() {
+            user
+        } else {
+            primary
+        }
+    }
+
+    /// Ensure a push batch doesn't exceed the configured maximum size, before processing any entry
+    pub(super) fn _ensure_batch_size(&self, len: usize) -> Result<(), Errors> {
+        let max_batch_size = self.max_batch_size.get();
+        if !max_batch_size.is_zero() && U256::from(len) > max_batch_size {
+            return Err(Errors::BatchTooLarge(BatchTooLarge {}));
+        }
+        Ok(())
+    }
+
+    /// Check if a batch should stop before its next entry, the gas left being under the batch reserve
+    pub(super) fn _is_batch_gas_exhausted(&self) -> bool {
+        U256::from(evm::gas_left()) < self.batch_gas_reserve.get()
+    }
+
+    /// Verify and credit a push signed by a quorum of validators (see `pushCcuMultiSig`)
+    pub(super) fn _push_ccu_multi_sig(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        vs: Vec<u8>,
+        rs: Vec<FixedBytes<32>>,
+        ss: Vec<FixedBytes<32>>,
+    ) -> Result<(), Errors> {
+        self._before_push()?;
+        self._ensure_deadline(deadline)?;
+        if vs.len() != rs.len() || vs.len() != ss.len() {
+            return Err(Errors::LengthMismatch(LengthMismatch {}));
+        }
+        if vs.len() > MAX_QUERY_LIMIT {
+            return Err(Errors::LimitTooLarge(LimitTooLarge {}));
+        }
+
+        // Rebuild the signed data, bound to the user's current nonce
+        let user = msg::sender();
+        let nonce = self.nonces.get(user);
+        let struct_hash =
+            Self::_consumption_struct_hash(user, channel_id, added_consumption, deadline, nonce);
+        self._ensure_not_revoked(struct_hash)?;
+        let digest = self._push_digest(struct_hash);
+        if self.used_or_cancelled.get(digest) {
+            return Err(Errors::SignatureCancelled(SignatureCancelled {}));
+        }
+
+        // Every signer must be accepted like a single signer push (channel override, expiry, rate limit),
+        // and enough distinct ones must have signed
+        let mut signers: Vec<Address> = Vec::with_capacity(vs.len());
+        for i in 0..vs.len() {
+            let signer = self
+                .eip712
+                .recover_typed_data_signer(struct_hash, vs[i], rs[i], ss[i])?;
+            // A repeated signer was already accepted, and only counts once
+            if !signers.contains(&signer) {
+                if !self._accept_channel_validator(channel_id, signer)? {
+                    return Err(Errors::InvalidPlatformSignature(
+                        InvalidPlatformSignature {},
+                    ));
+                }
+                signers.push(signer);
+            }
+        }
+        let quorum = self.validator_quorum.get().max(U256::from(1));
+        if U256::from(signers.len()) < quorum {
+            return Err(Errors::QuorumNotReached(QuorumNotReached {}));
+        }
+
+        // Credit the consumption, and consume the nonce and the signature (unless dropped by the circuit breaker)
+        let content_id = self.nutty_content_id.get();
+        if self
+            ._credit_consumption(user, content_id, channel_id, added_consumption)?
+            .is_none()
+        {
+            return Ok(());
+        }
+        self._consume_push(user, digest);
+        for signer in signers {
+            self._log_pushed_by(user, signer, channel_id);
+        }
+        Ok(())
+    }
+
+    /// Verify and credit a batch of pushes (see `pushCcuBatch`)
+    pub(super) fn _push_ccu_batch(
+        &mut self,
+        channel_ids: Vec<FixedBytes<32>>,
+        added_consumptions: Vec<U256>,
+        deadlines: Vec<U256>,
+        vs: Vec<u8>,
+        rs: Vec<FixedBytes<32>>,
+        ss: Vec<FixedBytes<32>>,
+        all_or_nothing: bool,
+    ) -> Result<(Vec<bool>, U256, bool), Errors> {
+        self._before_push()?;
+
+        // Ensure the arrays are consistent, and the batch bounded
+        let len = channel_ids.len();
+        if added_consumptions.len() != len
+            || deadlines.len() != len
+            || vs.len() != len
+            || rs.len() != len
+            || ss.len() != len
+        {
+            return Err(Errors::LengthMismatch(LengthMismatch {}));
+        }
+        self._ensure_batch_size(len)?;
+        if len > MAX_QUERY_LIMIT {
+            return Err(Errors::LimitTooLarge(LimitTooLarge {}));
+        }
+
+        let user = msg::sender();
+        let content_id = self.nutty_content_id.get();
+        let mut credited = Vec::with_capacity(len);
+        let mut authorized_validators = Vec::new();
+        for i in 0..len {
+            if self._is_batch_gas_exhausted() {
+                return Ok((credited, U256::from(i), false));
+            }
+
+            let verified = self._verify_push(
+                user,
+                channel_ids[i],
+                added_consumptions[i],
+                deadlines[i],
+                Address::ZERO,
+                vs[i],
+                rs[i],
+                ss[i],
+                &mut authorized_validators,
+            );
+            let (digest, validator) = match verified {
+                Ok(Some(verified)) => verified,
+                Ok(None) if all_or_nothing => {
+                    return Err(Errors::InvalidPlatformSignature(
+                        InvalidPlatformSignature {},
+                    ))
+                }
+                Err(error) if all_or_nothing => return Err(error),
+                // A failing registry would fail every entry, so it reverts the whole batch
+                Err(Errors::CallError(error)) => return Err(Errors::CallError(error)),
+                _ => {
+                    credited.push(false);
+                    continue;
+                }
+            };
+
+            // Dropped by the circuit breaker, every later entry would be too: stop there, so the batch can be
+            // resumed from this entry once unpaused
+            if self
+                ._credit_consumption(user, content_id, channel_ids[i], added_consumptions[i])?
+                .is_none()
+            {
+                return Ok((credited, U256::from(i), false));
+            }
+            self._consume_push(user, digest);
+            self._log_pushed_by(user, validator, channel_ids[i]);
+            credited.push(true);
+        }
+        Ok((credited, U256::from(len), true))
+    }
+
+    /// Verify and credit a push co-signed by a validator and the user (see `pushCcuDualSig`)
+    pub(super) fn _push_ccu_dual_sig(
+        &mut self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        validator_v: u8,
+        validator_r: FixedBytes<32>,
+        validator_s: FixedBytes<32>,
+        user_v: u8,
+        user_r: FixedBytes<32>,
+        user_s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._before_push()?;
+        self._ensure_deadline(deadline)?;
+
+        // Rebuild the signed data, bound to the user's current nonce
+        let nonce = self.nonces.get(user);
+        let struct_hash =
+            Self::_consumption_struct_hash(user, channel_id, added_consumption, deadline, nonce);
+        self._ensure_not_revoked(struct_hash)?;
+
+        // Ensure the signature wasn't already used, or cancelled by the platform
+        let digest = self._push_digest(struct_hash);
+        if self.used_or_cancelled.get(digest) {
+            return Err(Errors::SignatureCancelled(SignatureCancelled {}));
+        }
+
+        // The user signature must recover to the user
+        let user_signer =
+            self.eip712
+                .recover_typed_data_signer(struct_hash, user_v, user_r, user_s)?;
+        if user_signer != user {
+            return Err(Errors::InvalidUserSignature(InvalidUserSignature {}));
+        }
+
+        // The validator signature must recover to an authorized validator
+        let recovered_address = self.eip712.recover_typed_data_signer(
+            struct_hash,
+            validator_v,
+            validator_r,
+            validator_s,
+        )?;
+        if !self._accept_channel_validator(channel_id, recovered_address)? {
+            return self._reject_push();
+        }
+
+        // Credit the consumption, and consume the nonce and the signature (unless dropped by the circuit breaker)
+        let content_id = self.nutty_content_id.get();
+        if self
+            ._credit_consumption(user, content_id, channel_id, added_consumption)?
+            .is_none()
+        {
+            return Ok(());
+        }
+        self._consume_push(user, digest);
+        self._log_pushed_by(user, recovered_address, channel_id);
+        Ok(())
+    }
+
+    /// Verify and credit a push signed with `personal_sign` (see `pushCcuPersonalSign`)
+    pub(super) fn _push_ccu_personal_sign(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._before_push()?;
+        self._ensure_deadline(deadline)?;
+
+        // Rebuild the signed message, bound to the user's current nonce, the chain and this contract
+        let user = msg::sender();
+        let nonce = self.nonces.get(user);
+        let struct_hash =
+            Self::_consumption_struct_hash(user, channel_id, added_consumption, deadline, nonce);
+        self._ensure_not_revoked(struct_hash)?;
+        let message_hash = keccak(<sol! { (address, uint256, bytes32) }>::abi_encode(&(
+            contract::address(),
+            U256::from(block::chainid()),
+            struct_hash.0,
+        )));
+        let digest = Eip712::<ConsumptionParam>::personal_sign_digest(message_hash);
+        if self.used_or_cancelled.get(digest) {
+            return Err(Errors::SignatureCancelled(SignatureCancelled {}));
+        }
+
+        // The signature must recover to an authorized validator
+        let recovered_address = self.eip712.recover_personal_sign(message_hash, v, r, s)?;
+        if !self._accept_channel_validator(channel_id, recovered_address)? {
+            return self._reject_push();
+        }
+
+        // Credit the consumption, and consume the nonce and the signature (unless dropped by the circuit breaker)
+        let content_id = self.nutty_content_id.get();
+        if self
+            ._credit_consumption(user, content_id, channel_id, added_consumption)?
+            .is_none()
+        {
+            return Ok(());
+        }
+        self._consume_push(user, digest);
+        self._log_pushed_by(user, recovered_address, channel_id);
+        Ok(())
+    }
+
+    /// Verify and credit a push on a given content (see `pushCcuForContent`)
+    pub(super) fn _push_ccu_for_content(
+        &mut self,
+        content_id: U256,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._before_push()?;
+        self._ensure_deadline(deadline)?;
+
+        // Rebuild the signed data, bound to the user's current nonce
+        let user = msg::sender();
+        let nonce = self.nonces.get(user);
+        let struct_hash = Self::_content_struct_hash(
+            user,
+            content_id,
+            channel_id,
+            added_consumption,
+            deadline,
+            nonce,
+        );
+        self._ensure_not_revoked(struct_hash)?;
+        let digest = self._push_digest(struct_hash);
+        if self.used_or_cancelled.get(digest) {
+            return Err(Errors::SignatureCancelled(SignatureCancelled {}));
+        }
+
+        // The signature must recover to a validator authorized on this very content
+        let recovered_address = self
+            .eip712
+            .recover_typed_data_signer(struct_hash, v, r, s)?;
+        if !self._accept_channel_validator_for_content(content_id, channel_id, recovered_address)? {
+            return self._reject_push();
+        }
+
+        // Credit the consumption (also tracked per content), and consume the nonce and the signature
+        // (unless dropped by the circuit breaker)
+        if self
+            ._credit_consumption(user, content_id, channel_id, added_consumption)?
+            .is_none()
+        {
+            return Ok(());
+        }
+        self._consume_push(user, digest);
+        self._log_pushed_by(user, recovered_address, channel_id);
+        Ok(())
+    }
+
+    /// Verify and credit a push tied to a playback session (see `pushCcuWithSession`)
+    pub(super) fn _push_ccu_with_session(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        session_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._before_push()?;
+        self._ensure_deadline(deadline)?;
+
+        // Rebuild the signed data, bound to the user's current nonce
+        let user = msg::sender();
+        let nonce = self.nonces.get(user);
+        let struct_hash = Self::_session_struct_hash(
+            user,
+            channel_id,
+            session_id,
+            added_consumption,
+            deadline,
+            nonce,
+        );
+        self._ensure_not_revoked(struct_hash)?;
+        let digest = self._push_digest(struct_hash);
+        if self.used_or_cancelled.get(digest) {
+            return Err(Errors::SignatureCancelled(SignatureCancelled {}));
+        }
+
+        // The signature must recover to an authorized validator
+        let recovered_address = self
+            .eip712
+            .recover_typed_data_signer(struct_hash, v, r, s)?;
+        if !self._accept_channel_validator(channel_id, recovered_address)? {
+            return self._reject_push();
+        }
+
+        // Credit the consumption, and consume the nonce and the signature (unless dropped by the circuit breaker)
+        let content_id = self.nutty_content_id.get();
+        if self
+            ._credit_consumption(user, content_id, channel_id, added_consumption)?
+            .is_none()
+        {
+            return Ok(());
+        }
+        self._consume_push(user, digest);
+        self._log_pushed_by(user, recovered_address, channel_id);
+
+        evm::log(CcuPushedWithSession {
+            user,
+            channelId: channel_id,
+            sessionId: session_id,
+            totalConsumption: self.user_consumptions.get(user),
+        });
+        Ok(())
+    }
+
+    /// Verify and credit the increase of a cumulative session reading (see `pushCcuCumulative`)
+    pub(super) fn _push_ccu_cumulative(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        session_id: FixedBytes<32>,
+        cumulative_consumption: U256,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._before_push()?;
+        self._ensure_deadline(deadline)?;
+
+        // Rebuild the signed data, and check the validator
+        let user = msg::sender();
+        let struct_hash = Self::_cumulative_struct_hash(
+            user,
+            channel_id,
+            session_id,
+            cumulative_consumption,
+            deadline,
+        );
+        let recovered_address = self
+            .eip712
+            .recover_typed_data_signer(struct_hash, v, r, s)?;
+        if !self._accept_channel_validator(channel_id, recovered_address)? {
+            return self._reject_push();
+        }
+
+        // Compute the increase since the last reading of this session
+        let session_key = keccak([channel_id.0, session_id.0].concat());
+        let last_cumulative = self.session_cumulatives.getter(user).get(session_key);
+        if cumulative_consumption <= last_cumulative {
+            return Ok(());
+        }
+
+        // Credit the increase, and record the reading (unless dropped by the circuit breaker, so it can be sent again)
+        let content_id = self.nutty_content_id.get();
+        if self
+            ._credit_consumption(
+                user,
+                content_id,
+                channel_id,
+                cumulative_consumption - last_cumulative,
+            )?
+            .is_none()
+        {
+            return Ok(());
+        }
+        self.session_cumulatives
+            .setter(user)
+            .insert(session_key, cumulative_consumption);
+        Ok(())
+    }
+
+    /// Verify and credit a push carrying an idempotency key (see `pushCcuIdempotent`)
+    pub(super) fn _push_ccu_idempotent(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        deadline: U256,
+        idempotency_key: FixedBytes<32>,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<U256, Errors> {
+        self._before_push()?;
+        self._ensure_deadline(deadline)?;
+        self._ensure_within_key_retention(deadline)?;
+
+        // An already processed key is a no-op
+        let user = msg::sender();
+        let receipt_id = self._idempotent_receipt(user, idempotency_key);
+        if !receipt_id.is_zero() {
+            return Ok(receipt_id);
+        }
+
+        // Rebuild the signed data, ensuring it wasn't revoked, used or cancelled
+        let struct_hash = Self::_idempotent_struct_hash(
+            user,
+            channel_id,
+            added_consumption,
+            deadline,
+            idempotency_key,
+        );
+        self._ensure_not_revoked(struct_hash)?;
+        let digest = self._push_digest(struct_hash);
+        if self.used_or_cancelled.get(digest) {
+            return Err(Errors::SignatureCancelled(SignatureCancelled {}));
+        }
+
+        // Check the validator
+        let recovered_address = self
+            .eip712
+            .recover_typed_data_signer(struct_hash, v, r, s)?;
+        if !self._accept_channel_validator(channel_id, recovered_address)? {
+            self._reject_push()?;
+            return Ok(U256::ZERO);
+        }
+
+        // Credit the consumption (queued and escrowed pushes get their receipt too),
+        // and remember the key with its receipt, unless the push was dropped and can be retried
+        let content_id = self.nutty_content_id.get();
+        let Some(receipt_id) =
+            self._credit_consumption(user, content_id, channel_id, added_consumption)?
+        else {
+            return Ok(U256::ZERO);
+        };
+        self.used_or_cancelled.insert(digest, true);
+        let key = keccak([user.into_word().0, idempotency_key.0].concat());
+        let epoch = self._current_epoch();
+        self.idempotency_receipts
+            .setter(epoch)
+            .insert(key, receipt_id);
+        Ok(receipt_id)
+    }
+
+    /// Verify and credit a cohort leaf (see `pushCcuCohort`)
+    pub(super) fn _push_ccu_cohort(
+        &mut self,
+        user: Address,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+        proof: Vec<FixedBytes<32>>,
+        root: FixedBytes<32>,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<(), Errors> {
+        self._before_push()?;
+        self._ensure_deadline(deadline)?;
+
+        // Ensure the leaf is part of the cohort, and not credited yet
+        let leaf = Self::_cohort_leaf(user, channel_id, added_consumption);
+        if !merkle::verify(&proof, root, leaf) {
+            return Err(Errors::InvalidMerkleProof(InvalidMerkleProof {}));
+        }
+        let claim_key = keccak([root.0, leaf.0].concat());
+        if self.claimed_cohort_leaves.get(claim_key) {
+            return Err(Errors::CohortLeafAlreadyClaimed(
+                CohortLeafAlreadyClaimed {},
+            ));
+        }
+
+        // Check the validator signature once per cohort
+        let struct_hash = Self::_cohort_struct_hash(root, deadline);
+        let mut signer = self.cohort_signers.get(struct_hash);
+        if signer.is_zero() {
+            signer = self
+                .eip712
+                .recover_typed_data_signer(struct_hash, v, r, s)?;
+            if !self._accept_validator(signer)? {
+                return self._reject_push();
+            }
+            self.cohort_signers.insert(struct_hash, signer);
+        }
+
+        // A channel with a validator override only accepts the leaves of a cohort signed by that validator
+        let override_validator = self.channel_validators.get(channel_id);
+        if !override_validator.is_zero() && override_validator != signer {
+            return self._reject_push();
+        }
+
+        // Credit the leaf, and mark it claimed (unless dropped by the circuit breaker, so it can be claimed again)
+        let content_id = self.nutty_content_id.get();
+        if self
+            ._credit_consumption(user, content_id, channel_id, added_consumption)?
+            .is_none()
+        {
+            return Ok(());
+        }
+        self.claimed_cohort_leaves.insert(claim_key, true);
+        evm::log(CohortLeafClaimed {
+            root,
+            user,
+            channelId: channel_id,
+            amount: added_consumption,
+        });
+        Ok(())
+    }
+
+    /// Verify and credit a push spanning several contents (see `pushCcuMultiContent`)
+    pub(super) fn _push_ccu_multi_content(
+        &mut self,
+        content_ids: Vec<U256>,
+        channel_ids: Vec<FixedBytes<32>>,
+        added_consumptions: Vec<U256>,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<U256, Errors> {
+        self._before_push()?;
+        self._ensure_deadline(deadline)?;
+
+        // Ensure the entries are well formed
+        if content_ids.len() != channel_ids.len() || content_ids.len() != added_consumptions.len() {
+            return Err(Errors::LengthMismatch(LengthMismatch {}));
+        }
+        self._ensure_batch_size(content_ids.len())?;
+        if content_ids.len() > MAX_MULTI_CONTENT_ENTRIES {
+            return Err(Errors::LimitTooLarge(LimitTooLarge {}));
+        }
+
+        // Rebuild the signed data, bound to the user's current nonce
+        let user = msg::sender();
+        let nonce = self.nonces.get(user);
+        let struct_hash = Self::_multi_content_struct_hash(
+            user,
+            &content_ids,
+            &channel_ids,
+            &added_consumptions,
+            deadline,
+            nonce,
+        );
+        self._ensure_not_revoked(struct_hash)?;
+        let digest = self._push_digest(struct_hash);
+        if self.used_or_cancelled.get(digest) {
+            return Err(Errors::SignatureCancelled(SignatureCancelled {}));
+        }
+
+        // Recover the validator
+        let recovered_address = self
+            .eip712
+            .recover_typed_data_signer(struct_hash, v, r, s)?;
+
+        // Credit every entry not processed yet whose validator is accepted, stopping at the first one dropped by the circuit breaker
+        // (every later one would be too)
+        let previously_processed = self.multi_content_processed.get(digest);
+        let mut processed = previously_processed;
+        let mut credited = U256::ZERO;
+        for (i, ((content_id, channel_id), added_consumption)) in content_ids
+            .into_iter()
+            .zip(channel_ids.into_iter())
+            .zip(added_consumptions.into_iter())
+            .enumerate()
+        {
+            if processed.bit(i) {
+                continue;
+            }
+            if !self._accept_channel_validator_for_content(
+                content_id,
+                channel_id,
+                recovered_address,
+            )? {
+                processed |= U256::from(1) << i;
+                continue;
+            }
+
+            // Leave the signature valid, recording the entries processed so far, so that pushing it again
+            // once unpaused only credits the ones left
+            if self
+                ._credit_consumption(user, content_id, channel_id, added_consumption)?
+                .is_none()
+            {
+                self.multi_content_processed.insert(digest, processed);
+                return Ok(credited);
+            }
+            credited |= U256::from(1) << i;
+            processed |= U256::from(1) << i;
+        }
+
+        // Consume the nonce and the signature
+        if !previously_processed.is_zero() {
+            self.multi_content_processed.insert(digest, U256::ZERO);
+        }
+        self._consume_push(user, digest);
+        Ok(credited)
+    }
+}
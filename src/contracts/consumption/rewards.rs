@@ -0,0 +1,196 @@
+//! The eth and erc20 rewards, and the pushes queued during a rewards freeze
+
+use super::*;
+
+impl ChannelConsumptionContract {
+    /// Handle a push received while the rewards are frozen, depending on the freeze policy
+    /// Queuing costs four fresh storage slots per push (paid by the pusher), mostly refunded when credited
+    pub(super) fn _queue_frozen_push(
+        &mut self,
+        user: Address,
+        content_id: U256,
+        channel_id: FixedBytes<32>,
+        added_consumption: U256,
+    ) -> Result<(), Errors> {
+        if self.freeze_policy.get().to::<u8>() != FREEZE_POLICY_QUEUE
+            || self.frozen_push_users.len() >= MAX_FROZEN_PUSHES
+        {
+            return Err(Errors::RewardsFrozen(RewardsFrozen {}));
+        }
+        self.frozen_push_users.push(user);
+        self.frozen_push_contents.push(content_id);
+        self.frozen_push_channels.push(channel_id);
+        self.frozen_push_amounts.push(added_consumption);
+        evm::log(FrozenPushQueued {
+            user,
+            channelId: channel_id,
+            amount: added_consumption,
+        });
+        Ok(())
+    }
+
+    /// Credit every push queued during the freeze, exactly once, clearing the queue
+    /// A queued push breaking a rule at this point (e.g. a cap reached meanwhile) is dropped, not reverted,
+    /// so a single entry can't block the unfreeze; every rule is checked before the first write (see `_check_credit`),
+    /// so a dropped entry leaves no trace
+    /// A push dropped by the circuit breaker stops the crediting instead: it stays queued with the ones after it,
+    /// for the next unfreeze once the breaker is reset
+    pub(super) fn _credit_frozen_pushes(&mut self) -> Result<(), Errors> {
+        let len = self.frozen_push_users.len();
+        let mut processed = len;
+        for i in 0..len {
+            let user = self.frozen_push_users.get(i).unwrap_or_default();
+            let content_id = self.frozen_push_contents.get(i).unwrap_or_default();
+            let channel_id = self.frozen_push_channels.get(i).unwrap_or_default();
+            let amount = self.frozen_push_amounts.get(i).unwrap_or_default();
+            if self
+                ._check_credit(user, content_id, channel_id, amount)
+                .is_err()
+            {
+                evm::log(FrozenPushDropped {
+                    user,
+                    channelId: channel_id,
+                    amount,
+                });
+                continue;
+            }
+
+            // Past the checks, only an overflowing total can fail, reverting the whole unfreeze
+            if self
+                ._credit_consumption(user, content_id, channel_id, amount)?
+                .is_none()
+            {
+                processed = i;
+                break;
+            }
+        }
+
+        // Remove the processed entries, moving the ones left by the circuit breaker to the front of the queue
+        for i in processed..len {
+            let j = i - processed;
+            let user = self.frozen_push_users.get(i).unwrap_or_default();
+            let content_id = self.frozen_push_contents.get(i).unwrap_or_default();
+            let channel_id = self.frozen_push_channels.get(i).unwrap_or_default();
+            let amount = self.frozen_push_amounts.get(i).unwrap_or_default();
+            if let Some(mut slot) = self.frozen_push_users.setter(j) {
+                slot.set(user);
+            }
+            if let Some(mut slot) = self.frozen_push_contents.setter(j) {
+                slot.set(content_id);
+            }
+            if let Some(mut slot) = self.frozen_push_channels.setter(j) {
+                slot.set(channel_id);
+            }
+            if let Some(mut slot) = self.frozen_push_amounts.setter(j) {
+                slot.set(amount);
+            }
+        }
+        for _ in 0..processed {
+            self.frozen_push_users.pop();
+            self.frozen_push_contents.pop();
+            self.frozen_push_channels.pop();
+            self.frozen_push_amounts.pop();
+        }
+        Ok(())
+    }
+
+    /// Move the caller's unclaimed eth rewards to their withdrawable balance (see `claimRewards`)
+    pub(super) fn _claim_rewards(&mut self) -> Result<U256, Errors> {
+        self._ensure_unlocked()?;
+        let user = msg::sender();
+
+        // Compute the unclaimed rewards
+        let accrued = self.accrued_rewards.get(user);
+        let amount = accrued - self.claimed_rewards.get(user);
+        if amount.is_zero() {
+            return Err(Errors::NothingToClaim(NothingToClaim {}));
+        }
+
+        // Mark them as claimed, and make them withdrawable
+        self.claimed_rewards.insert(user, accrued);
+        let mut withdrawable_ptr = self.withdrawable.setter(user);
+        let withdrawable = withdrawable_ptr.get() + amount;
+        withdrawable_ptr.set(withdrawable);
+
+        evm::log(RewardsClaimed { user, amount });
+        Ok(amount)
+    }
+
+    /// Send the caller their eth withdrawable balance (see `withdraw`)
+    pub(super) fn _withdraw(&mut self) -> Result<(), Errors> {
+        self._ensure_unlocked()?;
+        let user = msg::sender();
+
+        // Checks
+        let amount = self.withdrawable.get(user);
+        if amount.is_zero() {
+            return Err(Errors::NothingToWithdraw(NothingToWithdraw {}));
+        }
+
+        // Effects
+        self.withdrawable.insert(user, U256::ZERO);
+
+        // Interactions (holding the call lock, so the recipient can only read our state)
+        self.call_lock.set(true);
+        let transfer_result = transfer_eth(self, user, amount);
+        self.call_lock.set(false);
+        transfer_result.map_err(|_| Errors::WithdrawFailed(WithdrawFailed {}))?;
+
+        evm::log(Withdrawn { user, amount });
+        Ok(())
+    }
+
+    /// Transfer the caller's erc20 rewards (see `claimTokenRewards`)
+    pub(super) fn _claim_token_rewards(&mut self) -> Result<U256, Errors> {
+        self._ensure_unlocked()?;
+        let user = msg::sender();
+
+        // Checks
+        let consumption = self.user_consumptions.get(user);
+        let unrewarded = consumption.saturating_sub(self.token_rewarded_consumptions.get(user));
+        let amount = unrewarded.saturating_mul(self.token_reward_rate.get());
+        let token = self.reward_token.get();
+        if amount.is_zero() || token.is_zero() {
+            return Err(Errors::NothingToClaim(NothingToClaim {}));
+        }
+
+        // Effects (burning the rewarded consumption if configured, the snapshots keeping the historical value)
+        if self.burn_on_claim.get() {
+            let remaining = consumption
+                .checked_sub(unrewarded)
+                .ok_or(Errors::InsufficientConsumption(InsufficientConsumption {}))?;
+            self._snapshot_user_consumption(user);
+            self.user_consumptions.insert(user, remaining);
+            let total = self
+                .total_consumption
+                .get()
+                .checked_sub(unrewarded)
+                .ok_or(Errors::InsufficientConsumption(InsufficientConsumption {}))?;
+            self.total_consumption.set(total);
+            self._update_team_consumption(user, unrewarded, false);
+            self.token_rewarded_consumptions.insert(user, remaining);
+            evm::log(ConsumptionBurned {
+                user,
+                amount: unrewarded,
+            });
+        } else {
+            self.token_rewarded_consumptions.insert(user, consumption);
+        }
+
+        // Interactions (holding the call lock, so the token can only read our state)
+        self.call_lock.set(true);
+        let transferred = call_helper::<transferCall>(self, token, (user, amount));
+        self.call_lock.set(false);
+        match transferred {
+            Ok(transferred) if transferred._0 => {}
+            _ => return Err(Errors::RewardTransferFailed(RewardTransferFailed {})),
+        }
+
+        evm::log(TokenRewardsClaimed {
+            user,
+            consumption: unrewarded,
+            amount,
+        });
+        Ok(amount)
+    }
+}
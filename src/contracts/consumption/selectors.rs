@@ -0,0 +1,514 @@
+//! The callable selectors, for introspection (`supportedSelectors`) and ERC-165 (`supportsInterface`)
+
+use alloy_sol_types::SolCall;
+
+// Every callable function (own and inherited), backing `supportedSelectors`
+// Must be kept in sync with the public impl
+mod supported {
+    use alloy_sol_types::sol;
+
+    sol! {
+        function initialize(address owner, uint256 nuttyContentId, address contentRegistry);
+        function setEip712NameAndVersion(string name, string version);
+        function getEip712NameAndVersion();
+        function transferOwnership(address newOwner);
+        function acceptOwnership();
+        function pendingOwner();
+        function renounceOwnership();
+        function pause();
+        function unpause();
+        function isPaused();
+        function setCircuitBreaker(uint256 maxVelocity, uint256 window);
+        function getCircuitBreaker();
+        function setChannelDailyCap(uint256 cap);
+        function setMaxConsumptionPerPush(uint256 maxPerPush);
+        function setMinConsumptionPerPush(uint256 minPerPush);
+        function getMinConsumptionPerPush();
+        function setCaps(uint256 maxPerPush, uint256 maxUser, uint256 maxTotal, uint256 dailyCap);
+        function getCaps();
+        function setUserCapClamping(bool clamp);
+        function isUserCapClamped();
+        function setMinGasForPush(uint256 minGas);
+        function setDecayRate(uint256 rateBps);
+        function getDecayRate();
+        function setMilestoneStep(uint256 step);
+        function getMilestoneStep();
+        function getMinGasForPush();
+        function setMinPushInterval(uint256 interval);
+        function getMinPushInterval();
+        function setMinChannelPushInterval(uint256 interval);
+        function getMinChannelPushInterval();
+        function setRejectSameBlockPushes(bool reject);
+        function isRejectingSameBlockPushes();
+        function setValidationMode(uint8 mode, address platformSigner, address registry);
+        function getValidationMode();
+        function getValidationAddresses();
+        function setValidatorAllowed(address validator, bool allowed);
+        function addValidator(address validator);
+        function removeValidator(address validator);
+        function isLocalValidator(address validator);
+        function setValidatorValidityPeriod(uint256 period);
+        function getValidatorValidityPeriod();
+        function activateValidator(address validator);
+        function getValidatorExpiry(address validator);
+        function setBatchGasReserve(uint256 reserve);
+        function getBatchGasReserve();
+        function setMaxBatchSize(uint256 maxBatchSize);
+        function getMaxBatchSize();
+        function isValidatorAllowed(address validator);
+        function setMaxPushesPerEpoch(uint256 maxPushes);
+        function getMaxPushesPerEpoch();
+        function setAllowZeroConsumption(bool allowed);
+        function isZeroConsumptionAllowed();
+        function setAllowEip155V(bool allowed);
+        function isEip155VAllowed();
+        function setValidatorRateLimit(uint256 maxPushes, uint256 window);
+        function getValidatorRateLimit();
+        function setVelocityAnomalyConfig(uint256 multiple, uint256 window);
+        function setMaxConsumptionRate(uint256 maxRate, bool reject);
+        function getMaxConsumptionRate();
+        function setMaxChannelsPerWindow(uint256 maxChannels, bool reject);
+        function getMaxChannelsPerWindow();
+        function getUserDailyChannelCount(address user, uint256 dayIndex);
+        function clearFlag(address user);
+        function getVelocityAnomalyConfig();
+        function advanceEpoch();
+        function setEpochDuration(uint256 duration);
+        function getEpochConfig();
+        function currentEpoch();
+        function getEpochPushCount(uint256 epoch, address user);
+        function getEpochTotals(uint256 fromEpoch, uint256 toEpoch);
+        function freezeRewards();
+        function unfreezeRewards();
+        function setEventMode(uint8 mode);
+        function getEventMode();
+        function setFreezePolicy(uint8 policy);
+        function getRewardsFreeze();
+        function setContentRewardRate(uint256 contentId, uint256 rate);
+        function getContentRewardRate(uint256 contentId);
+        function getAccruedRewards(address user);
+        function fundRewards();
+        function claimRewards();
+        function withdraw();
+        function getWithdrawable(address user);
+        function setTokenRewards(address token, uint256 rate);
+        function getTokenRewards();
+        function setBurnOnClaim(bool burn);
+        function isBurnOnClaim();
+        function getClaimableTokenRewards(address user);
+        function claimTokenRewards();
+        function getClaimedRewards(address user);
+        function getUserContentConsumption(uint256 contentId, address user);
+        function setChannelDenied(bytes32 channelId, bool denied);
+        function computeChannelId(uint256 contentId, uint256 subId);
+        function registerChannel(bytes32 channelId, address validator, uint256 cap, uint256 multiplierBps, uint256 contentId, bytes metadata);
+        function setCheckContentExistence(bool checked);
+        function isContentExistenceChecked();
+        function setContentTypeMultiplier(uint256 contentTypes, uint256 multiplierBps);
+        function getContentTypeMultiplier(uint256 contentTypes);
+        function getChannelRegistration(bytes32 channelId);
+        function setRequireChannelRegistration(bool required);
+        function isChannelRegistrationRequired();
+        function bindChannel(uint256 contentId, uint256 subId);
+        function getChannelContent(bytes32 channelId);
+        function setEnforceChannelBinding(bool enforced);
+        function isChannelBindingEnforced();
+        function isChannelDenied(bytes32 channelId);
+        function setHistogramBuckets(uint256[] upperBounds);
+        function getHistogramBuckets();
+        function getChannelBucketCount(bytes32 channelId, uint256 bucket);
+        function getChannelMedianBucket(bytes32 channelId);
+        function commitSnapshotRoot(uint256 snapshotId, bytes32 root);
+        function getSnapshotRoot(uint256 snapshotId);
+        function snapshot();
+        function getCurrentSnapshotId();
+        function getSnapshotTotal(uint256 id);
+        function getUserConsumptionAt(address user, uint256 id);
+        function verifyConsumptionDelta(address user, uint256 amountA, bytes32[] proofA, uint256 amountB, bytes32[] proofB, uint256 snapA, uint256 snapB);
+        function setConfigTimelock(uint256 delay);
+        function getConfigTimelock();
+        function queueConfigChange(bytes4 selector, bytes params);
+        function executeConfigChange(bytes32 id);
+        function getConfigChangeEta(bytes32 id);
+        function getChannelDailyCap();
+        function pushCcu(bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint8 v, bytes32 r, bytes32 s);
+        function pushCcuChecked(bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint8 v, bytes32 r, bytes32 s);
+        function pushCcuFor(address user, bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint8 v, bytes32 r, bytes32 s);
+        function pushCcuMultiSig(bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint8[] vs, bytes32[] rs, bytes32[] ss);
+        function pushCcuPacked(bytes32 channelId, uint256 addedConsumption, uint256 deadline, bytes signature);
+        function pushCcuCompact(bytes32 channelId, uint256 addedConsumption, uint256 deadline, bytes32 r, bytes32 vs);
+        function pushCcuLegacyV(bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint256 v, bytes32 r, bytes32 s);
+        function pushCcuWithHint(bytes32 channelId, uint256 addedConsumption, uint256 deadline, address expectedValidator, uint8 v, bytes32 r, bytes32 s);
+        function pushCcuBatch(bytes32[] channelIds, uint256[] addedConsumptions, uint256[] deadlines, uint8[] vs, bytes32[] rs, bytes32[] ss, bool allOrNothing);
+        function setStrictMode(bool strict);
+        function isStrictMode();
+        function setStrictValidatorHint(bool strict);
+        function isStrictValidatorHint();
+        function setSilentRegistryFailures(bool silent);
+        function areRegistryFailuresSilent();
+        function setValidatorQuorum(uint256 quorum);
+        function getValidatorQuorum();
+        function setContractValidatorsAllowed(bool allowed);
+        function areContractValidatorsAllowed();
+        function setTrustedForwarder(address forwarder);
+        function isTrustedForwarder(address forwarder);
+        function invalidateSignature(bytes32 digest);
+        function cancelSignature(bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint256 nonce);
+        function isSignatureRevoked(bytes32 structHash);
+        function isSignatureUsedOrCancelled(bytes32 digest);
+        function pushCcuDualSig(bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint8 validatorV, bytes32 validatorR, bytes32 validatorS, uint8 userV, bytes32 userR, bytes32 userS);
+        function pushCcuPersonalSign(bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint8 v, bytes32 r, bytes32 s);
+        function pushCcuForContent(uint256 contentId, bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint8 v, bytes32 r, bytes32 s);
+        function pushCcuWithSession(bytes32 channelId, bytes32 sessionId, uint256 addedConsumption, uint256 deadline, uint8 v, bytes32 r, bytes32 s);
+        function setChallengePeriod(uint256 period);
+        function getChallengePeriod();
+        function confirmPending(address user);
+        function challengePending(address user, uint256 amount);
+        function getPendingConsumption(address user);
+        function pushCcuCumulative(bytes32 channelId, bytes32 sessionId, uint256 cumulativeConsumption, uint256 deadline, uint8 v, bytes32 r, bytes32 s);
+        function getSessionCumulative(address user, bytes32 channelId, bytes32 sessionId);
+        function pushCcuIdempotent(bytes32 channelId, uint256 addedConsumption, uint256 deadline, bytes32 idempotencyKey, uint8 v, bytes32 r, bytes32 s);
+        function pushCcuCohort(address user, bytes32 channelId, uint256 addedConsumption, bytes32[] proof, bytes32 root, uint256 deadline, uint8 v, bytes32 r, bytes32 s);
+        function isCohortLeafClaimed(bytes32 root, address user, bytes32 channelId, uint256 addedConsumption);
+        function pushCcuMultiContent(uint256[] contentIds, bytes32[] channelIds, uint256[] addedConsumptions, uint256 deadline, uint8 v, bytes32 r, bytes32 s);
+        function resetUserConsumption(address user);
+        function resetUserConsumptionBatch(address[] users);
+        function decreaseUserConsumption(address user, uint256 amount);
+        function setUserConsumption(address user, uint256 newValue);
+        function migrateConsumption(address[] users, uint256[] amounts);
+        function finalizeMigration();
+        function isMigrated();
+        function exportConsumption(address[] users);
+        function backfillConsumption(address[] users, uint256[] amounts);
+        function applyPenalty(address user, uint256 penaltyBps, uint256 deadline, uint8 v, bytes32 r, bytes32 s);
+        function getNonce(address user);
+        function getSigningContext(address user);
+        function getPenaltyNonce(address user);
+        function simulatePush(address user, bytes32 channelId, uint256 addedConsumption);
+        function effectiveCreditedAmount(address user, bytes32 channelId, uint256 requested);
+        function verifyCcuSignature(address user, bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint8 v, bytes32 r, bytes32 s);
+        function setDeadlineGrace(uint256 grace);
+        function getDeadlineGrace();
+        function getConsumptionTypeHash();
+        function computeConsumptionStructHash(address user, bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint256 nonce);
+        function isValidIn(uint256 deadline, uint256 secondsAhead);
+        function pushDigestPreimage(address user, bytes32 channelId, uint256 addedConsumption, uint256 deadline);
+        function pushDigest(address user, bytes32 channelId, uint256 addedConsumption, uint256 deadline);
+        function getUserConsumption(address user);
+        function getUserConsumptionDecayed(address user);
+        function getUserConsumptionForDay(address user, uint256 dayIndex);
+        function getUserCount();
+        function getConsumptionStats(address user);
+        function getUsers(uint256 offset, uint256 limit);
+        function getUserConsumptionBatch(address[] users);
+        function setUserTeam(address user, bytes32 teamId);
+        function getUserTeam(address user);
+        function setConsumptionDelegate(address primary);
+        function acceptConsumptionDelegate(address secondary);
+        function getConsumptionDelegate(address secondary);
+        function getTeamConsumption(bytes32 teamId);
+        function getUserConsumptionWithMeta(address user);
+        function getUserData(address user);
+        function getUserChannelCount(address user);
+        function getChannelCount();
+        function getChannelTotalConsumption(bytes32 channelId);
+        function getUserChannelConsumption(address user, bytes32 channelId);
+        function getUserChannelConsumptionBatch(address user, bytes32[] channelIds);
+        function isRateLimited(address user);
+        function getLastPushAt(address user);
+        function getLastUpdate(address user);
+        function exportUserData(address user);
+        function amIAuthorizedValidator();
+        function getChannelsTotalConsumption(bytes32[] channels);
+        function isFlagged(address user);
+        function getRejectedPushCount();
+        function errorCode(bytes4 selector);
+        function errorSelector(uint16 code);
+        function supportedSelectors();
+        function supportsInterface(bytes4 interfaceId);
+        function getAcceptedPushCount();
+        function getRejectionRateBps();
+        function getTotalConsumption();
+        function getNuttyContentId();
+        function setContentRegistry(address newRegistry);
+        function getContentRegistry();
+        function owner();
+        function hasRole(bytes32 role, address account);
+        function getRoleAdmin(bytes32 role);
+        function grantRole(bytes32 role, address account);
+        function revokeRole(bytes32 role, address account);
+        function renounceRole(bytes32 role, address callerConfirmation);
+        function domainSeparator();
+        function domainSeparatorStatus();
+        function verifyingContractMatches();
+        function verifyDomainBatch(bytes32[] expectedSeparators);
+        function refreshDomainSeparator();
+        function eip712Domain();
+    }
+}
+
+/// The selectors of every callable function
+pub const SUPPORTED_SELECTORS: [[u8; 4]; 236] = [
+    supported::initializeCall::SELECTOR,
+    supported::setEip712NameAndVersionCall::SELECTOR,
+    supported::getEip712NameAndVersionCall::SELECTOR,
+    supported::transferOwnershipCall::SELECTOR,
+    supported::acceptOwnershipCall::SELECTOR,
+    supported::pendingOwnerCall::SELECTOR,
+    supported::renounceOwnershipCall::SELECTOR,
+    supported::pauseCall::SELECTOR,
+    supported::unpauseCall::SELECTOR,
+    supported::isPausedCall::SELECTOR,
+    supported::setCircuitBreakerCall::SELECTOR,
+    supported::getCircuitBreakerCall::SELECTOR,
+    supported::setChannelDailyCapCall::SELECTOR,
+    supported::setMaxConsumptionPerPushCall::SELECTOR,
+    supported::setMinConsumptionPerPushCall::SELECTOR,
+    supported::getMinConsumptionPerPushCall::SELECTOR,
+    supported::setCapsCall::SELECTOR,
+    supported::getCapsCall::SELECTOR,
+    supported::setUserCapClampingCall::SELECTOR,
+    supported::isUserCapClampedCall::SELECTOR,
+    supported::setMinGasForPushCall::SELECTOR,
+    supported::setDecayRateCall::SELECTOR,
+    supported::getDecayRateCall::SELECTOR,
+    supported::setMilestoneStepCall::SELECTOR,
+    supported::getMilestoneStepCall::SELECTOR,
+    supported::getMinGasForPushCall::SELECTOR,
+    supported::setMinPushIntervalCall::SELECTOR,
+    supported::getMinPushIntervalCall::SELECTOR,
+    supported::setMinChannelPushIntervalCall::SELECTOR,
+    supported::getMinChannelPushIntervalCall::SELECTOR,
+    supported::setRejectSameBlockPushesCall::SELECTOR,
+    supported::isRejectingSameBlockPushesCall::SELECTOR,
+    supported::setValidationModeCall::SELECTOR,
+    supported::getValidationModeCall::SELECTOR,
+    supported::getValidationAddressesCall::SELECTOR,
+    supported::setValidatorAllowedCall::SELECTOR,
+    supported::addValidatorCall::SELECTOR,
+    supported::removeValidatorCall::SELECTOR,
+    supported::isLocalValidatorCall::SELECTOR,
+    supported::setValidatorValidityPeriodCall::SELECTOR,
+    supported::getValidatorValidityPeriodCall::SELECTOR,
+    supported::activateValidatorCall::SELECTOR,
+    supported::getValidatorExpiryCall::SELECTOR,
+    supported::setBatchGasReserveCall::SELECTOR,
+    supported::getBatchGasReserveCall::SELECTOR,
+    supported::setMaxBatchSizeCall::SELECTOR,
+    supported::getMaxBatchSizeCall::SELECTOR,
+    supported::isValidatorAllowedCall::SELECTOR,
+    supported::setMaxPushesPerEpochCall::SELECTOR,
+    supported::getMaxPushesPerEpochCall::SELECTOR,
+    supported::setAllowZeroConsumptionCall::SELECTOR,
+    supported::isZeroConsumptionAllowedCall::SELECTOR,
+    supported::setAllowEip155VCall::SELECTOR,
+    supported::isEip155VAllowedCall::SELECTOR,
+    supported::setValidatorRateLimitCall::SELECTOR,
+    supported::getValidatorRateLimitCall::SELECTOR,
+    supported::setVelocityAnomalyConfigCall::SELECTOR,
+    supported::setMaxConsumptionRateCall::SELECTOR,
+    supported::getMaxConsumptionRateCall::SELECTOR,
+    supported::setMaxChannelsPerWindowCall::SELECTOR,
+    supported::getMaxChannelsPerWindowCall::SELECTOR,
+    supported::getUserDailyChannelCountCall::SELECTOR,
+    supported::clearFlagCall::SELECTOR,
+    supported::getVelocityAnomalyConfigCall::SELECTOR,
+    supported::advanceEpochCall::SELECTOR,
+    supported::setEpochDurationCall::SELECTOR,
+    supported::getEpochConfigCall::SELECTOR,
+    supported::currentEpochCall::SELECTOR,
+    supported::getEpochPushCountCall::SELECTOR,
+    supported::getEpochTotalsCall::SELECTOR,
+    supported::freezeRewardsCall::SELECTOR,
+    supported::unfreezeRewardsCall::SELECTOR,
+    supported::setEventModeCall::SELECTOR,
+    supported::getEventModeCall::SELECTOR,
+    supported::setFreezePolicyCall::SELECTOR,
+    supported::getRewardsFreezeCall::SELECTOR,
+    supported::setContentRewardRateCall::SELECTOR,
+    supported::getContentRewardRateCall::SELECTOR,
+    supported::getAccruedRewardsCall::SELECTOR,
+    supported::fundRewardsCall::SELECTOR,
+    supported::claimRewardsCall::SELECTOR,
+    supported::withdrawCall::SELECTOR,
+    supported::getWithdrawableCall::SELECTOR,
+    supported::setTokenRewardsCall::SELECTOR,
+    supported::getTokenRewardsCall::SELECTOR,
+    supported::setBurnOnClaimCall::SELECTOR,
+    supported::isBurnOnClaimCall::SELECTOR,
+    supported::getClaimableTokenRewardsCall::SELECTOR,
+    supported::claimTokenRewardsCall::SELECTOR,
+    supported::getClaimedRewardsCall::SELECTOR,
+    supported::getUserContentConsumptionCall::SELECTOR,
+    supported::setChannelDeniedCall::SELECTOR,
+    supported::computeChannelIdCall::SELECTOR,
+    supported::registerChannelCall::SELECTOR,
+    supported::setCheckContentExistenceCall::SELECTOR,
+    supported::isContentExistenceCheckedCall::SELECTOR,
+    supported::setContentTypeMultiplierCall::SELECTOR,
+    supported::getContentTypeMultiplierCall::SELECTOR,
+    supported::getChannelRegistrationCall::SELECTOR,
+    supported::setRequireChannelRegistrationCall::SELECTOR,
+    supported::isChannelRegistrationRequiredCall::SELECTOR,
+    supported::bindChannelCall::SELECTOR,
+    supported::getChannelContentCall::SELECTOR,
+    supported::setEnforceChannelBindingCall::SELECTOR,
+    supported::isChannelBindingEnforcedCall::SELECTOR,
+    supported::isChannelDeniedCall::SELECTOR,
+    supported::setHistogramBucketsCall::SELECTOR,
+    supported::getHistogramBucketsCall::SELECTOR,
+    supported::getChannelBucketCountCall::SELECTOR,
+    supported::getChannelMedianBucketCall::SELECTOR,
+    supported::commitSnapshotRootCall::SELECTOR,
+    supported::getSnapshotRootCall::SELECTOR,
+    supported::snapshotCall::SELECTOR,
+    supported::getCurrentSnapshotIdCall::SELECTOR,
+    supported::getSnapshotTotalCall::SELECTOR,
+    supported::getUserConsumptionAtCall::SELECTOR,
+    supported::verifyConsumptionDeltaCall::SELECTOR,
+    supported::setConfigTimelockCall::SELECTOR,
+    supported::getConfigTimelockCall::SELECTOR,
+    supported::queueConfigChangeCall::SELECTOR,
+    supported::executeConfigChangeCall::SELECTOR,
+    supported::getConfigChangeEtaCall::SELECTOR,
+    supported::getChannelDailyCapCall::SELECTOR,
+    supported::pushCcuCall::SELECTOR,
+    supported::pushCcuCheckedCall::SELECTOR,
+    supported::pushCcuForCall::SELECTOR,
+    supported::pushCcuMultiSigCall::SELECTOR,
+    supported::pushCcuPackedCall::SELECTOR,
+    supported::pushCcuCompactCall::SELECTOR,
+    supported::pushCcuLegacyVCall::SELECTOR,
+    supported::pushCcuWithHintCall::SELECTOR,
+    supported::pushCcuBatchCall::SELECTOR,
+    supported::setStrictModeCall::SELECTOR,
+    supported::isStrictModeCall::SELECTOR,
+    supported::setStrictValidatorHintCall::SELECTOR,
+    supported::isStrictValidatorHintCall::SELECTOR,
+    supported::setSilentRegistryFailuresCall::SELECTOR,
+    supported::areRegistryFailuresSilentCall::SELECTOR,
+    supported::setValidatorQuorumCall::SELECTOR,
+    supported::getValidatorQuorumCall::SELECTOR,
+    supported::setContractValidatorsAllowedCall::SELECTOR,
+    supported::areContractValidatorsAllowedCall::SELECTOR,
+    supported::setTrustedForwarderCall::SELECTOR,
+    supported::isTrustedForwarderCall::SELECTOR,
+    supported::invalidateSignatureCall::SELECTOR,
+    supported::cancelSignatureCall::SELECTOR,
+    supported::isSignatureRevokedCall::SELECTOR,
+    supported::isSignatureUsedOrCancelledCall::SELECTOR,
+    supported::pushCcuDualSigCall::SELECTOR,
+    supported::pushCcuPersonalSignCall::SELECTOR,
+    supported::pushCcuForContentCall::SELECTOR,
+    supported::pushCcuWithSessionCall::SELECTOR,
+    supported::setChallengePeriodCall::SELECTOR,
+    supported::getChallengePeriodCall::SELECTOR,
+    supported::confirmPendingCall::SELECTOR,
+    supported::challengePendingCall::SELECTOR,
+    supported::getPendingConsumptionCall::SELECTOR,
+    supported::pushCcuCumulativeCall::SELECTOR,
+    supported::getSessionCumulativeCall::SELECTOR,
+    supported::pushCcuIdempotentCall::SELECTOR,
+    supported::pushCcuCohortCall::SELECTOR,
+    supported::isCohortLeafClaimedCall::SELECTOR,
+    supported::pushCcuMultiContentCall::SELECTOR,
+    supported::resetUserConsumptionCall::SELECTOR,
+    supported::resetUserConsumptionBatchCall::SELECTOR,
+    supported::decreaseUserConsumptionCall::SELECTOR,
+    supported::setUserConsumptionCall::SELECTOR,
+    supported::migrateConsumptionCall::SELECTOR,
+    supported::finalizeMigrationCall::SELECTOR,
+    supported::isMigratedCall::SELECTOR,
+    supported::exportConsumptionCall::SELECTOR,
+    supported::backfillConsumptionCall::SELECTOR,
+    supported::applyPenaltyCall::SELECTOR,
+    supported::getNonceCall::SELECTOR,
+    supported::getSigningContextCall::SELECTOR,
+    supported::getPenaltyNonceCall::SELECTOR,
+    supported::simulatePushCall::SELECTOR,
+    supported::effectiveCreditedAmountCall::SELECTOR,
+    supported::verifyCcuSignatureCall::SELECTOR,
+    supported::setDeadlineGraceCall::SELECTOR,
+    supported::getDeadlineGraceCall::SELECTOR,
+    supported::getConsumptionTypeHashCall::SELECTOR,
+    supported::computeConsumptionStructHashCall::SELECTOR,
+    supported::isValidInCall::SELECTOR,
+    supported::pushDigestPreimageCall::SELECTOR,
+    supported::pushDigestCall::SELECTOR,
+    supported::getUserConsumptionCall::SELECTOR,
+    supported::getUserConsumptionDecayedCall::SELECTOR,
+    supported::getUserConsumptionForDayCall::SELECTOR,
+    supported::getUserCountCall::SELECTOR,
+    supported::getConsumptionStatsCall::SELECTOR,
+    supported::getUsersCall::SELECTOR,
+    supported::getUserConsumptionBatchCall::SELECTOR,
+    supported::setUserTeamCall::SELECTOR,
+    supported::getUserTeamCall::SELECTOR,
+    supported::setConsumptionDelegateCall::SELECTOR,
+    supported::acceptConsumptionDelegateCall::SELECTOR,
+    supported::getConsumptionDelegateCall::SELECTOR,
+    supported::getTeamConsumptionCall::SELECTOR,
+    supported::getUserConsumptionWithMetaCall::SELECTOR,
+    supported::getUserDataCall::SELECTOR,
+    supported::getUserChannelCountCall::SELECTOR,
+    supported::getChannelCountCall::SELECTOR,
+    supported::getChannelTotalConsumptionCall::SELECTOR,
+    supported::getUserChannelConsumptionCall::SELECTOR,
+    supported::getUserChannelConsumptionBatchCall::SELECTOR,
+    supported::isRateLimitedCall::SELECTOR,
+    supported::getLastPushAtCall::SELECTOR,
+    supported::getLastUpdateCall::SELECTOR,
+    supported::exportUserDataCall::SELECTOR,
+    supported::amIAuthorizedValidatorCall::SELECTOR,
+    supported::getChannelsTotalConsumptionCall::SELECTOR,
+    supported::isFlaggedCall::SELECTOR,
+    supported::getRejectedPushCountCall::SELECTOR,
+    supported::errorCodeCall::SELECTOR,
+    supported::errorSelectorCall::SELECTOR,
+    supported::supportedSelectorsCall::SELECTOR,
+    supported::supportsInterfaceCall::SELECTOR,
+    supported::getAcceptedPushCountCall::SELECTOR,
+    supported::getRejectionRateBpsCall::SELECTOR,
+    supported::getTotalConsumptionCall::SELECTOR,
+    supported::getNuttyContentIdCall::SELECTOR,
+    supported::setContentRegistryCall::SELECTOR,
+    supported::getContentRegistryCall::SELECTOR,
+    supported::ownerCall::SELECTOR,
+    supported::hasRoleCall::SELECTOR,
+    supported::getRoleAdminCall::SELECTOR,
+    supported::grantRoleCall::SELECTOR,
+    supported::revokeRoleCall::SELECTOR,
+    supported::renounceRoleCall::SELECTOR,
+    supported::domainSeparatorCall::SELECTOR,
+    supported::domainSeparatorStatusCall::SELECTOR,
+    supported::verifyingContractMatchesCall::SELECTOR,
+    supported::verifyDomainBatchCall::SELECTOR,
+    supported::refreshDomainSeparatorCall::SELECTOR,
+    supported::eip712DomainCall::SELECTOR,
+];
+
+/// The ERC-165 interface id of ERC-165 itself
+pub const ERC165_INTERFACE_ID: [u8; 4] = [0x01, 0xff, 0xc9, 0xa7];
+
+/// The interface id of the consumption contract, XOR of its core selectors (`pushCcu`, `getUserConsumption`,
+/// `getTotalConsumption`), for aggregators to detect a consumption contract before pushing to it
+pub const CONSUMPTION_INTERFACE_ID: [u8; 4] = xor_selectors(&[
+    supported::pushCcuCall::SELECTOR,
+    supported::getUserConsumptionCall::SELECTOR,
+    supported::getTotalConsumptionCall::SELECTOR,
+]);
+
+/// XOR a list of selectors into an ERC-165 interface id
+const fn xor_selectors(selectors: &[[u8; 4]]) -> [u8; 4] {
+    let mut interface_id = [0u8; 4];
+    let mut i = 0;
+    while i < selectors.len() {
+        let mut j = 0;
+        while j < 4 {
+            interface_id[j] ^= selectors[i][j];
+            j += 1;
+        }
+        i += 1;
+    }
+    interface_id
+}
@@ -0,0 +1,80 @@
+//! The on chain consumption snapshots, and the committed merkle snapshots
+
+use super::*;
+
+impl ChannelConsumptionContract {
+    /// Record a user's consumption for the current snapshot, before its first change since that snapshot
+    /// Lazy (as OpenZeppelin's ERC20Snapshot), so a snapshot costs nothing per user
+    pub(super) fn _snapshot_user_consumption(&mut self, user: Address) {
+        let current_id = self.consumption_snapshot_id.get();
+        if current_id.is_zero() {
+            return;
+        }
+        let last_id = {
+            let ids = self.user_snapshot_ids.getter(user);
+            match ids.len().checked_sub(1) {
+                Some(last) => ids.get(last).unwrap_or_default(),
+                None => U256::ZERO,
+            }
+        };
+        if last_id < current_id {
+            let consumption = self.user_consumptions.get(user);
+            self.user_snapshot_ids.setter(user).push(current_id);
+            self.user_snapshot_values.setter(user).push(consumption);
+        }
+    }
+
+    /// Get a user's consumption at a snapshot: the first value recorded from that snapshot on, if any,
+    /// otherwise the consumption didn't change since and the current one applies
+    pub(super) fn _user_consumption_at(&self, user: Address, snapshot_id: U256) -> U256 {
+        let ids = self.user_snapshot_ids.getter(user);
+        let (mut low, mut high) = (0, ids.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            if ids.get(mid).unwrap_or_default() < snapshot_id {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        if low == ids.len() {
+            self.user_consumptions.get(user)
+        } else {
+            self.user_snapshot_values
+                .getter(user)
+                .get(low)
+                .unwrap_or_default()
+        }
+    }
+
+    /// Ensure a consumption snapshot id was taken
+    pub(super) fn _ensure_snapshot_id(&self, snapshot_id: U256) -> Result<(), Errors> {
+        if snapshot_id.is_zero() || snapshot_id > self.consumption_snapshot_id.get() {
+            return Err(Errors::InvalidSnapshotId(InvalidSnapshotId {}));
+        }
+        Ok(())
+    }
+
+    /// Build a consumption snapshot leaf, `keccak(keccak(abi.encode(user, amount)))`
+    /// (OpenZeppelin `StandardMerkleTree` compatible)
+    pub(super) fn _snapshot_leaf(user: Address, amount: U256) -> B256 {
+        keccak(keccak(<sol! { (address, uint256) }>::abi_encode(&(
+            user, amount,
+        ))))
+    }
+
+    /// Verify a user consumption leaf against a committed snapshot root
+    pub(super) fn _verify_snapshot_leaf(
+        &self,
+        snapshot_id: U256,
+        user: Address,
+        amount: U256,
+        proof: &[B256],
+    ) -> Result<(), Errors> {
+        let root = self.snapshot_roots.get(snapshot_id);
+        if root.is_zero() || !merkle::verify(proof, root, Self::_snapshot_leaf(user, amount)) {
+            return Err(Errors::InvalidMerkleProof(InvalidMerkleProof {}));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,2364 @@
+//! Host tests of the consumption contract, run through motsu (storage and `msg::sender` are mocked)
+//! Pushes are signed with local validators (see `addValidator`), so no registry call is needed
+//! A failing call isn't rolled back as it would be on chain, so its partial writes stay visible
+
+use alloy_primitives::{Address, FixedBytes, B256, U256};
+use k256::ecdsa::SigningKey;
+use stylus_sdk::{block, crypto::keccak, msg};
+
+use super::*;
+
+const CONTENT_ID: U256 = U256::from_limbs([1, 0, 0, 0]);
+const CHANNEL_ID: FixedBytes<32> = FixedBytes([7u8; 32]);
+
+/// Unwrap a contract call, showing the revert data on failure (`Errors` isn't `Debug`)
+trait ExpectOk<T> {
+    fn expect_ok(self) -> T;
+}
+
+impl<T> ExpectOk<T> for Result<T, Errors> {
+    fn expect_ok(self) -> T {
+        match self {
+            Ok(value) => value,
+            Err(error) => panic!("reverted with 0x{}", hex::encode(Vec::<u8>::from(error))),
+        }
+    }
+}
+
+/// A deterministic signing key, so a failing test is reproducible
+fn key(seed: u8) -> SigningKey {
+    SigningKey::from_slice(&[seed; 32]).unwrap()
+}
+
+/// The address of a signing key
+fn address_of(key: &SigningKey) -> Address {
+    let point = key.verifying_key().to_encoded_point(false);
+    Address::from_slice(&keccak(&point.as_bytes()[1..])[12..])
+}
+
+/// Sign a digest, returning its `v`, `r` and `s` (k256 always produces a low `s`)
+fn sign(key: &SigningKey, digest: B256) -> (u8, FixedBytes<32>, FixedBytes<32>) {
+    let (signature, recovery_id) = key.sign_prehash_recoverable(digest.as_slice()).unwrap();
+    let bytes = signature.to_bytes();
+    (
+        27 + recovery_id.to_byte(),
+        FixedBytes::from_slice(&bytes[..32]),
+        FixedBytes::from_slice(&bytes[32..]),
+    )
+}
+
+/// A deadline an hour ahead
+fn deadline() -> U256 {
+    U256::from(block::timestamp() + 3600)
+}
+
+/// Initialize the contract, owned by the test sender, with a local validator
+fn setup(contract: &mut ChannelConsumptionContract) -> SigningKey {
+    contract
+        .initialize(msg::sender(), CONTENT_ID, Address::ZERO)
+        .expect_ok();
+    let validator = key(1);
+    contract.add_validator(address_of(&validator)).expect_ok();
+    validator
+}
+
+/// Sign a `pushCcu` of a user on the test channel, bound to their current nonce
+fn sign_push(
+    contract: &ChannelConsumptionContract,
+    signer: &SigningKey,
+    user: Address,
+    added_consumption: U256,
+    deadline: U256,
+) -> (u8, FixedBytes<32>, FixedBytes<32>) {
+    let digest = contract
+        .push_digest(user, CHANNEL_ID, added_consumption, deadline)
+        .expect_ok();
+    sign(signer, digest)
+}
+
+/// Sign and push a consumption of the test sender on the test channel
+fn push(
+    contract: &mut ChannelConsumptionContract,
+    validator: &SigningKey,
+    added_consumption: U256,
+) -> Result<(), Errors> {
+    let deadline = deadline();
+    let (v, r, s) = sign_push(
+        contract,
+        validator,
+        msg::sender(),
+        added_consumption,
+        deadline,
+    );
+    contract.push_ccu(CHANNEL_ID, added_consumption, deadline, v, r, s)
+}
+
+#[motsu::test]
+fn push_digest_preimage_hashes_to_the_digest(contract: ChannelConsumptionContract) {
+    setup(contract);
+    let (user, added, deadline) = (msg::sender(), U256::from(10), deadline());
+
+    let preimage = contract
+        .push_digest_preimage(user, CHANNEL_ID, added, deadline)
+        .expect_ok();
+    let digest = contract
+        .push_digest(user, CHANNEL_ID, added, deadline)
+        .expect_ok();
+    assert_eq!(preimage.len(), 66);
+    assert_eq!(&preimage[..2], &[0x19, 0x01]);
+    assert_eq!(keccak(&preimage[..]), digest);
+}
+
+#[motsu::test]
+fn push_ccu_credits_a_locally_listed_validator(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+
+    push(contract, &validator, U256::from(10)).expect_ok();
+
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(10)
+    );
+    assert_eq!(contract.get_nonce(msg::sender()).expect_ok(), U256::from(1));
+}
+
+#[motsu::test]
+fn forwarded_sender_is_read_from_the_end_of_the_calldata(contract: ChannelConsumptionContract) {
+    setup(contract);
+    let forwarder = msg::sender();
+    let user = address_of(&key(2));
+    contract.set_trusted_forwarder(forwarder).expect_ok();
+
+    // Whatever the length of the encoded call, the sender appended by the forwarder comes last
+    for args_len in [6 * 32, 11 * 32 + 7] {
+        let calldata = [vec![0xab; 4 + args_len], user.to_vec()].concat();
+        assert_eq!(
+            ChannelConsumptionContract::_forwarded_sender(forwarder, &calldata),
+            user
+        );
+    }
+    assert_eq!(
+        ChannelConsumptionContract::_forwarded_sender(forwarder, &[0xab; 19]),
+        forwarder
+    );
+}
+
+#[motsu::test]
+fn push_from_another_caller_than_the_forwarder_credits_the_caller(
+    contract: ChannelConsumptionContract,
+) {
+    let validator = setup(contract);
+    contract
+        .set_trusted_forwarder(address_of(&key(2)))
+        .expect_ok();
+
+    assert_eq!(contract._msg_sender(), msg::sender());
+    push(contract, &validator, U256::from(10)).expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(10)
+    );
+}
+
+#[motsu::test]
+fn channel_daily_cap_resets_on_a_new_day(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    contract.set_channel_daily_cap(U256::from(15)).expect_ok();
+
+    push(contract, &validator, U256::from(10)).expect_ok();
+    assert!(matches!(
+        push(contract, &validator, U256::from(10)),
+        Err(Errors::ChannelDailyCapExceeded(_))
+    ));
+
+    // Move the channel accumulator to the previous day, as if the day boundary was crossed since
+    let today = U256::from(block::timestamp() / SECONDS_PER_DAY);
+    contract
+        .channel_day_index
+        .insert(CHANNEL_ID, today - U256::from(1));
+    push(contract, &validator, U256::from(10)).expect_ok();
+    assert_eq!(
+        contract.channel_day_consumption.get(CHANNEL_ID),
+        U256::from(10)
+    );
+}
+
+/// Sign a `pushCcuDualSig` of a user key, co-signed by a validator, and push it
+fn push_dual_sig(
+    contract: &mut ChannelConsumptionContract,
+    validator: &SigningKey,
+    user: &SigningKey,
+    added_consumption: U256,
+) -> Result<(), Errors> {
+    let deadline = deadline();
+    let user_address = address_of(user);
+    let (validator_v, validator_r, validator_s) = sign_push(
+        contract,
+        validator,
+        user_address,
+        added_consumption,
+        deadline,
+    );
+    let (user_v, user_r, user_s) =
+        sign_push(contract, user, user_address, added_consumption, deadline);
+    contract._push_ccu_dual_sig(
+        user_address,
+        CHANNEL_ID,
+        added_consumption,
+        deadline,
+        validator_v,
+        validator_r,
+        validator_s,
+        user_v,
+        user_r,
+        user_s,
+    )
+}
+
+#[motsu::test]
+fn dual_sig_push_credits_once(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = key(2);
+    let added = U256::from(10);
+    let digest = contract
+        .push_digest(address_of(&user), CHANNEL_ID, added, deadline())
+        .expect_ok();
+
+    push_dual_sig(contract, &validator, &user, added).expect_ok();
+
+    assert_eq!(
+        contract.get_user_consumption(address_of(&user)).expect_ok(),
+        added
+    );
+    assert_eq!(
+        contract.get_nonce(address_of(&user)).expect_ok(),
+        U256::from(1)
+    );
+    assert!(contract.is_signature_used_or_cancelled(digest).expect_ok());
+}
+
+#[motsu::test]
+fn dual_sig_push_rejects_an_unknown_validator(contract: ChannelConsumptionContract) {
+    setup(contract);
+    let user = key(2);
+
+    // Silently dropped by default, reverting in strict mode
+    push_dual_sig(contract, &key(3), &user, U256::from(10)).expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(address_of(&user)).expect_ok(),
+        U256::ZERO
+    );
+    assert_eq!(contract.rejected_pushes.get(), U256::from(1));
+
+    contract.set_strict_mode(true).expect_ok();
+    assert!(matches!(
+        push_dual_sig(contract, &key(3), &user, U256::from(10)),
+        Err(Errors::InvalidPlatformSignature(_))
+    ));
+}
+
+#[motsu::test]
+fn dual_sig_push_rejects_a_wrong_user_signature(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = key(2);
+    let deadline = deadline();
+    let added = U256::from(10);
+    let (validator_v, validator_r, validator_s) =
+        sign_push(contract, &validator, address_of(&user), added, deadline);
+    // Signed by another key than the credited user's
+    let (user_v, user_r, user_s) = sign_push(contract, &key(3), address_of(&user), added, deadline);
+
+    let result = contract._push_ccu_dual_sig(
+        address_of(&user),
+        CHANNEL_ID,
+        added,
+        deadline,
+        validator_v,
+        validator_r,
+        validator_s,
+        user_v,
+        user_r,
+        user_s,
+    );
+    assert!(matches!(result, Err(Errors::InvalidUserSignature(_))));
+    assert_eq!(
+        contract.get_user_consumption(address_of(&user)).expect_ok(),
+        U256::ZERO
+    );
+}
+
+/// Sign a penalty of the test sender with a validator, and apply it
+fn penalize(
+    contract: &mut ChannelConsumptionContract,
+    validator: &SigningKey,
+    penalty_bps: U256,
+) -> Result<(), Errors> {
+    let (user, deadline) = (msg::sender(), deadline());
+    let nonce = contract.get_penalty_nonce(user).expect_ok();
+    let struct_hash =
+        ChannelConsumptionContract::_penalty_struct_hash(user, penalty_bps, deadline, nonce);
+    let (v, r, s) = sign(validator, contract._push_digest(struct_hash));
+    contract.apply_penalty(user, penalty_bps, deadline, v, r, s)
+}
+
+#[motsu::test]
+fn partial_penalty_removes_its_share(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    push(contract, &validator, U256::from(1000)).expect_ok();
+
+    penalize(contract, &validator, U256::from(2500)).expect_ok();
+
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(750)
+    );
+    assert_eq!(contract.total_consumption.get(), U256::from(750));
+    assert_eq!(
+        contract.get_penalty_nonce(msg::sender()).expect_ok(),
+        U256::from(1)
+    );
+}
+
+#[motsu::test]
+fn full_penalty_floors_at_zero(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    push(contract, &validator, U256::from(1000)).expect_ok();
+
+    penalize(contract, &validator, U256::from(10_000)).expect_ok();
+
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::ZERO
+    );
+    assert_eq!(contract.total_consumption.get(), U256::ZERO);
+    assert!(matches!(
+        penalize(contract, &validator, U256::from(10_001)),
+        Err(Errors::InvalidPenalty(_))
+    ));
+}
+
+#[motsu::test]
+fn push_count_budget_resets_on_a_new_epoch(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    contract.set_max_pushes_per_epoch(U256::from(2)).expect_ok();
+
+    push(contract, &validator, U256::from(10)).expect_ok();
+    push(contract, &validator, U256::from(10)).expect_ok();
+    assert!(matches!(
+        push(contract, &validator, U256::from(10)),
+        Err(Errors::PushCountExceeded(_))
+    ));
+
+    let epoch = contract.advance_epoch().expect_ok();
+    push(contract, &validator, U256::from(10)).expect_ok();
+    assert_eq!(
+        contract
+            .get_epoch_push_count(epoch, msg::sender())
+            .expect_ok(),
+        U256::from(1)
+    );
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(30)
+    );
+}
+
+#[motsu::test]
+fn consumption_delta_between_two_snapshot_roots(contract: ChannelConsumptionContract) {
+    setup(contract);
+    let (user, other) = (msg::sender(), address_of(&key(2)));
+
+    // Two leaves trees, each leaf proven by the other one
+    let other_leaf = ChannelConsumptionContract::_snapshot_leaf(other, U256::from(5));
+    let leaf_a = ChannelConsumptionContract::_snapshot_leaf(user, U256::from(100));
+    let leaf_b = ChannelConsumptionContract::_snapshot_leaf(user, U256::from(250));
+    let root_a = merkle::process_proof(&[other_leaf], leaf_a);
+    let root_b = merkle::process_proof(&[other_leaf], leaf_b);
+    contract
+        .commit_snapshot_root(U256::from(1), root_a)
+        .expect_ok();
+    contract
+        .commit_snapshot_root(U256::from(2), root_b)
+        .expect_ok();
+
+    let delta = contract
+        .verify_consumption_delta(
+            user,
+            U256::from(100),
+            vec![other_leaf],
+            U256::from(250),
+            vec![other_leaf],
+            U256::from(1),
+            U256::from(2),
+        )
+        .expect_ok();
+    assert_eq!(delta, U256::from(150));
+
+    // The first leaf proven against the second snapshot root
+    assert!(matches!(
+        contract.verify_consumption_delta(
+            user,
+            U256::from(100),
+            vec![other_leaf],
+            U256::from(250),
+            vec![other_leaf],
+            U256::from(2),
+            U256::from(2),
+        ),
+        Err(Errors::InvalidMerkleProof(_))
+    ));
+}
+
+/// Sign and push a consumption of the test sender on a given content
+fn push_for_content(
+    contract: &mut ChannelConsumptionContract,
+    validator: &SigningKey,
+    content_id: U256,
+    added_consumption: U256,
+) -> Result<(), Errors> {
+    let (user, deadline) = (msg::sender(), deadline());
+    let nonce = contract.get_nonce(user).expect_ok();
+    let struct_hash = ChannelConsumptionContract::_content_struct_hash(
+        user,
+        content_id,
+        CHANNEL_ID,
+        added_consumption,
+        deadline,
+        nonce,
+    );
+    let (v, r, s) = sign(validator, contract._push_digest(struct_hash));
+    contract.push_ccu_for_content(content_id, CHANNEL_ID, added_consumption, deadline, v, r, s)
+}
+
+#[motsu::test]
+fn rewards_add_up_across_content_rates(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let (premium_id, basic_id) = (U256::from(2), U256::from(3));
+    contract
+        .set_content_reward_rate(premium_id, U256::from(5))
+        .expect_ok();
+    contract
+        .set_content_reward_rate(basic_id, U256::from(2))
+        .expect_ok();
+
+    push_for_content(contract, &validator, premium_id, U256::from(10)).expect_ok();
+    push_for_content(contract, &validator, basic_id, U256::from(10)).expect_ok();
+
+    assert_eq!(
+        contract
+            .get_user_content_consumption(premium_id, msg::sender())
+            .expect_ok(),
+        U256::from(10)
+    );
+    assert_eq!(contract.claim_rewards().expect_ok(), U256::from(70));
+    assert_eq!(
+        contract.get_withdrawable(msg::sender()).expect_ok(),
+        U256::from(70)
+    );
+    assert!(matches!(
+        contract.claim_rewards(),
+        Err(Errors::NothingToClaim(_))
+    ));
+}
+
+/// Queue a timelocked config change, returning its id
+fn queue(contract: &mut ChannelConsumptionContract, call: impl SolCall) -> FixedBytes<32> {
+    let call_data = call.abi_encode();
+    contract
+        .queue_config_change(
+            FixedBytes::from_slice(&call_data[..4]),
+            call_data[4..].to_vec().into(),
+        )
+        .expect_ok()
+}
+
+#[motsu::test]
+fn timelocked_multipliers_wait_for_their_eta(contract: ChannelConsumptionContract) {
+    setup(contract);
+    contract.set_config_timelock(U256::from(3600)).expect_ok();
+    let (content_types, multiplier_bps) = (U256::from(4), U256::from(20_000));
+
+    // The direct setters are closed
+    assert!(matches!(
+        contract.set_content_type_multiplier(content_types, multiplier_bps),
+        Err(Errors::TimelockRequired(_))
+    ));
+    assert!(matches!(
+        contract.register_channel(
+            CHANNEL_ID,
+            Address::ZERO,
+            U256::ZERO,
+            multiplier_bps,
+            CONTENT_ID,
+            Vec::<u8>::new().into(),
+        ),
+        Err(Errors::TimelockRequired(_))
+    ));
+
+    // A queued change can't run before its eta
+    let multiplier_id = queue(
+        contract,
+        setContentTypeMultiplierCall {
+            contentTypes: content_types,
+            multiplierBps: multiplier_bps,
+        },
+    );
+    let channel_id = queue(
+        contract,
+        registerChannelCall {
+            channelId: CHANNEL_ID,
+            validator: Address::ZERO,
+            cap: U256::ZERO,
+            multiplierBps: multiplier_bps,
+            contentId: CONTENT_ID,
+            metadata: Default::default(),
+        },
+    );
+    for id in [multiplier_id, channel_id] {
+        assert!(matches!(
+            contract.execute_config_change(id),
+            Err(Errors::ConfigChangeNotReady(_))
+        ));
+    }
+    assert_eq!(
+        contract
+            .get_content_type_multiplier(content_types)
+            .expect_ok(),
+        U256::ZERO
+    );
+
+    // Once the delay elapsed (moved back, as the clock can't be moved), both apply
+    let now = U256::from(block::timestamp());
+    for id in [multiplier_id, channel_id] {
+        contract.queued_config_etas.insert(id, now);
+        contract.execute_config_change(id).expect_ok();
+    }
+    assert_eq!(
+        contract
+            .get_content_type_multiplier(content_types)
+            .expect_ok(),
+        multiplier_bps
+    );
+    assert_eq!(
+        contract.channel_multipliers_bps.get(CHANNEL_ID),
+        multiplier_bps
+    );
+}
+
+#[motsu::test]
+fn legacy_v_must_carry_the_current_chain_id(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let (added, deadline) = (U256::from(10), deadline());
+    let (v, r, s) = sign_push(contract, &validator, msg::sender(), added, deadline);
+    let legacy_v =
+        |chain_id: u64| U256::from(chain_id) * U256::from(2) + U256::from(35 + u64::from(v - 27));
+    let chain_id = block::chainid();
+
+    // Refused until allowed
+    assert!(matches!(
+        contract.push_ccu_legacy_v(CHANNEL_ID, added, deadline, legacy_v(chain_id), r, s),
+        Err(Errors::InvalidSignatureV(_))
+    ));
+    contract.set_allow_eip155_v(true).expect_ok();
+
+    // Tagged with another chain
+    assert!(matches!(
+        contract.push_ccu_legacy_v(CHANNEL_ID, added, deadline, legacy_v(chain_id + 1), r, s),
+        Err(Errors::InvalidSignatureChainId(_))
+    ));
+
+    // Tagged with this chain, whatever its size
+    contract
+        .push_ccu_legacy_v(CHANNEL_ID, added, deadline, legacy_v(chain_id), r, s)
+        .expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        added
+    );
+}
+
+#[motsu::test]
+fn set_caps_sentinels_keep_the_previous_values(contract: ChannelConsumptionContract) {
+    setup(contract);
+    let caps = (
+        U256::from(100),
+        U256::from(1_000),
+        U256::from(10_000),
+        U256::from(500),
+    );
+    contract
+        .set_caps(caps.0, caps.1, caps.2, caps.3)
+        .expect_ok();
+    assert_eq!(contract.get_caps().expect_ok(), caps);
+
+    // Only the per user cap changes
+    contract
+        .set_caps(U256::MAX, U256::from(2_000), U256::MAX, U256::MAX)
+        .expect_ok();
+    assert_eq!(
+        contract.get_caps().expect_ok(),
+        (caps.0, U256::from(2_000), caps.2, caps.3)
+    );
+}
+
+#[motsu::test]
+fn escrowed_push_is_booked_once_confirmed(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    contract
+        .set_content_reward_rate(CONTENT_ID, U256::from(2))
+        .expect_ok();
+    contract.set_challenge_period(U256::from(3600)).expect_ok();
+
+    push(contract, &validator, U256::from(10)).expect_ok();
+
+    // Nothing is booked (nor rewarded) while in escrow
+    assert_eq!(
+        contract.get_pending_consumption(user).expect_ok().0,
+        U256::from(10)
+    );
+    assert_eq!(
+        contract
+            .get_channel_total_consumption(CHANNEL_ID)
+            .expect_ok(),
+        U256::ZERO
+    );
+    assert!(matches!(
+        contract.claim_rewards(),
+        Err(Errors::NothingToClaim(_))
+    ));
+    assert!(matches!(
+        contract.confirm_pending(user),
+        Err(Errors::PendingNotReleasable(_))
+    ));
+
+    // Past the challenge period (time can't move here, so the release is brought forward)
+    contract
+        .pending_release_at
+        .insert(user, U256::from(block::timestamp()));
+    contract.confirm_pending(user).expect_ok();
+
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(10)
+    );
+    assert_eq!(
+        contract
+            .get_channel_total_consumption(CHANNEL_ID)
+            .expect_ok(),
+        U256::from(10)
+    );
+    assert_eq!(
+        contract
+            .get_user_channel_consumption(user, CHANNEL_ID)
+            .expect_ok(),
+        U256::from(10)
+    );
+    assert_eq!(contract.claim_rewards().expect_ok(), U256::from(20));
+    assert_eq!(
+        contract.channel_pending_consumptions.get(CHANNEL_ID),
+        U256::ZERO
+    );
+}
+
+#[motsu::test]
+fn challenged_escrow_is_never_booked(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    contract
+        .set_content_reward_rate(CONTENT_ID, U256::from(2))
+        .expect_ok();
+    contract.set_challenge_period(U256::from(3600)).expect_ok();
+    push(contract, &validator, U256::from(10)).expect_ok();
+    push(contract, &validator, U256::from(5)).expect_ok();
+
+    // Cancels the whole second push and part of the first one
+    contract.challenge_pending(user, U256::from(8)).expect_ok();
+    assert_eq!(
+        contract.get_pending_consumption(user).expect_ok().0,
+        U256::from(7)
+    );
+    assert_eq!(
+        contract.channel_pending_consumptions.get(CHANNEL_ID),
+        U256::from(7)
+    );
+
+    contract
+        .pending_release_at
+        .insert(user, U256::from(block::timestamp()));
+    contract.confirm_pending(user).expect_ok();
+
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(7)
+    );
+    assert_eq!(
+        contract
+            .get_channel_total_consumption(CHANNEL_ID)
+            .expect_ok(),
+        U256::from(7)
+    );
+    assert_eq!(contract.claim_rewards().expect_ok(), U256::from(14));
+}
+
+/// Sign a `pushCcuMultiContent` of the test sender on the test channel, bound to their current nonce
+fn sign_multi_content(
+    contract: &ChannelConsumptionContract,
+    signer: &SigningKey,
+    content_ids: &[U256],
+    added_consumptions: &[U256],
+    deadline: U256,
+) -> (FixedBytes<32>, (u8, FixedBytes<32>, FixedBytes<32>)) {
+    let user = msg::sender();
+    let struct_hash = ChannelConsumptionContract::_multi_content_struct_hash(
+        user,
+        content_ids,
+        &vec![CHANNEL_ID; content_ids.len()],
+        added_consumptions,
+        deadline,
+        contract.nonces.get(user),
+    );
+    let digest = contract._push_digest(struct_hash);
+    (digest, sign(signer, digest))
+}
+
+#[motsu::test]
+fn multi_content_push_consumes_the_nonce(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let (content_ids, added) = (
+        vec![U256::from(2), U256::from(3)],
+        vec![U256::from(10), U256::from(5)],
+    );
+    let deadline = deadline();
+    let (_, (v, r, s)) = sign_multi_content(contract, &validator, &content_ids, &added, deadline);
+
+    let credited = contract
+        .push_ccu_multi_content(
+            content_ids.clone(),
+            vec![CHANNEL_ID; 2],
+            added.clone(),
+            deadline,
+            v,
+            r,
+            s,
+        )
+        .expect_ok();
+    assert_eq!(credited, U256::from(0b11));
+    assert_eq!(contract.get_nonce(msg::sender()).expect_ok(), U256::from(1));
+
+    // A replay no longer recovers the validator (the nonce moved on), so nothing is credited
+    let credited = contract
+        .push_ccu_multi_content(content_ids, vec![CHANNEL_ID; 2], added, deadline, v, r, s)
+        .expect_ok();
+    assert_eq!(credited, U256::ZERO);
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(15)
+    );
+}
+
+#[motsu::test]
+fn multi_content_push_honours_a_cancelled_signature(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let (content_ids, added) = (vec![U256::from(2)], vec![U256::from(10)]);
+    let deadline = deadline();
+    let (digest, (v, r, s)) =
+        sign_multi_content(contract, &validator, &content_ids, &added, deadline);
+
+    contract.invalidate_signature(digest).expect_ok();
+
+    assert!(matches!(
+        contract.push_ccu_multi_content(content_ids, vec![CHANNEL_ID], added, deadline, v, r, s),
+        Err(Errors::SignatureCancelled(_))
+    ));
+    assert_eq!(contract.get_nonce(msg::sender()).expect_ok(), U256::ZERO);
+}
+
+#[motsu::test]
+fn multi_content_push_skips_an_unauthorized_signer(contract: ChannelConsumptionContract) {
+    setup(contract);
+    let (content_ids, added) = (
+        vec![U256::from(2), U256::from(3)],
+        vec![U256::from(10), U256::from(5)],
+    );
+    let deadline = deadline();
+    let (_, (v, r, s)) = sign_multi_content(contract, &key(2), &content_ids, &added, deadline);
+
+    let credited = contract
+        .push_ccu_multi_content(content_ids, vec![CHANNEL_ID; 2], added, deadline, v, r, s)
+        .expect_ok();
+    assert_eq!(credited, U256::ZERO);
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::ZERO
+    );
+}
+
+#[motsu::test]
+fn multi_content_push_resumes_after_the_circuit_breaker(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    contract
+        .set_circuit_breaker(U256::from(10), U256::ZERO)
+        .expect_ok();
+    let (content_ids, added) = (
+        vec![U256::from(2), U256::from(3)],
+        vec![U256::from(5), U256::from(10)],
+    );
+    let deadline = deadline();
+    let (digest, (v, r, s)) =
+        sign_multi_content(contract, &validator, &content_ids, &added, deadline);
+    let push_multi_content = |contract: &mut ChannelConsumptionContract| {
+        contract.push_ccu_multi_content(
+            content_ids.clone(),
+            vec![CHANNEL_ID; 2],
+            added.clone(),
+            deadline,
+            v,
+            r,
+            s,
+        )
+    };
+
+    // The second entry trips the breaker, leaving the signature valid
+    assert_eq!(push_multi_content(contract).expect_ok(), U256::from(0b01));
+    assert!(contract.is_paused().expect_ok());
+    assert_eq!(contract.get_nonce(user).expect_ok(), U256::ZERO);
+    assert!(!contract.used_or_cancelled.get(digest));
+
+    // Pushed again once unpaused (and the breaker raised), only the entry left is credited
+    contract.unpause().expect_ok();
+    contract
+        .set_circuit_breaker(U256::from(100), U256::ZERO)
+        .expect_ok();
+    assert_eq!(push_multi_content(contract).expect_ok(), U256::from(0b10));
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(15)
+    );
+    assert_eq!(contract.get_nonce(user).expect_ok(), U256::from(1));
+    assert!(contract.used_or_cancelled.get(digest));
+}
+
+/// Sign and push a cumulative reading of the test sender on the test channel
+fn push_cumulative(
+    contract: &mut ChannelConsumptionContract,
+    validator: &SigningKey,
+    session_id: FixedBytes<32>,
+    cumulative_consumption: U256,
+) -> Result<(), Errors> {
+    let deadline = deadline();
+    let struct_hash = ChannelConsumptionContract::_cumulative_struct_hash(
+        msg::sender(),
+        CHANNEL_ID,
+        session_id,
+        cumulative_consumption,
+        deadline,
+    );
+    let (v, r, s) = sign(validator, contract._push_digest(struct_hash));
+    contract.push_ccu_cumulative(
+        CHANNEL_ID,
+        session_id,
+        cumulative_consumption,
+        deadline,
+        v,
+        r,
+        s,
+    )
+}
+
+#[motsu::test]
+fn cumulative_readings_credit_their_increase(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let (first_session, second_session) = (FixedBytes([1u8; 32]), FixedBytes([2u8; 32]));
+
+    push_cumulative(contract, &validator, first_session, U256::from(10)).expect_ok();
+    push_cumulative(contract, &validator, first_session, U256::from(25)).expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(25)
+    );
+
+    // A stale (or repeated) reading credits nothing
+    push_cumulative(contract, &validator, first_session, U256::from(20)).expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(25)
+    );
+
+    // A new session starts back from zero
+    push_cumulative(contract, &validator, second_session, U256::from(5)).expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(30)
+    );
+}
+
+#[motsu::test]
+fn exported_user_data_matches_the_getters(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    let other_channel = FixedBytes([8u8; 32]);
+    push(contract, &validator, U256::from(10)).expect_ok();
+    push(contract, &validator, U256::from(5)).expect_ok();
+    let deadline = deadline();
+    let digest = contract
+        .push_digest(user, other_channel, U256::from(7), deadline)
+        .expect_ok();
+    let (v, r, s) = sign(&validator, digest);
+    contract
+        .push_ccu(other_channel, U256::from(7), deadline, v, r, s)
+        .expect_ok();
+
+    let (consumption, pending, _, last_push, penalty_nonce, flagged, channels, consumptions) =
+        contract.export_user_data(user).expect_ok();
+
+    assert_eq!(consumption, contract.get_user_consumption(user).expect_ok());
+    assert_eq!(consumption, U256::from(22));
+    assert_eq!(
+        pending,
+        contract.get_pending_consumption(user).expect_ok().0
+    );
+    assert_eq!(last_push, contract.get_last_push_at(user).expect_ok());
+    assert_eq!(penalty_nonce, contract.get_penalty_nonce(user).expect_ok());
+    assert_eq!(flagged, contract.is_flagged(user).expect_ok());
+    assert_eq!(channels, vec![CHANNEL_ID, other_channel]);
+    for (channel_id, channel_consumption) in channels.into_iter().zip(consumptions) {
+        assert_eq!(
+            channel_consumption,
+            contract
+                .get_user_channel_consumption(user, channel_id)
+                .expect_ok()
+        );
+    }
+}
+
+#[motsu::test]
+fn switching_validation_modes_keeps_the_registry(contract: ChannelConsumptionContract) {
+    setup(contract);
+    let (registry, platform_signer) = (Address::repeat_byte(0xaa), Address::repeat_byte(0xbb));
+
+    // The registry mode needs a registry, none being set yet
+    assert!(matches!(
+        contract.set_validation_mode(VALIDATION_MODE_REGISTRY, Address::ZERO, Address::ZERO),
+        Err(Errors::InvalidValidationMode(_))
+    ));
+    contract
+        .set_validation_mode(VALIDATION_MODE_REGISTRY, Address::ZERO, registry)
+        .expect_ok();
+    assert_eq!(
+        contract.get_validation_mode().expect_ok(),
+        (VALIDATION_MODE_REGISTRY, registry)
+    );
+
+    // Leaving the registry mode, and coming back to it without passing the registry again
+    contract
+        .set_validation_mode(
+            VALIDATION_MODE_SINGLE_SIGNER,
+            platform_signer,
+            Address::ZERO,
+        )
+        .expect_ok();
+    assert_eq!(
+        contract.get_validation_mode().expect_ok(),
+        (VALIDATION_MODE_SINGLE_SIGNER, platform_signer)
+    );
+    contract
+        .set_validation_mode(VALIDATION_MODE_ALLOWLIST, Address::ZERO, Address::ZERO)
+        .expect_ok();
+    assert_eq!(
+        contract.get_validation_mode().expect_ok(),
+        (VALIDATION_MODE_ALLOWLIST, Address::ZERO)
+    );
+    contract
+        .set_validation_mode(VALIDATION_MODE_REGISTRY, Address::ZERO, Address::ZERO)
+        .expect_ok();
+    assert_eq!(
+        contract.get_validation_mode().expect_ok(),
+        (VALIDATION_MODE_REGISTRY, registry)
+    );
+}
+
+/// Sign and push a consumption of the test sender on the test channel, under an idempotency key
+fn push_idempotent(
+    contract: &mut ChannelConsumptionContract,
+    validator: &SigningKey,
+    added_consumption: U256,
+    deadline: U256,
+    idempotency_key: FixedBytes<32>,
+) -> Result<U256, Errors> {
+    let struct_hash = ChannelConsumptionContract::_idempotent_struct_hash(
+        msg::sender(),
+        CHANNEL_ID,
+        added_consumption,
+        deadline,
+        idempotency_key,
+    );
+    let (v, r, s) = sign(validator, contract._push_digest(struct_hash));
+    contract.push_ccu_idempotent(
+        CHANNEL_ID,
+        added_consumption,
+        deadline,
+        idempotency_key,
+        v,
+        r,
+        s,
+    )
+}
+
+#[motsu::test]
+fn repeated_idempotency_key_is_a_no_op(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let idempotency_key = FixedBytes([3u8; 32]);
+
+    let receipt_id = push_idempotent(
+        contract,
+        &validator,
+        U256::from(10),
+        deadline(),
+        idempotency_key,
+    )
+    .expect_ok();
+    assert!(!receipt_id.is_zero());
+
+    // Re-signed with another deadline, the retry still returns the original receipt
+    let retried = push_idempotent(
+        contract,
+        &validator,
+        U256::from(10),
+        deadline() + U256::from(1),
+        idempotency_key,
+    )
+    .expect_ok();
+    assert_eq!(retried, receipt_id);
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(10)
+    );
+}
+
+#[motsu::test]
+fn queued_idempotent_push_gets_its_own_receipt(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    push(contract, &validator, U256::from(1)).expect_ok();
+    let previous_receipt = contract.user_last_receipt.get(msg::sender());
+    contract.set_freeze_policy(FREEZE_POLICY_QUEUE).expect_ok();
+    contract.freeze_rewards().expect_ok();
+    let idempotency_key = FixedBytes([3u8; 32]);
+
+    let receipt_id = push_idempotent(
+        contract,
+        &validator,
+        U256::from(10),
+        deadline(),
+        idempotency_key,
+    )
+    .expect_ok();
+    assert!(receipt_id > previous_receipt);
+
+    // The retry doesn't queue the push a second time
+    let retried = push_idempotent(
+        contract,
+        &validator,
+        U256::from(10),
+        deadline() + U256::from(1),
+        idempotency_key,
+    )
+    .expect_ok();
+    assert_eq!(retried, receipt_id);
+    contract.unfreeze_rewards().expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(11)
+    );
+}
+
+#[motsu::test]
+fn idempotent_push_deadline_stays_within_the_key_retention(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    contract.set_epoch_duration(U256::from(3600)).expect_ok();
+    let idempotency_key = FixedBytes([3u8; 32]);
+
+    // Past the end of the next epoch, the key could be forgotten before the signature expires
+    let far_deadline = U256::from(block::timestamp() + 3 * 3600);
+    assert!(matches!(
+        push_idempotent(
+            contract,
+            &validator,
+            U256::from(10),
+            far_deadline,
+            idempotency_key
+        ),
+        Err(Errors::DeadlineTooFar(_))
+    ));
+
+    // A cancelled signature is refused, its key staying unused
+    let deadline = deadline();
+    let struct_hash = ChannelConsumptionContract::_idempotent_struct_hash(
+        msg::sender(),
+        CHANNEL_ID,
+        U256::from(10),
+        deadline,
+        idempotency_key,
+    );
+    contract
+        .invalidate_signature(contract._push_digest(struct_hash))
+        .expect_ok();
+    assert!(matches!(
+        push_idempotent(
+            contract,
+            &validator,
+            U256::from(10),
+            deadline,
+            idempotency_key
+        ),
+        Err(Errors::SignatureCancelled(_))
+    ));
+}
+
+/// The solidity signatures of the `#[selector]` functions of a `#[public]` impl source
+fn public_signatures(source: &str) -> Vec<String> {
+    let solidity_type = |rust_type: &str| match rust_type {
+        "Address" => "address",
+        "U256" => "uint256",
+        "u8" => "uint8",
+        "u16" => "uint16",
+        "u64" => "uint64",
+        "bool" => "bool",
+        "FixedBytes<4>" => "bytes4",
+        "FixedBytes<32>" => "bytes32",
+        "Bytes" => "bytes",
+        "String" => "string",
+        "Vec<Address>" => "address[]",
+        "Vec<U256>" => "uint256[]",
+        "Vec<FixedBytes<32>>" => "bytes32[]",
+        "Vec<bool>" => "bool[]",
+        "Vec<u8>" => "uint8[]",
+        other => panic!("unmapped argument type {other}"),
+    };
+    source
+        .split("#[selector(name = \"")
+        .skip(1)
+        .map(|chunk| {
+            let name = &chunk[..chunk.find('"').unwrap()];
+            let function = &chunk[chunk.find("pub fn ").unwrap()..];
+            let arguments = &function[function.find('(').unwrap() + 1..function.find(')').unwrap()];
+            let types: Vec<&str> = arguments
+                .split(',')
+                .map(str::trim)
+                .filter(|argument| !argument.is_empty() && !argument.ends_with("self"))
+                .map(|argument| solidity_type(argument.split(':').nth(1).unwrap().trim()))
+                .collect();
+            format!("{name}({})", types.join(","))
+        })
+        .collect()
+}
+
+#[test]
+fn supported_selectors_match_the_public_interface() {
+    let mut signatures = public_signatures(include_str!("mod.rs"));
+    signatures.extend(public_signatures(include_str!("../../utils/eip712.rs")));
+    // Inherited from the OpenZeppelin `Ownable` and `AccessControl`, unless overridden
+    for inherited in [
+        "owner()",
+        "transferOwnership(address)",
+        "renounceOwnership()",
+        "hasRole(bytes32,address)",
+        "getRoleAdmin(bytes32)",
+        "grantRole(bytes32,address)",
+        "revokeRole(bytes32,address)",
+        "renounceRole(bytes32,address)",
+    ] {
+        if !signatures.iter().any(|signature| signature == inherited) {
+            signatures.push(inherited.to_string());
+        }
+    }
+
+    let mut expected: Vec<[u8; 4]> = signatures
+        .iter()
+        .map(|signature| keccak(signature.as_bytes())[..4].try_into().unwrap())
+        .collect();
+    expected.sort();
+    let mut supported = selectors::SUPPORTED_SELECTORS.to_vec();
+    supported.sort();
+    let duplicates = supported
+        .windows(2)
+        .filter(|pair| pair[0] == pair[1])
+        .count();
+    assert_eq!(duplicates, 0, "a selector is listed twice");
+    assert_eq!(supported, expected, "supportedSelectors is out of sync");
+}
+
+#[motsu::test]
+fn cohort_leaves_are_credited_once(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let (user, other_user) = (msg::sender(), Address::repeat_byte(0x11));
+    let leaf = ChannelConsumptionContract::_cohort_leaf(user, CHANNEL_ID, U256::from(10));
+    let other_leaf =
+        ChannelConsumptionContract::_cohort_leaf(other_user, CHANNEL_ID, U256::from(20));
+    let root = merkle::process_proof(&[other_leaf], leaf);
+    let deadline = deadline();
+    let (v, r, s) = sign(
+        &validator,
+        contract._push_digest(ChannelConsumptionContract::_cohort_struct_hash(
+            root, deadline,
+        )),
+    );
+
+    // Each member is credited through its own proof, whoever submits it
+    contract
+        .push_ccu_cohort(
+            user,
+            CHANNEL_ID,
+            U256::from(10),
+            vec![other_leaf],
+            root,
+            deadline,
+            v,
+            r,
+            s,
+        )
+        .expect_ok();
+    contract
+        .push_ccu_cohort(
+            other_user,
+            CHANNEL_ID,
+            U256::from(20),
+            vec![leaf],
+            root,
+            deadline,
+            v,
+            r,
+            s,
+        )
+        .expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(10)
+    );
+    assert_eq!(
+        contract.get_user_consumption(other_user).expect_ok(),
+        U256::from(20)
+    );
+
+    assert!(matches!(
+        contract.push_ccu_cohort(
+            user,
+            CHANNEL_ID,
+            U256::from(10),
+            vec![other_leaf],
+            root,
+            deadline,
+            v,
+            r,
+            s
+        ),
+        Err(Errors::CohortLeafAlreadyClaimed(_))
+    ));
+}
+
+#[motsu::test]
+fn forged_cohort_leaf_is_refused(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    let leaf = ChannelConsumptionContract::_cohort_leaf(user, CHANNEL_ID, U256::from(10));
+    let other_leaf = ChannelConsumptionContract::_cohort_leaf(
+        Address::repeat_byte(0x11),
+        CHANNEL_ID,
+        U256::from(20),
+    );
+    let root = merkle::process_proof(&[other_leaf], leaf);
+    let deadline = deadline();
+    let (v, r, s) = sign(
+        &validator,
+        contract._push_digest(ChannelConsumptionContract::_cohort_struct_hash(
+            root, deadline,
+        )),
+    );
+
+    // An inflated amount isn't part of the signed cohort
+    assert!(matches!(
+        contract.push_ccu_cohort(
+            user,
+            CHANNEL_ID,
+            U256::from(100),
+            vec![other_leaf],
+            root,
+            deadline,
+            v,
+            r,
+            s
+        ),
+        Err(Errors::InvalidMerkleProof(_))
+    ));
+    assert_eq!(contract.get_user_consumption(user).expect_ok(), U256::ZERO);
+}
+
+#[motsu::test]
+fn effective_credit_binds_on_each_cap(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    push(contract, &validator, U256::from(10)).expect_ok();
+    let effective = |contract: &ChannelConsumptionContract| {
+        contract
+            .effective_credited_amount(user, CHANNEL_ID, U256::from(1_000))
+            .expect_ok()
+    };
+    let register = |contract: &mut ChannelConsumptionContract, cap: u64, multiplier_bps: u64| {
+        contract
+            .register_channel(
+                CHANNEL_ID,
+                Address::ZERO,
+                U256::from(cap),
+                U256::from(multiplier_bps),
+                CONTENT_ID,
+                Vec::<u8>::new().into(),
+            )
+            .expect_ok();
+    };
+    assert_eq!(effective(contract), U256::from(1_000));
+
+    // The channel multiplier applies before any cap
+    register(contract, 0, 20_000);
+    assert_eq!(
+        contract
+            .effective_credited_amount(user, CHANNEL_ID, U256::from(30))
+            .expect_ok(),
+        U256::from(60)
+    );
+    register(contract, 0, 0);
+
+    // Per push cap
+    contract
+        .set_caps(U256::from(100), U256::MAX, U256::MAX, U256::MAX)
+        .expect_ok();
+    assert_eq!(effective(contract), U256::from(100));
+
+    // Channel lifetime cap (10 already booked)
+    register(contract, 60, 0);
+    assert_eq!(effective(contract), U256::from(50));
+
+    // Channel daily cap (10 already accrued today)
+    contract
+        .set_caps(U256::MAX, U256::MAX, U256::MAX, U256::from(40))
+        .expect_ok();
+    assert_eq!(effective(contract), U256::from(30));
+
+    // Per user cap
+    contract
+        .set_caps(U256::MAX, U256::from(30), U256::MAX, U256::MAX)
+        .expect_ok();
+    assert_eq!(effective(contract), U256::from(20));
+
+    // Global cap
+    contract
+        .set_caps(U256::MAX, U256::MAX, U256::from(20), U256::MAX)
+        .expect_ok();
+    assert_eq!(effective(contract), U256::from(10));
+}
+
+#[motsu::test]
+fn team_members_sum_into_the_team_total(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let (user, teammate, team_id) = (
+        msg::sender(),
+        Address::repeat_byte(0x11),
+        FixedBytes([9u8; 32]),
+    );
+    contract.set_user_team(user, team_id).expect_ok();
+    contract.set_user_team(teammate, team_id).expect_ok();
+
+    push(contract, &validator, U256::from(10)).expect_ok();
+    // The teammate is credited through a single leaf cohort, as only the test sender can push
+    let leaf = ChannelConsumptionContract::_cohort_leaf(teammate, CHANNEL_ID, U256::from(20));
+    let deadline = deadline();
+    let (v, r, s) = sign(
+        &validator,
+        contract._push_digest(ChannelConsumptionContract::_cohort_struct_hash(
+            leaf, deadline,
+        )),
+    );
+    contract
+        .push_ccu_cohort(
+            teammate,
+            CHANNEL_ID,
+            U256::from(20),
+            vec![],
+            leaf,
+            deadline,
+            v,
+            r,
+            s,
+        )
+        .expect_ok();
+
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(10)
+    );
+    assert_eq!(
+        contract.get_user_consumption(teammate).expect_ok(),
+        U256::from(20)
+    );
+    assert_eq!(
+        contract.get_team_consumption(team_id).expect_ok(),
+        U256::from(30)
+    );
+}
+
+#[motsu::test]
+fn queued_pushes_are_credited_exactly_once(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    contract.set_freeze_policy(FREEZE_POLICY_QUEUE).expect_ok();
+    contract.freeze_rewards().expect_ok();
+
+    push(contract, &validator, U256::from(10)).expect_ok();
+    push(contract, &validator, U256::from(5)).expect_ok();
+    assert_eq!(
+        contract.get_rewards_freeze().expect_ok(),
+        (true, FREEZE_POLICY_QUEUE, U256::from(2))
+    );
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::ZERO
+    );
+
+    contract.unfreeze_rewards().expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(15)
+    );
+    assert_eq!(contract.get_rewards_freeze().expect_ok().2, U256::ZERO);
+
+    // Another freeze cycle has nothing left to credit
+    contract.freeze_rewards().expect_ok();
+    contract.unfreeze_rewards().expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(15)
+    );
+}
+
+/// Assert that only the first of two queued pushes (of 10 then 5, on the test channel) was booked,
+/// the dropped one leaving no trace on the totals, the rewards, the daily accrual nor the push counts
+fn assert_only_the_first_push_booked(contract: &ChannelConsumptionContract, reward_rate: U256) {
+    let user = msg::sender();
+    let epoch = contract._current_epoch();
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(10)
+    );
+    assert_eq!(
+        contract.accrued_rewards.get(user),
+        U256::from(10) * reward_rate
+    );
+    assert_eq!(
+        contract.channel_consumptions.get(CHANNEL_ID),
+        U256::from(10)
+    );
+    assert_eq!(
+        contract
+            .content_user_consumptions
+            .getter(CONTENT_ID)
+            .get(user),
+        U256::from(10)
+    );
+    assert_eq!(contract.epoch_totals.get(epoch), U256::from(10));
+    assert_eq!(contract._channel_day_accrued(CHANNEL_ID), U256::from(10));
+    assert_eq!(
+        contract.epoch_push_counts.getter(epoch).get(user),
+        U256::from(1)
+    );
+    assert_eq!(contract.get_rewards_freeze().expect_ok().2, U256::ZERO);
+}
+
+#[motsu::test]
+fn failing_queued_push_is_dropped_without_blocking_the_unfreeze(
+    contract: ChannelConsumptionContract,
+) {
+    let validator = setup(contract);
+    let reward_rate = U256::from(2);
+    contract
+        .set_content_reward_rate(CONTENT_ID, reward_rate)
+        .expect_ok();
+    contract.set_freeze_policy(FREEZE_POLICY_QUEUE).expect_ok();
+    contract.freeze_rewards().expect_ok();
+    push(contract, &validator, U256::from(10)).expect_ok();
+    push(contract, &validator, U256::from(5)).expect_ok();
+
+    // Both queued pushes land in the unfreeze block, so the second one breaks the same block rule
+    contract.set_reject_same_block_pushes(true).expect_ok();
+    contract.unfreeze_rewards().expect_ok();
+
+    assert_only_the_first_push_booked(contract, reward_rate);
+}
+
+#[motsu::test]
+fn queued_push_at_an_implausible_rate_is_dropped_without_any_write(
+    contract: ChannelConsumptionContract,
+) {
+    let validator = setup(contract);
+    let reward_rate = U256::from(2);
+    contract
+        .set_content_reward_rate(CONTENT_ID, reward_rate)
+        .expect_ok();
+    contract.set_freeze_policy(FREEZE_POLICY_QUEUE).expect_ok();
+    contract.freeze_rewards().expect_ok();
+    push(contract, &validator, U256::from(10)).expect_ok();
+    push(contract, &validator, U256::from(5)).expect_ok();
+
+    // The first push of a user is never implausible, the second one comes within the same second
+    contract
+        .set_max_consumption_rate(U256::from(1), true)
+        .expect_ok();
+    contract.unfreeze_rewards().expect_ok();
+
+    assert_only_the_first_push_booked(contract, reward_rate);
+    assert!(!contract.flagged.get(msg::sender()));
+}
+
+#[motsu::test]
+fn batch_under_the_gas_reserve_resumes_where_it_stopped(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    let added = [U256::from(10), U256::from(20), U256::from(30)];
+    let deadline = deadline();
+    let signatures: Vec<_> = added
+        .iter()
+        .enumerate()
+        .map(|(nonce, added_consumption)| {
+            let struct_hash = ChannelConsumptionContract::_consumption_struct_hash(
+                user,
+                CHANNEL_ID,
+                *added_consumption,
+                deadline,
+                U256::from(nonce),
+            );
+            sign(&validator, contract._push_digest(struct_hash))
+        })
+        .collect();
+    let push_batch = |contract: &mut ChannelConsumptionContract| {
+        contract.push_ccu_batch(
+            vec![CHANNEL_ID; added.len()],
+            added.to_vec(),
+            vec![deadline; added.len()],
+            signatures.iter().map(|(v, _, _)| *v).collect(),
+            signatures.iter().map(|(_, r, _)| *r).collect(),
+            signatures.iter().map(|(_, _, s)| *s).collect(),
+            false,
+        )
+    };
+
+    // The gas left can't shrink here, so a reserve above it stops the batch before its first entry
+    contract.set_batch_gas_reserve(U256::MAX).expect_ok();
+    let (credited, processed_up_to, done) = push_batch(contract).expect_ok();
+    assert!(credited.is_empty() && processed_up_to.is_zero() && !done);
+    assert_eq!(contract.get_user_consumption(user).expect_ok(), U256::ZERO);
+
+    // Resumed from the index reached once there's gas to spare
+    contract.set_batch_gas_reserve(U256::ZERO).expect_ok();
+    let (credited, processed_up_to, done) = push_batch(contract).expect_ok();
+    assert_eq!(credited, vec![true; 3]);
+    assert_eq!(processed_up_to, U256::from(3));
+    assert!(done);
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(60)
+    );
+}
+
+#[motsu::test]
+fn pause_blocks_pushes_but_not_views(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    push(contract, &validator, U256::from(10)).expect_ok();
+
+    contract.pause().expect_ok();
+    assert!(contract.is_paused().expect_ok());
+    assert!(matches!(
+        push(contract, &validator, U256::from(5)),
+        Err(Errors::ContractPaused(_))
+    ));
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(10)
+    );
+    assert_eq!(contract.get_total_consumption().expect_ok(), U256::from(10));
+
+    contract.unpause().expect_ok();
+    push(contract, &validator, U256::from(5)).expect_ok();
+    assert_eq!(contract.get_total_consumption().expect_ok(), U256::from(15));
+
+    // Only a pauser can pause
+    contract
+        .access_control
+        ._revoke_role(B256::from(PAUSER_ROLE), msg::sender());
+    assert!(matches!(contract.pause(), Err(Errors::AccessControl(_))));
+}
+
+#[motsu::test]
+fn initialize_primes_the_domain_separator_cache(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    assert!(contract.eip712.is_initialized());
+    assert_eq!(contract.eip712.cached_chain_id(), block::chainid());
+    let (cached, fresh, matches) = contract.eip712.domain_separator_status().expect_ok();
+    assert!(matches);
+
+    // The first push reads the warm cache, leaving it untouched
+    push(contract, &validator, U256::from(10)).expect_ok();
+    assert_eq!(contract.eip712.cached_chain_id(), block::chainid());
+    assert_eq!(
+        contract.eip712.domain_separator_status().expect_ok(),
+        (cached, fresh, true)
+    );
+}
+
+#[motsu::test]
+fn transfer_ownership_routes_to_the_two_step_override(contract: ChannelConsumptionContract) {
+    use stylus_sdk::abi::Router;
+
+    setup(contract);
+    let new_owner = Address::repeat_byte(0x22);
+
+    // Called through the router, as on chain, rather than the method: the inherited one step
+    // `Ownable::transferOwnership` would hand over the ownership right away
+    let selector = u32::from_be_bytes(
+        keccak(b"transferOwnership(address)")[..4]
+            .try_into()
+            .unwrap(),
+    );
+    let input = <sol! { (address,) }>::abi_encode_params(&(new_owner,));
+    let routed = <ChannelConsumptionContract as Router<ChannelConsumptionContract>>::route(
+        contract, selector, &input,
+    );
+    assert!(matches!(routed, Some(Ok(_))));
+
+    assert_eq!(contract.ownable.owner(), msg::sender());
+    assert_eq!(contract.pending_owner().expect_ok(), new_owner);
+}
+
+#[motsu::test]
+fn pending_owner_has_no_power_until_accepting(contract: ChannelConsumptionContract) {
+    setup(contract);
+    // The test sender can't be anyone else, so it plays the pending owner of another owner
+    let (current_owner, pending_owner) = (Address::repeat_byte(0x22), msg::sender());
+    contract.ownable._transfer_ownership(current_owner);
+    contract.pending_owner.set(pending_owner);
+
+    assert!(matches!(
+        contract.set_batch_gas_reserve(U256::from(1)),
+        Err(Errors::Ownable(_))
+    ));
+    assert!(matches!(
+        contract.transfer_ownership(pending_owner),
+        Err(Errors::Ownable(_))
+    ));
+
+    contract.accept_ownership().expect_ok();
+    assert_eq!(contract.ownable.owner(), pending_owner);
+    assert_eq!(contract.pending_owner().expect_ok(), Address::ZERO);
+    contract.set_batch_gas_reserve(U256::from(1)).expect_ok();
+}
+
+#[motsu::test]
+fn compact_signature_recovers_the_same_signer(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let (user, added, deadline) = (msg::sender(), U256::from(10), deadline());
+    let (v, r, s) = sign_push(contract, &validator, user, added, deadline);
+
+    // `vs` carries the y parity in its top bit
+    let mut vs = s;
+    vs.0[0] |= (v - 27) << 7;
+    let (compact_v, compact_s) = split_compact_vs(&vs.0);
+    assert_eq!((compact_v, FixedBytes(compact_s)), (v, s));
+
+    let struct_hash = ChannelConsumptionContract::_consumption_struct_hash(
+        user,
+        CHANNEL_ID,
+        added,
+        deadline,
+        contract.get_nonce(user).expect_ok(),
+    );
+    let expanded = contract
+        .eip712
+        .recover_typed_data_signer_view(struct_hash, v, r, s)
+        .expect_ok();
+    let compact = contract
+        .eip712
+        .recover_typed_data_signer_view(struct_hash, compact_v, r, FixedBytes(compact_s))
+        .expect_ok();
+    assert_eq!(expanded, address_of(&validator));
+    assert_eq!(compact, expanded);
+
+    contract
+        .push_ccu_compact(CHANNEL_ID, added, deadline, r, vs)
+        .expect_ok();
+    assert_eq!(contract.get_user_consumption(user).expect_ok(), added);
+}
+
+#[motsu::test]
+fn interface_ids_match_the_signature_strings(contract: ChannelConsumptionContract) {
+    let selector =
+        |signature: &str| -> [u8; 4] { keccak(signature.as_bytes())[..4].try_into().unwrap() };
+    let mut consumption_id = [0u8; 4];
+    for signature in [
+        "pushCcu(bytes32,uint256,uint256,uint8,bytes32,bytes32)",
+        "getUserConsumption(address)",
+        "getTotalConsumption()",
+    ] {
+        for (byte, selector_byte) in consumption_id.iter_mut().zip(selector(signature)) {
+            *byte ^= selector_byte;
+        }
+    }
+    assert_eq!(selectors::CONSUMPTION_INTERFACE_ID, consumption_id);
+    assert_eq!(
+        selectors::ERC165_INTERFACE_ID,
+        selector("supportsInterface(bytes4)")
+    );
+
+    assert!(contract
+        .supports_interface(FixedBytes(consumption_id))
+        .expect_ok());
+    assert!(contract
+        .supports_interface(FixedBytes(selectors::ERC165_INTERFACE_ID))
+        .expect_ok());
+    // The ERC-165 invalid interface id
+    assert!(!contract
+        .supports_interface(FixedBytes([0xff; 4]))
+        .expect_ok());
+}
+
+#[motsu::test]
+fn last_update_is_set_by_a_successful_push_only(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    assert_eq!(contract.get_last_update(user).expect_ok(), U256::ZERO);
+
+    // A push by an unknown validator is silently rejected
+    push(contract, &key(2), U256::from(10)).expect_ok();
+    assert_eq!(contract.get_last_update(user).expect_ok(), U256::ZERO);
+
+    push(contract, &validator, U256::from(10)).expect_ok();
+    assert_eq!(
+        contract.get_last_update(user).expect_ok(),
+        U256::from(block::timestamp())
+    );
+    assert_eq!(
+        contract.get_last_update(user).expect_ok(),
+        contract.get_last_push_at(user).expect_ok()
+    );
+}
+
+#[motsu::test]
+fn reentering_a_push_during_an_external_call_reverts(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let deadline = deadline();
+    let (v, r, s) = sign_push(
+        contract,
+        &validator,
+        msg::sender(),
+        U256::from(10),
+        deadline,
+    );
+
+    // A reward token calling back in holds the call lock, as set around every mutable external call
+    contract.call_lock.set(true);
+    assert!(matches!(
+        contract.push_ccu(CHANNEL_ID, U256::from(10), deadline, v, r, s),
+        Err(Errors::ReentrantCall(_))
+    ));
+    assert!(matches!(
+        contract.push_ccu_batch(
+            vec![CHANNEL_ID],
+            vec![U256::from(10)],
+            vec![deadline],
+            vec![v],
+            vec![r],
+            vec![s],
+            false,
+        ),
+        Err(Errors::ReentrantCall(_))
+    ));
+    // Views stay readable from the callback
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::ZERO
+    );
+
+    contract.call_lock.set(false);
+    contract
+        .push_ccu(CHANNEL_ID, U256::from(10), deadline, v, r, s)
+        .expect_ok();
+}
+
+#[motsu::test]
+fn migration_can_be_split_until_finalized(contract: ChannelConsumptionContract) {
+    setup(contract);
+    let (alice, bob, carol) = (
+        address_of(&key(3)),
+        address_of(&key(4)),
+        address_of(&key(5)),
+    );
+
+    contract
+        .migrate_consumption(vec![alice, bob], vec![U256::from(10), U256::from(20)])
+        .expect_ok();
+    contract
+        .migrate_consumption(vec![carol], vec![U256::from(30)])
+        .expect_ok();
+    assert_eq!(
+        contract
+            .export_consumption(vec![alice, bob, carol])
+            .expect_ok(),
+        vec![U256::from(10), U256::from(20), U256::from(30)]
+    );
+    assert_eq!(contract.get_total_consumption().expect_ok(), U256::from(60));
+    // The migrated users are listed like the pushing ones
+    assert_eq!(
+        contract.get_users(U256::ZERO, U256::from(10)).expect_ok(),
+        vec![alice, bob, carol]
+    );
+
+    contract.finalize_migration().expect_ok();
+    assert!(contract.is_migrated().expect_ok());
+    assert!(matches!(
+        contract.migrate_consumption(vec![alice], vec![U256::from(1)]),
+        Err(Errors::AlreadyMigrated(_))
+    ));
+    assert!(matches!(
+        contract.finalize_migration(),
+        Err(Errors::AlreadyMigrated(_))
+    ));
+}
+
+#[motsu::test]
+fn migration_past_the_max_consumption_reverts(contract: ChannelConsumptionContract) {
+    setup(contract);
+    let (alice, bob) = (address_of(&key(3)), address_of(&key(4)));
+    contract
+        .migrate_consumption(vec![alice], vec![U256::MAX])
+        .expect_ok();
+
+    // Overflowing either the user total or the global one
+    assert!(matches!(
+        contract.migrate_consumption(vec![alice], vec![U256::from(1)]),
+        Err(Errors::ConsumptionOverflow(_))
+    ));
+    assert!(matches!(
+        contract.migrate_consumption(vec![bob], vec![U256::from(1)]),
+        Err(Errors::ConsumptionOverflow(_))
+    ));
+    assert!(matches!(
+        contract.migrate_consumption(vec![bob], vec![]),
+        Err(Errors::LengthMismatch(_))
+    ));
+}
+
+/// Keep a single one of the owner roles on the test sender
+fn keep_only_role(contract: &mut ChannelConsumptionContract, kept: [u8; 32]) {
+    for role in OWNER_ROLES {
+        if role != kept {
+            contract
+                .access_control
+                ._revoke_role(B256::from(role), msg::sender());
+        }
+    }
+}
+
+#[motsu::test]
+fn pauser_cannot_update_the_registry(contract: ChannelConsumptionContract) {
+    setup(contract);
+    keep_only_role(contract, PAUSER_ROLE);
+
+    contract.pause().expect_ok();
+    contract.unpause().expect_ok();
+    assert!(matches!(
+        contract.set_content_registry(address_of(&key(9))),
+        Err(Errors::AccessControl(_))
+    ));
+    assert!(matches!(
+        contract.reset_user_consumption(msg::sender()),
+        Err(Errors::AccessControl(_))
+    ));
+}
+
+#[motsu::test]
+fn registry_admin_cannot_pause(contract: ChannelConsumptionContract) {
+    setup(contract);
+    keep_only_role(contract, REGISTRY_ADMIN_ROLE);
+
+    let registry = address_of(&key(9));
+    contract.set_content_registry(registry).expect_ok();
+    assert_eq!(contract.content_registry.get(), registry);
+    assert!(matches!(contract.pause(), Err(Errors::AccessControl(_))));
+    assert!(matches!(
+        contract.set_user_consumption(msg::sender(), U256::from(1)),
+        Err(Errors::AccessControl(_))
+    ));
+}
+
+#[motsu::test]
+fn consumption_corrections_need_the_consumption_admin_role(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    push(contract, &validator, U256::from(10)).expect_ok();
+    let user = msg::sender();
+
+    contract
+        .decrease_user_consumption(user, U256::from(4))
+        .expect_ok();
+    contract
+        .set_user_consumption(user, U256::from(8))
+        .expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(8)
+    );
+
+    contract
+        .access_control
+        ._revoke_role(B256::from(CONSUMPTION_ADMIN_ROLE), user);
+    // Still the owner, but not allowed to correct the totals anymore
+    assert_eq!(contract.ownable.owner(), user);
+    assert!(matches!(
+        contract.reset_user_consumption(user),
+        Err(Errors::AccessControl(_))
+    ));
+    assert!(matches!(
+        contract.reset_user_consumption_batch(vec![user]),
+        Err(Errors::AccessControl(_))
+    ));
+    assert!(matches!(
+        contract.decrease_user_consumption(user, U256::from(1)),
+        Err(Errors::AccessControl(_))
+    ));
+    assert!(matches!(
+        contract.set_user_consumption(user, U256::ZERO),
+        Err(Errors::AccessControl(_))
+    ));
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(8)
+    );
+}
+
+#[motsu::test]
+fn accepting_the_ownership_moves_the_owner_roles(contract: ChannelConsumptionContract) {
+    setup(contract);
+    let new_owner = msg::sender();
+    let previous_owner = address_of(&key(9));
+    let pauser = address_of(&key(10));
+
+    // The sender is fixed, so the previous owner is set up directly, with a pauser role handed out
+    contract.ownable._transfer_ownership(previous_owner);
+    for role in OWNER_ROLES {
+        contract
+            .access_control
+            ._revoke_role(B256::from(role), new_owner);
+        contract
+            .access_control
+            ._grant_role(B256::from(role), previous_owner);
+    }
+    contract
+        .access_control
+        ._grant_role(B256::from(PAUSER_ROLE), pauser);
+    contract.pending_owner.set(new_owner);
+
+    contract.accept_ownership().expect_ok();
+    assert_eq!(contract.ownable.owner(), new_owner);
+    for role in OWNER_ROLES {
+        assert!(contract
+            .access_control
+            .has_role(B256::from(role), new_owner));
+        assert!(!contract
+            .access_control
+            .has_role(B256::from(role), previous_owner));
+    }
+    assert!(contract
+        .access_control
+        .has_role(B256::from(PAUSER_ROLE), pauser));
+    contract.pause().expect_ok();
+}
+
+/// Sign a `pushCcuMultiSig` of the test sender by each of the given validators
+fn push_multi_sig(
+    contract: &mut ChannelConsumptionContract,
+    validators: &[&SigningKey],
+    added_consumption: U256,
+) -> Result<(), Errors> {
+    let deadline = deadline();
+    let (mut vs, mut rs, mut ss) = (vec![], vec![], vec![]);
+    for validator in validators {
+        let (v, r, s) = sign_push(
+            contract,
+            validator,
+            msg::sender(),
+            added_consumption,
+            deadline,
+        );
+        vs.push(v);
+        rs.push(r);
+        ss.push(s);
+    }
+    contract.push_ccu_multi_sig(CHANNEL_ID, added_consumption, deadline, vs, rs, ss)
+}
+
+#[motsu::test]
+fn multi_sig_signers_go_through_the_validator_rate_limit(contract: ChannelConsumptionContract) {
+    let first = setup(contract);
+    let second = key(2);
+    contract.add_validator(address_of(&second)).expect_ok();
+    contract.set_validator_quorum(U256::from(2)).expect_ok();
+    contract
+        .set_validator_rate_limit(U256::from(1), U256::from(3600))
+        .expect_ok();
+
+    // A repeated signer counts once, toward the quorum and the rate limit
+    push_multi_sig(contract, &[&first, &second, &first], U256::from(10)).expect_ok();
+    assert_eq!(
+        contract.validator_window_pushes.get(address_of(&first)),
+        U256::from(1)
+    );
+    assert_eq!(
+        contract.validator_window_pushes.get(address_of(&second)),
+        U256::from(1)
+    );
+
+    // Both signers are out of budget for this window
+    assert!(matches!(
+        push_multi_sig(contract, &[&first, &second], U256::from(10)),
+        Err(Errors::InvalidPlatformSignature(_))
+    ));
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(10)
+    );
+}
+
+#[motsu::test]
+fn effective_credit_is_zero_while_the_channel_cools_down(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    contract
+        .set_min_channel_push_interval(U256::from(60))
+        .expect_ok();
+    push(contract, &validator, U256::from(10)).expect_ok();
+
+    assert_eq!(
+        contract
+            .effective_credited_amount(user, CHANNEL_ID, U256::from(10))
+            .expect_ok(),
+        U256::ZERO
+    );
+    assert!(matches!(
+        push(contract, &validator, U256::from(10)),
+        Err(Errors::CooldownActive(_))
+    ));
+
+    // Another channel isn't cooling down
+    assert_eq!(
+        contract
+            .effective_credited_amount(user, FixedBytes([8u8; 32]), U256::from(10))
+            .expect_ok(),
+        U256::from(10)
+    );
+}
+
+#[motsu::test]
+fn multi_sig_honours_the_channel_validator_override(contract: ChannelConsumptionContract) {
+    let first = setup(contract);
+    let second = key(2);
+    contract.add_validator(address_of(&second)).expect_ok();
+    contract.set_validator_quorum(U256::from(2)).expect_ok();
+
+    // Only the override validator is accepted on the channel, so a second signer is refused
+    contract
+        .channel_validators
+        .insert(CHANNEL_ID, address_of(&first));
+    assert!(matches!(
+        push_multi_sig(contract, &[&first, &second], U256::from(10)),
+        Err(Errors::InvalidPlatformSignature(_))
+    ));
+    assert_eq!(contract.get_nonce(msg::sender()).expect_ok(), U256::ZERO);
+
+    contract.set_validator_quorum(U256::from(1)).expect_ok();
+    push_multi_sig(contract, &[&first], U256::from(10)).expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::from(10)
+    );
+}
+
+#[motsu::test]
+fn multi_content_push_honours_the_channel_validator_override(contract: ChannelConsumptionContract) {
+    let first = setup(contract);
+    let second = key(2);
+    contract.add_validator(address_of(&second)).expect_ok();
+    contract
+        .channel_validators
+        .insert(CHANNEL_ID, address_of(&first));
+    let (content_ids, added) = (
+        vec![U256::from(2), U256::from(3)],
+        vec![U256::from(10), U256::from(5)],
+    );
+    let deadline = deadline();
+
+    // Authorized on the contents, but not the channel's validator: every entry is skipped
+    let (_, (v, r, s)) = sign_multi_content(contract, &second, &content_ids, &added, deadline);
+    let credited = contract
+        .push_ccu_multi_content(
+            content_ids.clone(),
+            vec![CHANNEL_ID; 2],
+            added.clone(),
+            deadline,
+            v,
+            r,
+            s,
+        )
+        .expect_ok();
+    assert_eq!(credited, U256::ZERO);
+    assert_eq!(
+        contract.get_user_consumption(msg::sender()).expect_ok(),
+        U256::ZERO
+    );
+
+    let (_, (v, r, s)) = sign_multi_content(contract, &first, &content_ids, &added, deadline);
+    let credited = contract
+        .push_ccu_multi_content(content_ids, vec![CHANNEL_ID; 2], added, deadline, v, r, s)
+        .expect_ok();
+    assert_eq!(credited, U256::from(0b11));
+}
+
+#[motsu::test]
+fn cohort_leaf_honours_the_channel_validator_override(contract: ChannelConsumptionContract) {
+    let first = setup(contract);
+    let second = key(2);
+    contract.add_validator(address_of(&second)).expect_ok();
+    contract
+        .channel_validators
+        .insert(CHANNEL_ID, address_of(&first));
+    let user = msg::sender();
+    let root = ChannelConsumptionContract::_cohort_leaf(user, CHANNEL_ID, U256::from(10));
+
+    // Each cohort gets its own deadline, so its own approval
+    let push_cohort =
+        |contract: &mut ChannelConsumptionContract, signer: &SigningKey, deadline: U256| {
+            let (v, r, s) = sign(
+                signer,
+                contract._push_digest(ChannelConsumptionContract::_cohort_struct_hash(
+                    root, deadline,
+                )),
+            );
+            contract.push_ccu_cohort(
+                user,
+                CHANNEL_ID,
+                U256::from(10),
+                vec![],
+                root,
+                deadline,
+                v,
+                r,
+                s,
+            )
+        };
+
+    // A cohort signed by another validator is silently refused on the channel, leaving the leaf unclaimed
+    push_cohort(contract, &second, deadline()).expect_ok();
+    assert_eq!(contract.get_user_consumption(user).expect_ok(), U256::ZERO);
+
+    push_cohort(contract, &first, deadline() + U256::from(1)).expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(10)
+    );
+}
+
+#[motsu::test]
+fn pushing_past_the_max_consumption_reverts(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+
+    // The accrued rewards
+    contract
+        .set_content_reward_rate(CONTENT_ID, U256::MAX)
+        .expect_ok();
+    assert!(matches!(
+        push(contract, &validator, U256::from(2)),
+        Err(Errors::ConsumptionOverflow(_))
+    ));
+    contract
+        .set_content_reward_rate(CONTENT_ID, U256::ZERO)
+        .expect_ok();
+
+    // The per content total
+    contract
+        .content_user_consumptions
+        .setter(CONTENT_ID)
+        .setter(user)
+        .set(U256::MAX);
+    assert!(matches!(
+        push(contract, &validator, U256::from(1)),
+        Err(Errors::ConsumptionOverflow(_))
+    ));
+    contract
+        .content_user_consumptions
+        .setter(CONTENT_ID)
+        .setter(user)
+        .set(U256::ZERO);
+
+    // The user total
+    contract
+        .user_consumptions
+        .insert(user, U256::MAX - U256::from(5));
+    assert!(matches!(
+        push(contract, &validator, U256::from(10)),
+        Err(Errors::ConsumptionOverflow(_))
+    ));
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::MAX - U256::from(5)
+    );
+}
+
+#[motsu::test]
+fn confirming_past_the_max_consumption_reverts(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    contract.set_challenge_period(U256::from(1)).expect_ok();
+    push(contract, &validator, U256::from(10)).expect_ok();
+
+    // The escrow matured, but would overflow the confirmed total
+    contract.pending_release_at.insert(user, U256::ZERO);
+    contract
+        .user_consumptions
+        .insert(user, U256::MAX - U256::from(5));
+    assert!(matches!(
+        contract.confirm_pending(user),
+        Err(Errors::ConsumptionOverflow(_))
+    ));
+}
+
+#[motsu::test]
+fn push_dropped_by_the_circuit_breaker_can_be_sent_again(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    contract
+        .set_circuit_breaker(U256::from(10), U256::ZERO)
+        .expect_ok();
+    push(contract, &validator, U256::from(5)).expect_ok();
+
+    // Past the maximum increase of the block: dropped, pausing the contract, with the signature left unused
+    let deadline = deadline();
+    let added = U256::from(10);
+    let (v, r, s) = sign_push(contract, &validator, user, added, deadline);
+    let digest = contract
+        .push_digest(user, CHANNEL_ID, added, deadline)
+        .expect_ok();
+    assert_eq!(
+        contract
+            .push_ccu_checked(CHANNEL_ID, added, deadline, v, r, s)
+            .expect_ok(),
+        (false, U256::from(5))
+    );
+    assert!(contract.is_paused().expect_ok());
+    assert_eq!(contract.get_nonce(user).expect_ok(), U256::from(1));
+    assert!(!contract.used_or_cancelled.get(digest));
+
+    // Once unpaused (and the breaker raised), the very same signature goes through
+    contract.unpause().expect_ok();
+    contract
+        .set_circuit_breaker(U256::from(100), U256::ZERO)
+        .expect_ok();
+    assert_eq!(
+        contract
+            .push_ccu_checked(CHANNEL_ID, added, deadline, v, r, s)
+            .expect_ok(),
+        (true, U256::from(15))
+    );
+    assert_eq!(contract.get_nonce(user).expect_ok(), U256::from(2));
+    assert!(contract.used_or_cancelled.get(digest));
+}
+
+#[motsu::test]
+fn queued_pushes_dropped_by_the_circuit_breaker_stay_queued(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    contract
+        .set_circuit_breaker(U256::from(10), U256::ZERO)
+        .expect_ok();
+    contract.set_freeze_policy(FREEZE_POLICY_QUEUE).expect_ok();
+    contract.freeze_rewards().expect_ok();
+    for added in [5, 10, 3] {
+        push(contract, &validator, U256::from(added)).expect_ok();
+    }
+
+    // The second entry trips the breaker: it stays queued, with the one after it
+    contract.unfreeze_rewards().expect_ok();
+    assert!(contract.is_paused().expect_ok());
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(5)
+    );
+    assert_eq!(contract.get_rewards_freeze().expect_ok().2, U256::from(2));
+    assert_eq!(contract.frozen_push_amounts.get(0), Some(U256::from(10)));
+    assert_eq!(contract.frozen_push_amounts.get(1), Some(U256::from(3)));
+
+    // Credited by the next unfreeze, once unpaused (and the breaker raised)
+    contract.unpause().expect_ok();
+    contract
+        .set_circuit_breaker(U256::from(100), U256::ZERO)
+        .expect_ok();
+    contract.unfreeze_rewards().expect_ok();
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(18)
+    );
+    assert_eq!(contract.get_rewards_freeze().expect_ok().2, U256::ZERO);
+}
+
+#[motsu::test]
+fn batch_stops_at_the_entry_dropped_by_the_circuit_breaker(contract: ChannelConsumptionContract) {
+    let validator = setup(contract);
+    let user = msg::sender();
+    contract
+        .set_circuit_breaker(U256::from(10), U256::ZERO)
+        .expect_ok();
+
+    // Entries signed for consecutive nonces, the second one tripping the breaker
+    let deadline = deadline();
+    let added = [U256::from(5), U256::from(10)];
+    let (mut vs, mut rs, mut ss) = (vec![], vec![], vec![]);
+    for (nonce, added) in added.iter().enumerate() {
+        let struct_hash = ChannelConsumptionContract::_consumption_struct_hash(
+            user,
+            CHANNEL_ID,
+            *added,
+            deadline,
+            U256::from(nonce),
+        );
+        let (v, r, s) = sign(&validator, contract._push_digest(struct_hash));
+        vs.push(v);
+        rs.push(r);
+        ss.push(s);
+    }
+
+    let (credited, processed, done) = contract
+        .push_ccu_batch(
+            vec![CHANNEL_ID; 2],
+            added.to_vec(),
+            vec![deadline; 2],
+            vs,
+            rs,
+            ss,
+            true,
+        )
+        .expect_ok();
+    assert_eq!(
+        (credited, processed, done),
+        (vec![true], U256::from(1), false)
+    );
+    assert_eq!(contract.get_nonce(user).expect_ok(), U256::from(1));
+    assert_eq!(
+        contract.get_user_consumption(user).expect_ok(),
+        U256::from(5)
+    );
+    assert!(contract.is_paused().expect_ok());
+}
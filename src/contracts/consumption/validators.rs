@@ -0,0 +1,222 @@
+//! The validator checks (validation modes, registry, channel overrides, rate limits)
+
+use super::*;
+
+impl ChannelConsumptionContract {
+    /// Check that the validator has the right roles
+    pub(super) fn _check_validator_role(&mut self, validator: Address) -> Result<(), Errors> {
+        let content_id = self.nutty_content_id.get();
+        self._check_validator_role_for_content(content_id, validator)
+    }
+
+    /// Check that the validator has the right roles on a given content
+    pub(super) fn _check_validator_role_for_content(
+        &mut self,
+        content_id: U256,
+        validator: Address,
+    ) -> Result<(), Errors> {
+        // Expired validator keys are rejected, whatever the registry says
+        if self._is_validator_expired(validator) {
+            return Err(Errors::InvalidPlatformSignature(
+                InvalidPlatformSignature {},
+            ));
+        }
+
+        // The zero address (an unrecoverable signature) is never a validator, no need to ask the registry
+        if validator.is_zero() {
+            return Err(Errors::InvalidPlatformSignature(
+                InvalidPlatformSignature {},
+            ));
+        }
+
+        // Outside of the registry mode, no need to call anyone
+        if let Some(is_authorized) = self._check_validator_locally(validator) {
+            return if is_authorized {
+                Ok(())
+            } else {
+                Err(Errors::InvalidPlatformSignature(
+                    InvalidPlatformSignature {},
+                ))
+            };
+        }
+
+        // Locally listed validators don't need the registry call, the others fall back to it
+        if self.local_validators.get(validator) {
+            return Ok(());
+        }
+
+        // Ensure the signer has the interaction validator roles for this content)
+        // (through a static call, so the registry can't mutate any state, ours included)
+        // (without a registry, nobody is authorized, rather than calling the zero address)
+        let content_registry = self.content_registry.get();
+        if content_registry.is_zero() {
+            return Err(Errors::InvalidPlatformSignature(
+                InvalidPlatformSignature {},
+            ));
+        }
+        let has_role =
+            static_call_helper::<isAuthorizedCall>(content_registry, (content_id, validator))
+                .map_err(|_| Errors::CallError(CallError {}))?;
+
+        // Return the right state depending on the output
+        if has_role._0 {
+            Ok(())
+        } else {
+            Err(Errors::InvalidPlatformSignature(
+                InvalidPlatformSignature {},
+            ))
+        }
+    }
+
+    /// Check a validator against the single platform signer or the allowlist, depending on the validation mode
+    /// Returns `None` in the registry mode, where the content registry must be asked
+    pub(super) fn _check_validator_locally(&self, validator: Address) -> Option<bool> {
+        match self.validation_mode.get().to::<u8>() {
+            VALIDATION_MODE_SINGLE_SIGNER => {
+                Some(!validator.is_zero() && validator == self.platform_signer.get())
+            }
+            VALIDATION_MODE_ALLOWLIST => Some(self.validator_allowlist.get(validator)),
+            _ => None,
+        }
+    }
+
+    /// Check if a locally tracked validator key is past its expiry
+    pub(super) fn _is_validator_expired(&self, validator: Address) -> bool {
+        let expiry = self.validator_expiry.get(validator);
+        !expiry.is_zero() && U256::from(block::timestamp()) >= expiry
+    }
+
+    /// Start tracking a validator key lifetime, from now and for the validity period (no-op without one)
+    pub(super) fn _activate_validator(&mut self, validator: Address) {
+        let period = self.validator_validity_period.get();
+        if period.is_zero() {
+            return;
+        }
+        let expiry = U256::from(block::timestamp()) + period;
+        self.validator_expiry.insert(validator, expiry);
+        evm::log(ValidatorExpirySet { validator, expiry });
+    }
+
+    /// Check that a recovered signer can validate a push: validator role, and validator rate limit
+    /// Returns false on an unauthorized validator, counting the push as rejected without leaking the reason
+    /// A failing registry call reverts with `CallError`, unless registry failures are configured as silent
+    pub(super) fn _accept_validator(&mut self, validator: Address) -> Result<bool, Errors> {
+        let content_id = self.nutty_content_id.get();
+        self._accept_validator_for_content(content_id, validator)
+    }
+
+    /// Same as `_accept_validator`, but checking the validator roles on a given content
+    pub(super) fn _accept_validator_for_content(
+        &mut self,
+        content_id: U256,
+        validator: Address,
+    ) -> Result<bool, Errors> {
+        let accepted = self._is_validator_authorized(content_id, validator)?
+            && self._count_validator_push(validator);
+        Ok(self._record_validator_acceptance(accepted))
+    }
+
+    /// Same as `_accept_validator`, but for a push on a single channel, honoring its validator override
+    /// A channel with a validator override only accepts that validator, without asking the registry
+    pub(super) fn _accept_channel_validator(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        validator: Address,
+    ) -> Result<bool, Errors> {
+        let content_id = self.nutty_content_id.get();
+        self._accept_channel_validator_for_content(content_id, channel_id, validator)
+    }
+
+    /// Same as `_accept_channel_validator`, but checking the validator roles on a given content
+    pub(super) fn _accept_channel_validator_for_content(
+        &mut self,
+        content_id: U256,
+        channel_id: FixedBytes<32>,
+        validator: Address,
+    ) -> Result<bool, Errors> {
+        let override_validator = self.channel_validators.get(channel_id);
+        if override_validator.is_zero() {
+            return self._accept_validator_for_content(content_id, validator);
+        }
+        let accepted = validator == override_validator
+            && !self._is_validator_expired(validator)
+            && self._count_validator_push(validator);
+        Ok(self._record_validator_acceptance(accepted))
+    }
+
+    /// Same as `_accept_channel_validator`, remembering the validators authorized within the current call
+    /// (e.g. a batch), so a repeated signer doesn't call the registry again; it can't change mid call anyway
+    pub(super) fn _accept_channel_validator_cached(
+        &mut self,
+        channel_id: FixedBytes<32>,
+        validator: Address,
+        authorized_validators: &mut Vec<Address>,
+    ) -> Result<bool, Errors> {
+        let has_override = !self.channel_validators.get(channel_id).is_zero();
+        if has_override || !authorized_validators.contains(&validator) {
+            let accepted = self._accept_channel_validator(channel_id, validator)?;
+            if accepted && !has_override {
+                authorized_validators.push(validator);
+            }
+            return Ok(accepted);
+        }
+
+        // Already authorized, only the validator rate limit is left to check
+        let accepted = self._count_validator_push(validator);
+        Ok(self._record_validator_acceptance(accepted))
+    }
+
+    /// Check if a validator has the roles on a content, telling a failing registry call apart from a refusal
+    /// The registry call failure is only swallowed (as a refusal) if registry failures are configured as silent
+    pub(super) fn _is_validator_authorized(
+        &mut self,
+        content_id: U256,
+        validator: Address,
+    ) -> Result<bool, Errors> {
+        match self._check_validator_role_for_content(content_id, validator) {
+            Ok(()) => Ok(true),
+            Err(Errors::CallError(error)) if !self.silent_registry_failures.get() => {
+                Err(Errors::CallError(error))
+            }
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Count a push as accepted or rejected by the validator checks, returning whether it was accepted
+    pub(super) fn _record_validator_acceptance(&mut self, accepted: bool) -> bool {
+        if accepted {
+            self.accepted_pushes
+                .set(self.accepted_pushes.get() + U256::from(1));
+        } else {
+            self.rejected_pushes
+                .set(self.rejected_pushes.get() + U256::from(1));
+        }
+        accepted
+    }
+
+    /// Count an accepted push for a validator in the current window
+    /// Returns false if the validator exceeded its budget (no-op while no rate limit is set)
+    pub(super) fn _count_validator_push(&mut self, validator: Address) -> bool {
+        let max_pushes = self.validator_max_pushes.get();
+        let window = self.validator_window.get();
+        if max_pushes.is_zero() || window.is_zero() {
+            return true;
+        }
+
+        // Reset the counter if we switched to a new window
+        let window_index = U256::from(block::timestamp()) / window;
+        let pushes = if self.validator_window_index.get(validator) == window_index {
+            self.validator_window_pushes.get(validator)
+        } else {
+            self.validator_window_index.insert(validator, window_index);
+            U256::ZERO
+        };
+        let pushes = pushes + U256::from(1);
+        if pushes > max_pushes {
+            return false;
+        }
+
+        self.validator_window_pushes.insert(validator, pushes);
+        true
+    }
+}
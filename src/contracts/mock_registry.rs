@@ -0,0 +1,94 @@
+use alloy_primitives::{Address, U256};
+use openzeppelin_stylus::access::ownable;
+use stylus_sdk::{
+    msg,
+    prelude::*,
+    storage::{StorageAddress, StorageBool, StorageMap, StorageU256},
+};
+
+use crate::utils::errors::Errors;
+
+/// Minimal content registry standing in for the real one on test deployments
+/// Answers the `solidity.rs` registry calls with owner set values, every one of them a view, as they're all
+/// reached through static calls
+#[storage]
+#[entrypoint]
+pub struct MockContentRegistry {
+    owner: StorageAddress,
+    // The set answers (content id => exists, content id => types, content id => caller => authorized)
+    existing_contents: StorageMap<U256, StorageBool>,
+    content_types: StorageMap<U256, StorageU256>,
+    authorized: StorageMap<U256, StorageMap<Address, StorageBool>>,
+}
+
+#[public]
+impl MockContentRegistry {
+    /// Set the caller as owner, if not already set
+    #[selector(name = "initialize")]
+    pub fn initialize(&mut self) -> Result<(), Errors> {
+        if self.owner.get().is_zero() {
+            self.owner.set(msg::sender());
+        }
+        Ok(())
+    }
+
+    /// Set whether a content exists, and its types bitmask
+    #[selector(name = "setContent")]
+    pub fn set_content(
+        &mut self,
+        content_id: U256,
+        exists: bool,
+        types: U256,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.existing_contents.insert(content_id, exists);
+        self.content_types.insert(content_id, types);
+        Ok(())
+    }
+
+    /// Set whether a caller is authorized on a content
+    #[selector(name = "setAuthorized")]
+    pub fn set_authorized(
+        &mut self,
+        content_id: U256,
+        caller: Address,
+        authorized: bool,
+    ) -> Result<(), Errors> {
+        self._only_owner()?;
+        self.authorized
+            .setter(content_id)
+            .insert(caller, authorized);
+        Ok(())
+    }
+
+    /// Registry view: whether a content exists, as set by the owner
+    #[selector(name = "isExistingContent")]
+    pub fn is_existing_content(&self, content_id: U256) -> Result<bool, Errors> {
+        Ok(self.existing_contents.get(content_id))
+    }
+
+    /// Registry view: the content types bitmask, as set by the owner
+    #[selector(name = "getContentTypes")]
+    pub fn get_content_types(&self, content_id: U256) -> Result<U256, Errors> {
+        Ok(self.content_types.get(content_id))
+    }
+
+    /// Registry view: whether a caller is authorized on a content, as set by the owner
+    #[selector(name = "isAuthorized")]
+    pub fn is_authorized(&self, content_id: U256, caller: Address) -> Result<bool, Errors> {
+        Ok(self.authorized.getter(content_id).get(caller))
+    }
+}
+
+impl MockContentRegistry {
+    /// Ensure the caller is the owner, the only one setting the answers
+    fn _only_owner(&self) -> Result<(), Errors> {
+        let sender = msg::sender();
+        if sender != self.owner.get() {
+            return Err(Errors::Ownable(ownable::Error::UnauthorizedAccount(
+                ownable::OwnableUnauthorizedAccount { account: sender },
+            )));
+        }
+        Ok(())
+    }
+}
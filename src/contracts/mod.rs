@@ -1 +1,3 @@
 mod consumption;
+#[cfg(feature = "mock-registry")]
+mod mock_registry;
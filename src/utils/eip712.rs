@@ -1,24 +1,52 @@
+use alloc::{string::String, vec::Vec};
 use core::marker::PhantomData;
 
 use inkmate_common::crypto::ecrecover::EcRecoverTrait;
 use stylus_sdk::{
     alloy_primitives::{Address, FixedBytes, B256, U256, U64},
-    alloy_sol_types::sol,
-    block, contract,
+    alloy_sol_types::{sol, SolCall},
+    block,
+    call::RawCall,
+    contract,
     crypto::keccak,
+    evm,
     prelude::*,
-    storage::{StorageB256, StorageU64},
+    storage::{StorageB256, StorageBool, StorageString, StorageU64},
 };
 
+#[cfg(any(test, feature = "native-ecrecover"))]
+use crate::utils::signature::NativeEcRecover;
+#[cfg(not(test))]
+use crate::utils::signature::PrecompileEcRecover;
 use crate::utils::{
-    errors::{EcRecoverError, Errors},
-    signature::PrecompileEcRecover,
+    errors::{
+        EcRecoverError, EcRecoverFailed, EcRecoverMalformedInput, Errors, InvalidChainId,
+        InvalidSignatureChainId, InvalidSignatureComponents, InvalidSignatureS, InvalidSignatureV,
+    },
+    signature::{is_low_s, is_valid_scalar},
+    solidity::isValidSignatureCall,
 };
 
+/// The ecrecover implementation used on-chain, the precompile (swapped for the pure Rust one in host tests)
+#[cfg(not(test))]
+type DefaultEcRecover = PrecompileEcRecover;
+#[cfg(test)]
+type DefaultEcRecover = NativeEcRecover;
+
+sol! {
+    event EIP712DomainChanged();
+    event DomainSeparatorUpdated(uint64 oldChainId, uint64 newChainId, bytes32 newSeparator);
+}
+
+/// The value returned by an EIP-1271 `isValidSignature` call for a valid signature
+const EIP1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
 pub trait Eip712Params {
     // Name of the contract
     const NAME: &'static str;
     const VERSION: &'static str;
+    // Optional domain salt, telling apart deployments sharing a chain id (none keeps the 4 fields domain)
+    const SALT: Option<[u8; 32]> = None;
 }
 
 // Define the global owned contract storage
@@ -26,48 +54,217 @@ pub trait Eip712Params {
 pub struct Eip712<T: Eip712Params> {
     cached_chain_id: StorageU64,
     cached_domain_separator: StorageB256,
+    allow_eip155_v: StorageBool,
+    // Runtime overrides of the `Eip712Params` name and version (empty for none)
+    name_override: StorageString,
+    version_override: StorageString,
     phantom: PhantomData<T>,
 }
 
 impl<T: Eip712Params> Eip712<T> {
     // Initialise the Eip712 contract (build initial cached domain separator)
     pub fn initialize(&mut self) {
-        let initial_domain_separator = Eip712::<T>::compute_domain_separator();
+        let initial_domain_separator = self.compute_domain_separator();
         self.cached_chain_id.set(U64::from(block::chainid()));
         self.cached_domain_separator.set(initial_domain_separator);
     }
 
-    /// Compute a new domain separator
-    fn compute_domain_separator() -> B256 {
+    /// Check if the domain separator cache has been primed
+    pub fn is_initialized(&self) -> bool {
+        !self.cached_domain_separator.get().is_zero()
+    }
 
-        keccak(
-            <sol! { (bytes32, bytes32, bytes32, uint256, address) }>::encode(&(
-                keccak("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)").0,
-                keccak(T::NAME.as_bytes()).0,
-                keccak(T::VERSION.as_bytes()).0,
-                U256::from(block::chainid()),
-                contract::address(),
-            )),
+    /// Get the chain id the domain separator cache was built for (zero if never primed)
+    pub fn cached_chain_id(&self) -> u64 {
+        self.cached_chain_id.get().to::<u64>()
+    }
+
+    /// Set whether EIP-155 style `v` values (`chainId * 2 + 35 + recovery`) are accepted by `normalize_eip155_v`
+    pub fn set_allow_eip155_v(&mut self, allowed: bool) {
+        self.allow_eip155_v.set(allowed);
+    }
+
+    /// Check whether EIP-155 style `v` values are accepted
+    pub fn allows_eip155_v(&self) -> bool {
+        self.allow_eip155_v.get()
+    }
+
+    /// Normalize a raw `v` back to a plain one, turning an EIP-155 style one (`chainId * 2 + 35 + recovery`)
+    /// into 27 / 28 if allowed, and only if its embedded chain id is the current one
+    /// EIP-712 signatures shouldn't carry it, but some legacy clients do it anyway; it's taken as a `uint256`,
+    /// as it can't fit the `uint8` of the other push selectors beyond chain id 110 (e.g. on any Arbitrum chain)
+    pub fn normalize_eip155_v(&self, v: U256) -> Result<u8, Errors> {
+        if v < U256::from(35) {
+            return Ok(v.to::<u8>());
+        }
+        if !self.allow_eip155_v.get() {
+            return Err(Errors::InvalidSignatureV(InvalidSignatureV {}));
+        }
+        let tag = v - U256::from(35);
+        if tag / U256::from(2) != U256::from(block::chainid()) {
+            return Err(Errors::InvalidSignatureChainId(InvalidSignatureChainId {}));
+        }
+        Ok(27 + (tag % U256::from(2)).to::<u8>())
+    }
+
+    /// Normalize a `v` of 0 / 1 (as produced by some libraries) to 27 / 28, rejecting anything else
+    fn normalize_v(v: u8) -> Result<u8, Errors> {
+        match v {
+            0 | 1 => Ok(v + 27),
+            27 | 28 => Ok(v),
+            _ => Err(Errors::InvalidSignatureV(InvalidSignatureV {})),
+        }
+    }
+
+    /// Override the domain name and version (empty to fall back to the `Eip712Params` ones)
+    /// The domain separator is recomputed and re-cached right away, invalidating every pending signature
+    pub fn set_name_and_version(&mut self, name: &str, version: &str) {
+        self.name_override.set_str(name);
+        self.version_override.set_str(version);
+        let domain_separator = self.compute_domain_separator();
+        self.cached_chain_id.set(U64::from(block::chainid()));
+        self.cached_domain_separator.set(domain_separator);
+        evm::log(EIP712DomainChanged {});
+    }
+
+    /// Get the domain name and version in use, the overrides if set, otherwise the `Eip712Params` ones
+    pub fn name_and_version(&self) -> (String, String) {
+        let name = self.name_override.get_string();
+        let version = self.version_override.get_string();
+        (
+            if name.is_empty() {
+                T::NAME.into()
+            } else {
+                name
+            },
+            if version.is_empty() {
+                T::VERSION.into()
+            } else {
+                version
+            },
         )
     }
 
+    /// Hash of the domain name in use
+    fn hashed_name(&self) -> B256 {
+        let name = self.name_override.get_string();
+        if name.is_empty() {
+            keccak(T::NAME.as_bytes())
+        } else {
+            keccak(name.as_bytes())
+        }
+    }
+
+    /// Hash of the domain version in use
+    fn hashed_version(&self) -> B256 {
+        let version = self.version_override.get_string();
+        if version.is_empty() {
+            keccak(T::VERSION.as_bytes())
+        } else {
+            keccak(version.as_bytes())
+        }
+    }
+
+    /// Compute a new domain separator
+    fn compute_domain_separator(&self) -> B256 {
+        self.compute_domain_separator_for(block::chainid(), contract::address())
+    }
+
+    /// Compute the domain separator for a given chain id and verifying contract
+    fn compute_domain_separator_for(&self, chain_id: u64, verifying_contract: Address) -> B256 {
+        let hashed_name = self.hashed_name();
+        let hashed_version = self.hashed_version();
+        match T::SALT {
+            None => keccak(
+                <sol! { (bytes32, bytes32, bytes32, uint256, address) }>::encode(&(
+                    keccak("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)").0,
+                    hashed_name.0,
+                    hashed_version.0,
+                    U256::from(chain_id),
+                    verifying_contract,
+                )),
+            ),
+            Some(salt) => keccak(
+                <sol! { (bytes32, bytes32, bytes32, uint256, address, bytes32) }>::encode(&(
+                    keccak("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract,bytes32 salt)").0,
+                    hashed_name.0,
+                    hashed_version.0,
+                    U256::from(chain_id),
+                    verifying_contract,
+                    salt,
+                )),
+            ),
+        }
+    }
+
     /// Get the current domain separator
     /// Mutable since, if not cached, it could compute it and store it in cache
+    /// A zero chain id (some exotic test environments) is refused, as it would match a never initialized cache
     pub fn domain_separator(&mut self) -> Result<B256, Errors> {
+        let chain_id = block::chainid();
+        if chain_id == 0 {
+            return Err(Errors::InvalidChainId(InvalidChainId {}));
+        }
+
         // If the chain id is the same, return the cached domain separator
-        if block::chainid() == self.cached_chain_id.get().to::<u64>() {
+        // (a zero cached chain id meaning never initialized, it can't match)
+        let old_chain_id = self.cached_chain_id.get().to::<u64>();
+        if chain_id == old_chain_id {
             Ok(self.cached_domain_separator.get())
         } else {
             // Otherwise, update it
-            let domain_separator = Eip712::<T>::compute_domain_separator();
-            // Updated cached infos
-            self.cached_chain_id.set(U64::from(block::chainid()));
+            let domain_separator = self.compute_domain_separator();
+            // Updated cached infos, leaving a trace of the chain id change
+            self.cached_chain_id.set(U64::from(chain_id));
             self.cached_domain_separator.set(domain_separator);
+            evm::log(DomainSeparatorUpdated {
+                oldChainId: old_chain_id,
+                newChainId: chain_id,
+                newSeparator: domain_separator,
+            });
             // And read it
             Ok(domain_separator)
         }
     }
 
+    /// Get the domain separator without writing to the cache
+    /// Returns the cached value while it's valid for the current chain, otherwise a freshly computed one
+    pub fn current_domain_separator(&self) -> B256 {
+        let cached_chain_id = self.cached_chain_id.get().to::<u64>();
+        if cached_chain_id != 0 && block::chainid() == cached_chain_id {
+            self.cached_domain_separator.get()
+        } else {
+            self.compute_domain_separator()
+        }
+    }
+
+    /// Build the `0x19 0x01 || domainSeparator || structHash` preimage of the typed data digest
+    fn encode_typed_data(domain_separator: B256, struct_hash: B256) -> [u8; 2 + 32 + 32] {
+        let mut digest_input = [0u8; 2 + 32 + 32];
+        digest_input[0] = 0x19;
+        digest_input[1] = 0x01;
+        digest_input[2..34].copy_from_slice(&domain_separator[..]);
+        digest_input[34..66].copy_from_slice(&struct_hash[..]);
+        digest_input
+    }
+
+    /// Get the typed data digest preimage for the given struct hash (read only)
+    pub fn typed_data_preimage(&self, struct_hash: B256) -> [u8; 2 + 32 + 32] {
+        Eip712::<T>::encode_typed_data(self.current_domain_separator(), struct_hash)
+    }
+
+    /// Compute the final typed data digest, `keccak(0x19 0x01 || domainSeparator || structHash)`
+    /// Mutable since, if domain separator not cached, it could recompute it and store it in cache
+    /// The only failure is a zero chain id; a storage read can't fail softly on Stylus (it traps), so there's no
+    /// cache failure to fall back from
+    pub fn hash_typed_data_v4(&mut self, struct_hash: B256) -> Result<B256, Errors> {
+        let domain_separator = self.domain_separator()?;
+        Ok(keccak(Eip712::<T>::encode_typed_data(
+            domain_separator,
+            struct_hash,
+        )))
+    }
+
     /// Recovery the typed data signer
     /// Mutable since, if domain separator not cached, it could recompute  it and store it in cache
     pub fn recover_typed_data_signer(
@@ -77,31 +274,371 @@ impl<T: Eip712Params> Eip712<T> {
         r: FixedBytes<32>,
         s: FixedBytes<32>,
     ) -> Result<Address, Errors> {
-        // Rebuild the digest input
-        let mut digest_input = [0u8; 2 + 32 + 32];
-        digest_input[0] = 0x19;
-        digest_input[1] = 0x01;
-        digest_input[2..34].copy_from_slice(&self.domain_separator()?[..]);
-        digest_input[34..66].copy_from_slice(&struct_hash[..]);
+        let digest = self.hash_typed_data_v4(struct_hash)?;
+        self.recover_digest_signer(digest, v, r, s)
+    }
+
+    /// Recover the signer of a plain message hash, signed with the `personal_sign` scheme
+    /// (`keccak("\x19Ethereum Signed Message:\n32" || messageHash)`), rather than the `0x1901` typed data one
+    /// No domain is involved, so the message itself must bind the chain and the verifying contract
+    pub fn recover_personal_sign(
+        &mut self,
+        message_hash: B256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<Address, Errors> {
+        let digest = Eip712::<T>::personal_sign_digest(message_hash);
+        self.recover_digest_signer(digest, v, r, s)
+    }
+
+    /// Compute the `personal_sign` digest of a message hash
+    pub fn personal_sign_digest(message_hash: B256) -> B256 {
+        keccak(
+            [
+                b"\x19Ethereum Signed Message:\n32".as_slice(),
+                message_hash.as_slice(),
+            ]
+            .concat(),
+        )
+    }
+
+    /// Recover the typed data signer without writing to the domain separator cache (for views)
+    pub fn recover_typed_data_signer_view(
+        &self,
+        struct_hash: B256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<Address, Errors> {
+        let digest = keccak(self.typed_data_preimage(struct_hash));
+        self.recover_digest_signer(digest, v, r, s)
+    }
+
+    /// Recover the signer of a typed data digest
+    fn recover_digest_signer(
+        &self,
+        digest: B256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<Address, Errors> {
+        self.recover_digest_signer_with::<DefaultEcRecover>(digest, v, r, s)
+    }
+
+    /// Same as `recover_digest_signer`, over any ecrecover implementation
+    /// (e.g. a pure Rust one, to test the digest and recovery path off-chain with known key pairs)
+    pub fn recover_digest_signer_with<R: EcRecoverTrait>(
+        &self,
+        digest: B256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+    ) -> Result<Address, Errors> {
+        let v = Eip712::<T>::normalize_v(v)?;
+
+        // Reject zero scalars, which some precompiles map to an arbitrary address rather than failing
+        if r.0 == [0u8; 32] || s.0 == [0u8; 32] {
+            return Err(Errors::InvalidSignatureComponents(
+                InvalidSignatureComponents {},
+            ));
+        }
 
-        // TODO the ecdsa recovery we need:
+        // Reject malleable signatures, before reaching the precompile
+        if !is_low_s(&s.0) {
+            return Err(Errors::InvalidSignatureS(InvalidSignatureS {}));
+        }
+
+        // Reject out of range scalars, which no implementation can recover from
+        if !is_valid_scalar(&r.0) || !is_valid_scalar(&s.0) {
+            return Err(Errors::EcRecoverMalformedInput(EcRecoverMalformedInput {}));
+        }
 
         // Do an ecdsa recovery check on the signature
+        let recovered = R::ecrecover(&digest, v, &r.0, &s.0);
+
+        // Fallback to the pure rust recovery if the precompile path failed
+        #[cfg(feature = "native-ecrecover")]
+        let recovered = recovered.or_else(|_| NativeEcRecover::ecrecover(&digest, v, &r.0, &s.0));
+
         let recovered_address = Address::from_slice(
-            &PrecompileEcRecover::ecrecover(&keccak(digest_input), v, &r.0, &s.0)
-                .map_err(|_| Errors::EcRecoverError(EcRecoverError {}))?,
+            &recovered.map_err(|_| Errors::EcRecoverFailed(EcRecoverFailed {}))?,
         );
 
+        // A malformed signature recovering to the zero address is rejected outright,
+        // rather than relying on the registry to refuse it
+        if recovered_address.is_zero() {
+            return Err(Errors::EcRecoverError(EcRecoverError {}));
+        }
+
         // Return the recovered address
         Ok(recovered_address)
     }
+
+    /// Recover the typed data signer, falling back to an EIP-1271 check against a candidate contract signer
+    /// The ecrecover result is used if it matches the candidate (or without candidate), otherwise the candidate
+    /// is asked through `isValidSignature(digest, r || s || v)`, and returned if it answers the magic value
+    /// If the candidate doesn't validate it either, the ecrecover outcome is returned as is
+    pub fn recover_or_verify_1271(
+        &mut self,
+        struct_hash: B256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
+        contract_signer: Address,
+    ) -> Result<Address, Errors> {
+        let recovered = self.recover_typed_data_signer(struct_hash, v, r, s);
+        if contract_signer.is_zero() {
+            return recovered;
+        }
+        if let Ok(recovered_address) = recovered {
+            if recovered_address == contract_signer {
+                return recovered;
+            }
+        }
+
+        // Ask the candidate contract, with the standard packed signature
+        let digest = keccak(self.typed_data_preimage(struct_hash));
+        let mut signature = Vec::with_capacity(65);
+        signature.extend_from_slice(&r[..]);
+        signature.extend_from_slice(&s[..]);
+        signature.push(v);
+        if Eip712::<T>::is_valid_1271_signature(contract_signer, digest, signature) {
+            Ok(contract_signer)
+        } else {
+            recovered
+        }
+    }
+
+    /// Check a signature against a contract signer, through a static `isValidSignature` call
+    /// Any revert, non contract signer or malformed answer counts as an invalid signature
+    fn is_valid_1271_signature(signer: Address, digest: B256, signature: Vec<u8>) -> bool {
+        let calldata = isValidSignatureCall::new((digest, signature.into())).abi_encode();
+        // SAFETY: static call, the signer can't modify any state, nor reenter us with a state change
+        let res = unsafe { RawCall::new_static().call(signer, &calldata) };
+        match res {
+            Ok(res) => isValidSignatureCall::abi_decode_returns(&res, false)
+                .map(|ret| ret.magicValue.0 == EIP1271_MAGIC_VALUE)
+                .unwrap_or(false),
+            Err(_) => false,
+        }
+    }
 }
 
 #[public]
 impl<T: Eip712Params> Eip712<T> {
-    /// Get the current domain separator
+    /// Get the current domain separator, exactly as used for the signatures recovery
+    /// Reads the cached value, only recomputing (and re-caching) it on a chain id mismatch
     #[selector(name = "domainSeparator")]
-    pub fn read_domain_separator(&self) -> Result<FixedBytes<32>, Errors> {
-        Ok(Eip712::<T>::compute_domain_separator())
+    pub fn read_domain_separator(&mut self) -> Result<FixedBytes<32>, Errors> {
+        self.domain_separator()
+    }
+
+    /// Diagnostic view comparing the cached domain separator with a freshly computed one
+    /// A mismatch means a `refreshDomainSeparator` call is needed
+    #[selector(name = "domainSeparatorStatus")]
+    pub fn domain_separator_status(
+        &self,
+    ) -> Result<(FixedBytes<32>, FixedBytes<32>, bool), Errors> {
+        let cached = self.cached_domain_separator.get();
+        let fresh = self.compute_domain_separator();
+        Ok((cached, fresh, cached == fresh))
+    }
+
+    /// Check that the cached domain separator was computed for this very contract address
+    /// Catches proxy / construction edge cases where `verifyingContract` isn't the deployed address
+    #[selector(name = "verifyingContractMatches")]
+    pub fn verifying_contract_matches(&self) -> Result<bool, Errors> {
+        let expected = self.compute_domain_separator_for(
+            self.cached_chain_id.get().to::<u64>(),
+            contract::address(),
+        );
+        Ok(self.cached_domain_separator.get() == expected)
+    }
+
+    /// Check the current domain separator against a list of acceptable ones (one boolean per entry)
+    #[selector(name = "verifyDomainBatch")]
+    pub fn verify_domain_batch(
+        &self,
+        expected_separators: Vec<FixedBytes<32>>,
+    ) -> Result<Vec<bool>, Errors> {
+        let domain_separator = self.current_domain_separator();
+        Ok(expected_separators
+            .iter()
+            .map(|expected| *expected == domain_separator)
+            .collect())
+    }
+
+    /// Get the EIP-712 domain fields, per EIP-5267, so wallets can rebuild the domain without guessing it
+    /// `fields` flags the used ones: name, version, chain id and verifying contract, plus the salt if any
+    #[selector(name = "eip712Domain")]
+    pub fn eip712_domain(
+        &self,
+    ) -> Result<
+        (
+            FixedBytes<1>,
+            String,
+            String,
+            U256,
+            Address,
+            FixedBytes<32>,
+            Vec<U256>,
+        ),
+        Errors,
+    > {
+        let (name, version) = self.name_and_version();
+        let (fields, salt) = match T::SALT {
+            None => (0x0f, FixedBytes::ZERO),
+            Some(salt) => (0x1f, FixedBytes::from(salt)),
+        };
+        Ok((
+            FixedBytes::from([fields]),
+            name,
+            version,
+            U256::from(block::chainid()),
+            contract::address(),
+            salt,
+            Vec::new(),
+        ))
+    }
+
+    /// Recompute the domain separator and update the cache if it's stale
+    #[selector(name = "refreshDomainSeparator")]
+    pub fn refresh_domain_separator(&mut self) -> Result<FixedBytes<32>, Errors> {
+        self.domain_separator()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use inkmate_common::crypto::ecrecover::{
+        EcRecoverTrait, EcdsaError, EC_RECOVER_INPUT_LEN, NUM_BYTES_ADDRESS,
+    };
+    use stylus_sdk::{
+        alloy_primitives::{address, b256, FixedBytes, U64},
+        block, contract,
+        crypto::keccak,
+    };
+
+    use super::{Eip712, Eip712Params};
+    use crate::utils::errors::Errors;
+
+    struct TestParams;
+
+    impl Eip712Params for TestParams {
+        const NAME: &'static str = "Test";
+        const VERSION: &'static str = "1";
+    }
+
+    /// An ecrecover answering an arbitrary address whatever the input, like a faulty precompile would
+    struct GarbageEcRecover;
+
+    impl EcRecoverTrait for GarbageEcRecover {
+        fn ecrecover_implementation(
+            _input: [u8; EC_RECOVER_INPUT_LEN],
+        ) -> Result<[u8; NUM_BYTES_ADDRESS], EcdsaError> {
+            Ok([0xab; NUM_BYTES_ADDRESS])
+        }
+    }
+
+    /// The domain of the EIP-712 specification example
+    struct MailParams;
+
+    impl Eip712Params for MailParams {
+        const NAME: &'static str = "Ether Mail";
+        const VERSION: &'static str = "1";
+    }
+
+    #[motsu::test]
+    fn status_reports_a_stale_cache(eip712: Eip712<TestParams>) {
+        eip712.initialize();
+        let (cached, fresh, matches) = eip712.domain_separator_status().ok().unwrap();
+        assert_eq!(cached, fresh);
+        assert!(matches);
+
+        // Simulate a chain id change, leaving the cache built for the previous chain
+        let old_chain_id = block::chainid() + 1;
+        let stale = eip712.compute_domain_separator_for(old_chain_id, contract::address());
+        eip712.cached_chain_id.set(U64::from(old_chain_id));
+        eip712.cached_domain_separator.set(stale);
+        let (cached, fresh, matches) = eip712.domain_separator_status().ok().unwrap();
+        assert_eq!(cached, stale);
+        assert_ne!(cached, fresh);
+        assert!(!matches);
+
+        // Refreshing brings it back in line
+        eip712.refresh_domain_separator().ok().unwrap();
+        assert!(eip712.domain_separator_status().ok().unwrap().2);
+    }
+
+    #[motsu::test]
+    fn verify_domain_batch_flags_each_entry(eip712: Eip712<TestParams>) {
+        eip712.initialize();
+        let current = eip712.current_domain_separator();
+        let other = eip712.compute_domain_separator_for(block::chainid() + 1, contract::address());
+
+        let matches = eip712
+            .verify_domain_batch(vec![other, current])
+            .ok()
+            .unwrap();
+        assert_eq!(matches, vec![false, true]);
+    }
+
+    #[motsu::test]
+    fn stale_cache_is_rebuilt_when_hashing(eip712: Eip712<TestParams>) {
+        eip712.initialize();
+        let struct_hash = keccak(b"struct");
+        let expected = keccak(eip712.typed_data_preimage(struct_hash));
+
+        // A cache built for another chain is recomputed rather than used
+        let old_chain_id = block::chainid() + 1;
+        let stale = eip712.compute_domain_separator_for(old_chain_id, contract::address());
+        eip712.cached_chain_id.set(U64::from(old_chain_id));
+        eip712.cached_domain_separator.set(stale);
+        assert_eq!(eip712.hash_typed_data_v4(struct_hash).ok(), Some(expected));
+        assert_eq!(
+            eip712.cached_domain_separator.get(),
+            eip712.compute_domain_separator()
+        );
+    }
+
+    #[motsu::test]
+    fn digest_matches_the_specification_example(eip712: Eip712<MailParams>) {
+        // The `Mail` example of the EIP-712 specification, domain on chain 1
+        let domain_separator = eip712
+            .compute_domain_separator_for(1, address!("CcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"));
+        assert_eq!(
+            domain_separator,
+            b256!("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+        );
+
+        // Cache it as the current chain's, as the test chain and contract address differ from the example
+        eip712.cached_chain_id.set(U64::from(block::chainid()));
+        eip712.cached_domain_separator.set(domain_separator);
+        let mail_hash = b256!("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e");
+        assert_eq!(
+            eip712.hash_typed_data_v4(mail_hash).ok(),
+            Some(b256!(
+                "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+            ))
+        );
+    }
+
+    #[motsu::test]
+    fn zero_signature_scalars_are_rejected_before_recovery(eip712: Eip712<TestParams>) {
+        let digest = keccak(b"digest");
+        let scalar = FixedBytes::<32>::with_last_byte(1);
+        let zero = FixedBytes::<32>::ZERO;
+
+        for (r, s) in [(zero, scalar), (scalar, zero), (zero, zero)] {
+            assert!(matches!(
+                eip712.recover_digest_signer_with::<GarbageEcRecover>(digest, 27, r, s),
+                Err(Errors::InvalidSignatureComponents(_))
+            ));
+        }
+        // Non zero scalars do reach the (faulty) recovery
+        assert!(eip712
+            .recover_digest_signer_with::<GarbageEcRecover>(digest, 27, scalar, scalar)
+            .is_ok());
     }
 }
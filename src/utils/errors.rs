@@ -1,22 +1,284 @@
-use stylus_sdk::{alloy_sol_types::sol, prelude::SolidityError};
+use openzeppelin_stylus::access::{control, ownable};
+use stylus_sdk::{
+    alloy_sol_types::{sol, SolError},
+    prelude::SolidityError,
+};
 
 // Define the global errors
 sol! {
     error AlreadyInitialized();
+    error NotInitialized();
+    error ContractPaused();
     error CallError();
+    error ReentrantCall();
+    error InsufficientGas();
+    error InvalidAddress();
+    error DelegationLoop();
 
     // Eip 712
     error EcRecoverError();
+    error EcRecoverMalformedInput();
+    error EcRecoverFailed();
+    error InvalidSignatureChainId();
+    error InvalidChainId();
+    error InvalidSignatureComponents();
+    error InvalidSignatureS();
+    error InvalidSignatureV();
+    error InvalidSignatureLength();
 
     error InvalidPlatformSignature();
+    error InvalidUserSignature();
+    error ExpiredSignature();
+    error SignatureCancelled();
+    error SignatureRevoked();
+    error DeadlineTooFar();
+    error ValidatorHintMismatch();
+    error QuorumNotReached();
+
+    // Consumption limits
+    error ZeroConsumption();
+    error ContentNotFound();
+    error ChannelDenied();
+    error ChannelContentMismatch();
+    error ChannelNotRegistered();
+    error ChannelCapExceeded();
+    error ConsumptionTooSmall();
+    error ConsumptionTooLarge();
+    error ConsumptionOverflow();
+    error InsufficientConsumption();
+    error UserCapExceeded();
+    error GlobalCapExceeded();
+    error ChannelDailyCapExceeded();
+    error PushCountExceeded();
+    error RateLimited();
+    error ImplausibleConsumptionRate();
+    error ChannelSpreadExceeded();
+    error CooldownActive();
+    error DoublePushInBlock();
+
+    // Rewards
+    error RewardsFrozen();
+    error NothingToClaim();
+    error NothingToWithdraw();
+    error WithdrawFailed();
+    error RewardTransferFailed();
+
+    // Escrow
+    error PendingNotReleasable();
+    error TooManyPendingPushes();
+
+    // Penalties
+    error InvalidPenalty();
+
+    // Queries
+    error LimitTooLarge();
+    error BatchTooLarge();
+    error RangeTooLarge();
+    error LengthMismatch();
+
+    // Migration
+    error AlreadyMigrated();
+
+    // Histograms
+    error InvalidHistogramBuckets();
+
+    // Snapshots
+    error InvalidMerkleProof();
+    error InvalidConsumptionDelta();
+    error InvalidSnapshotId();
+
+    // Cohorts
+    error CohortLeafAlreadyClaimed();
+
+    // Validation mode
+    error InvalidValidationMode();
+
+    // Timelocked config
+    error TimelockRequired();
+    error UnknownConfigChange();
+    error InvalidConfigParams();
+    error ConfigChangeNotQueued();
+    error ConfigChangeNotReady();
 }
 
 #[derive(SolidityError)]
 pub enum Errors {
     AlreadyInitialized(AlreadyInitialized),
+    NotInitialized(NotInitialized),
+    ContractPaused(ContractPaused),
     CallError(CallError),
+    ReentrantCall(ReentrantCall),
+    InsufficientGas(InsufficientGas),
+    InvalidAddress(InvalidAddress),
+    DelegationLoop(DelegationLoop),
 
     EcRecoverError(EcRecoverError),
+    EcRecoverMalformedInput(EcRecoverMalformedInput),
+    EcRecoverFailed(EcRecoverFailed),
+    InvalidSignatureChainId(InvalidSignatureChainId),
+    InvalidChainId(InvalidChainId),
+    InvalidSignatureComponents(InvalidSignatureComponents),
+    InvalidSignatureS(InvalidSignatureS),
+    InvalidSignatureV(InvalidSignatureV),
+    InvalidSignatureLength(InvalidSignatureLength),
 
     InvalidPlatformSignature(InvalidPlatformSignature),
+    InvalidUserSignature(InvalidUserSignature),
+    ExpiredSignature(ExpiredSignature),
+    SignatureCancelled(SignatureCancelled),
+    SignatureRevoked(SignatureRevoked),
+    DeadlineTooFar(DeadlineTooFar),
+    ValidatorHintMismatch(ValidatorHintMismatch),
+    QuorumNotReached(QuorumNotReached),
+
+    ZeroConsumption(ZeroConsumption),
+    ContentNotFound(ContentNotFound),
+    ChannelDenied(ChannelDenied),
+    ChannelContentMismatch(ChannelContentMismatch),
+    ChannelNotRegistered(ChannelNotRegistered),
+    ChannelCapExceeded(ChannelCapExceeded),
+    ConsumptionTooSmall(ConsumptionTooSmall),
+    ConsumptionTooLarge(ConsumptionTooLarge),
+    ConsumptionOverflow(ConsumptionOverflow),
+    InsufficientConsumption(InsufficientConsumption),
+    UserCapExceeded(UserCapExceeded),
+    GlobalCapExceeded(GlobalCapExceeded),
+    ChannelDailyCapExceeded(ChannelDailyCapExceeded),
+    PushCountExceeded(PushCountExceeded),
+    RateLimited(RateLimited),
+    ImplausibleConsumptionRate(ImplausibleConsumptionRate),
+    ChannelSpreadExceeded(ChannelSpreadExceeded),
+    CooldownActive(CooldownActive),
+    DoublePushInBlock(DoublePushInBlock),
+
+    RewardsFrozen(RewardsFrozen),
+    NothingToClaim(NothingToClaim),
+    NothingToWithdraw(NothingToWithdraw),
+    WithdrawFailed(WithdrawFailed),
+    RewardTransferFailed(RewardTransferFailed),
+
+    PendingNotReleasable(PendingNotReleasable),
+    TooManyPendingPushes(TooManyPendingPushes),
+
+    InvalidPenalty(InvalidPenalty),
+
+    LimitTooLarge(LimitTooLarge),
+    BatchTooLarge(BatchTooLarge),
+    RangeTooLarge(RangeTooLarge),
+    LengthMismatch(LengthMismatch),
+
+    AlreadyMigrated(AlreadyMigrated),
+
+    InvalidHistogramBuckets(InvalidHistogramBuckets),
+
+    InvalidMerkleProof(InvalidMerkleProof),
+    InvalidConsumptionDelta(InvalidConsumptionDelta),
+    InvalidSnapshotId(InvalidSnapshotId),
+
+    CohortLeafAlreadyClaimed(CohortLeafAlreadyClaimed),
+
+    InvalidValidationMode(InvalidValidationMode),
+
+    TimelockRequired(TimelockRequired),
+    UnknownConfigChange(UnknownConfigChange),
+    InvalidConfigParams(InvalidConfigParams),
+    ConfigChangeNotQueued(ConfigChangeNotQueued),
+    ConfigChangeNotReady(ConfigChangeNotReady),
+
+    Ownable(ownable::Error),
+    AccessControl(control::Error),
+}
+
+/// The stable numeric code of every custom error, by selector, for frontends to map them to messages
+/// Codes are never reused nor renumbered: a new error gets the next code, a removed one keeps its code retired
+/// Zero is the unknown error sentinel
+pub const ERROR_CODES: [([u8; 4], u16); 72] = [
+    (ownable::OwnableUnauthorizedAccount::SELECTOR, 1),
+    (ownable::OwnableInvalidOwner::SELECTOR, 2),
+    (AlreadyInitialized::SELECTOR, 3),
+    (NotInitialized::SELECTOR, 4),
+    (ContractPaused::SELECTOR, 5),
+    (CallError::SELECTOR, 6),
+    (ReentrantCall::SELECTOR, 7),
+    (InsufficientGas::SELECTOR, 8),
+    (EcRecoverError::SELECTOR, 9),
+    (InvalidSignatureChainId::SELECTOR, 10),
+    (InvalidSignatureS::SELECTOR, 11),
+    (InvalidSignatureV::SELECTOR, 12),
+    (InvalidPlatformSignature::SELECTOR, 13),
+    (InvalidUserSignature::SELECTOR, 14),
+    (ExpiredSignature::SELECTOR, 15),
+    (SignatureCancelled::SELECTOR, 16),
+    (ValidatorHintMismatch::SELECTOR, 17),
+    (ZeroConsumption::SELECTOR, 18),
+    (ChannelDenied::SELECTOR, 19),
+    (ChannelContentMismatch::SELECTOR, 20),
+    (ChannelNotRegistered::SELECTOR, 21),
+    (ChannelCapExceeded::SELECTOR, 22),
+    (ConsumptionTooLarge::SELECTOR, 23),
+    (UserCapExceeded::SELECTOR, 24),
+    (GlobalCapExceeded::SELECTOR, 25),
+    (ChannelDailyCapExceeded::SELECTOR, 26),
+    (PushCountExceeded::SELECTOR, 27),
+    (RateLimited::SELECTOR, 28),
+    (RewardsFrozen::SELECTOR, 29),
+    (NothingToClaim::SELECTOR, 30),
+    (NothingToWithdraw::SELECTOR, 31),
+    (WithdrawFailed::SELECTOR, 32),
+    (PendingNotReleasable::SELECTOR, 33),
+    (InvalidPenalty::SELECTOR, 34),
+    (LimitTooLarge::SELECTOR, 35),
+    (RangeTooLarge::SELECTOR, 36),
+    (LengthMismatch::SELECTOR, 37),
+    (InvalidHistogramBuckets::SELECTOR, 38),
+    (InvalidMerkleProof::SELECTOR, 39),
+    (InvalidConsumptionDelta::SELECTOR, 40),
+    (CohortLeafAlreadyClaimed::SELECTOR, 41),
+    (InvalidValidationMode::SELECTOR, 42),
+    (TimelockRequired::SELECTOR, 43),
+    (UnknownConfigChange::SELECTOR, 44),
+    (InvalidConfigParams::SELECTOR, 45),
+    (ConfigChangeNotQueued::SELECTOR, 46),
+    (ConfigChangeNotReady::SELECTOR, 47),
+    (CooldownActive::SELECTOR, 48),
+    (ContentNotFound::SELECTOR, 49),
+    (InvalidAddress::SELECTOR, 50),
+    (AlreadyMigrated::SELECTOR, 51),
+    (control::AccessControlUnauthorizedAccount::SELECTOR, 52),
+    (control::AccessControlBadConfirmation::SELECTOR, 53),
+    (ConsumptionTooSmall::SELECTOR, 54),
+    (QuorumNotReached::SELECTOR, 55),
+    (RewardTransferFailed::SELECTOR, 56),
+    (InvalidSnapshotId::SELECTOR, 57),
+    (ImplausibleConsumptionRate::SELECTOR, 58),
+    (InvalidSignatureLength::SELECTOR, 59),
+    (SignatureRevoked::SELECTOR, 60),
+    (ConsumptionOverflow::SELECTOR, 61),
+    (InsufficientConsumption::SELECTOR, 62),
+    (EcRecoverMalformedInput::SELECTOR, 63),
+    (EcRecoverFailed::SELECTOR, 64),
+    (ChannelSpreadExceeded::SELECTOR, 65),
+    (DoublePushInBlock::SELECTOR, 66),
+    (BatchTooLarge::SELECTOR, 67),
+    (DelegationLoop::SELECTOR, 68),
+    (InvalidChainId::SELECTOR, 69),
+    (InvalidSignatureComponents::SELECTOR, 70),
+    (TooManyPendingPushes::SELECTOR, 71),
+    (DeadlineTooFar::SELECTOR, 72),
+];
+
+/// Get the numeric code of an error selector (zero if unknown)
+pub fn error_code(selector: [u8; 4]) -> u16 {
+    ERROR_CODES
+        .iter()
+        .find(|(error_selector, _)| *error_selector == selector)
+        .map_or(0, |(_, code)| *code)
+}
+
+/// Get the error selector of a numeric code (`None` if unknown)
+pub fn error_selector(code: u16) -> Option<[u8; 4]> {
+    ERROR_CODES
+        .iter()
+        .find(|(_, error_code)| *error_code == code)
+        .map(|(selector, _)| *selector)
 }
@@ -0,0 +1,22 @@
+use stylus_sdk::{alloy_primitives::B256, crypto::keccak};
+
+/// Verify a Merkle proof, hashing each pair in sorted order (OpenZeppelin `MerkleProof` compatible)
+pub fn verify(proof: &[B256], root: B256, leaf: B256) -> bool {
+    process_proof(proof, leaf) == root
+}
+
+/// Rebuild the root from a leaf and its proof
+pub fn process_proof(proof: &[B256], leaf: B256) -> B256 {
+    proof
+        .iter()
+        .fold(leaf, |computed, sibling| hash_pair(computed, *sibling))
+}
+
+/// Hash a pair of nodes, the smaller one first
+fn hash_pair(a: B256, b: B256) -> B256 {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut input = [0u8; 64];
+    input[..32].copy_from_slice(&first[..]);
+    input[32..].copy_from_slice(&second[..]);
+    keccak(input)
+}
@@ -1,4 +1,5 @@
 pub mod eip712;
 pub mod errors;
+pub mod merkle;
 pub mod signature;
 pub mod solidity;
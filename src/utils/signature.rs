@@ -7,8 +7,52 @@ use inkmate_common::crypto::ecrecover::{
     EcdsaError, EC_RECOVER_ADDRESS_LAST_BYTE, EC_RECOVER_INPUT_LEN, NUM_BYTES_ADDRESS,
     NUM_BYTES_U256,
 };
+#[cfg(any(test, feature = "native-ecrecover"))]
+use stylus_sdk::crypto::keccak;
 use stylus_sdk::{alloy_primitives::Address, call::RawCall};
 
+/// Half of the secp256k1 curve order, the highest `s` of a non malleable signature (as in OpenZeppelin's ECDSA)
+pub const SECP256K1N_HALF: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+/// The secp256k1 curve order, above which a signature scalar is malformed
+pub const SECP256K1N: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// Check that a signature scalar (`r` or `s`) is in the `[1, n - 1]` range
+pub fn is_valid_scalar(scalar: &[u8; 32]) -> bool {
+    *scalar != [0u8; 32] && *scalar < SECP256K1N
+}
+
+/// Check that a signature `s` is in the lower half of the curve order
+/// Both `s` and `n - s` recover the same signer, so only the low one is accepted
+pub fn is_low_s(s: &[u8; 32]) -> bool {
+    *s <= SECP256K1N_HALF
+}
+
+/// Expand the `vs` half of an EIP-2098 compact signature into its `v` and `s`
+/// The top bit of `vs` is the y parity, the lower 255 bits are `s`
+pub fn split_compact_vs(vs: &[u8; 32]) -> (u8, [u8; 32]) {
+    let v = 27 + (vs[0] >> 7);
+    let mut s = *vs;
+    s[0] &= 0x7f;
+    (v, s)
+}
+
+/// Split a packed `r || s || v` 65 bytes signature into its `v`, `r` and `s` (`None` on any other length)
+pub fn split_packed(signature: &[u8]) -> Option<(u8, [u8; 32], [u8; 32])> {
+    if signature.len() != 65 {
+        return None;
+    }
+    let r: [u8; 32] = signature[0..32].try_into().ok()?;
+    let s: [u8; 32] = signature[32..64].try_into().ok()?;
+    Some((signature[64], r, s))
+}
+
 pub struct PrecompileEcRecover;
 
 impl EcRecoverTrait for PrecompileEcRecover {
@@ -16,15 +60,59 @@ impl EcRecoverTrait for PrecompileEcRecover {
     fn ecrecover_implementation(
         input: [u8; EC_RECOVER_INPUT_LEN],
     ) -> Result<[u8; NUM_BYTES_ADDRESS], EcdsaError> {
-        let res = RawCall::new_static()
-            // Only get the last 20 bytes of the 32-byte return data
-            .limit_return_data(NUM_BYTES_U256 - NUM_BYTES_ADDRESS, NUM_BYTES_ADDRESS)
-            .call(
-                Address::with_last_byte(EC_RECOVER_ADDRESS_LAST_BYTE),
-                &input,
-            )
-            .map_err(|_| EcdsaError)?;
+        // SAFETY: static call to the ecrecover precompile, which can't reenter
+        let res = unsafe {
+            RawCall::new_static()
+                // Only get the last 20 bytes of the 32-byte return data
+                .limit_return_data(NUM_BYTES_U256 - NUM_BYTES_ADDRESS, NUM_BYTES_ADDRESS)
+                .call(
+                    Address::with_last_byte(EC_RECOVER_ADDRESS_LAST_BYTE),
+                    &input,
+                )
+        }
+        .map_err(|_| EcdsaError)?;
 
         res.try_into().map_err(|_| EcdsaError)
     }
 }
+
+/// Pure Rust secp256k1 recovery, for environments lacking the ecrecover precompile
+/// Way more expensive than the precompile (the whole curve arithmetic runs in wasm),
+/// and pulls `k256` into the contract, adding a significant amount to the wasm size
+/// Also used by the host tests, which have no precompile to call
+#[cfg(any(test, feature = "native-ecrecover"))]
+pub struct NativeEcRecover;
+
+#[cfg(any(test, feature = "native-ecrecover"))]
+impl EcRecoverTrait for NativeEcRecover {
+    /// Recover the signer from the precompile formatted input (`hash || v || r || s`)
+    fn ecrecover_implementation(
+        input: [u8; EC_RECOVER_INPUT_LEN],
+    ) -> Result<[u8; NUM_BYTES_ADDRESS], EcdsaError> {
+        use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+
+        let (hash, rest) = input.split_at(NUM_BYTES_U256);
+        let (v, signature) = rest.split_at(NUM_BYTES_U256);
+
+        // Only 27 / 28 are valid `v`, like on the precompile
+        if v[..NUM_BYTES_U256 - 1].iter().any(|byte| *byte != 0) {
+            return Err(EcdsaError);
+        }
+        let recovery_id = match v[NUM_BYTES_U256 - 1] {
+            27 => RecoveryId::from_byte(0),
+            28 => RecoveryId::from_byte(1),
+            _ => None,
+        }
+        .ok_or(EcdsaError)?;
+
+        // Recover the public key, and derive the address from it
+        let signature = Signature::from_slice(signature).map_err(|_| EcdsaError)?;
+        let key = VerifyingKey::recover_from_prehash(hash, &signature, recovery_id)
+            .map_err(|_| EcdsaError)?;
+        let hashed_key = keccak(&key.to_encoded_point(false).as_bytes()[1..]);
+
+        hashed_key[NUM_BYTES_U256 - NUM_BYTES_ADDRESS..]
+            .try_into()
+            .map_err(|_| EcdsaError)
+    }
+}
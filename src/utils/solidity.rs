@@ -6,4 +6,10 @@ sol! {
     function isExistingContent(uint256 _contentId) public view returns (bool);
     function getContentTypes(uint256 _contentId) public view returns (uint256);
     function isAuthorized(uint256 _contentId, address _caller) public view returns (bool);
+
+    /// EIP-1271 contract signatures
+    function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4 magicValue);
+
+    /// ERC-20 reward token
+    function transfer(address to, uint256 amount) external returns (bool);
 }
@@ -0,0 +1,196 @@
+//! Shared setup of the integration tests, run against a deployed consumption contract
+//! They read `RPC_URL`, `PRIV_KEY_PATH` (a file holding the deployer's private key, the contract owner)
+//! and `STYLUS_CONTRACT_ADDRESS` from the environment (or a `.env` file), and are skipped when unset
+//! The registry tests also need `MOCK_REGISTRY_ADDRESS`, a `mock-registry` build deployed with the same key
+
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use ethers::{
+    abi::Token,
+    middleware::SignerMiddleware,
+    prelude::abigen,
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer},
+    types::{Address, Bytes, TransactionRequest, H256, U256},
+    utils::keccak256,
+};
+use eyre::eyre;
+use tokio::sync::{Mutex, MutexGuard};
+
+abigen!(
+    Consumption,
+    r#"[
+        function addValidator(address validator) external
+        function getNuttyContentId() external view returns (uint256)
+        function setContentRewardRate(uint256 contentId, uint256 rate) external
+        function fundRewards() external payable
+        function pushDigest(address user, bytes32 channelId, uint256 addedConsumption, uint256 deadline) external view returns (bytes32)
+        function pushCcu(bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external
+        function pushCcuChecked(bytes32 channelId, uint256 addedConsumption, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external returns (bool, uint256)
+        function pushCcuBatch(bytes32[] channelIds, uint256[] addedConsumptions, uint256[] deadlines, uint8[] vs, bytes32[] rs, bytes32[] ss, bool allOrNothing) external returns (bool[], uint256, bool)
+        function domainSeparator() external returns (bytes32)
+        function setValidationMode(uint8 mode, address platformSigner, address registry) external
+        function claimRewards() external returns (uint256)
+        function withdraw() external
+        function getWithdrawable(address user) external view returns (uint256)
+        function getUserConsumption(address user) external view returns (uint256)
+        function getNonce(address user) external view returns (uint256)
+        function setUserConsumption(address user, uint256 newValue) external
+        function resetUserConsumption(address user) external returns (uint256)
+        function resetUserConsumptionBatch(address[] users) external returns (uint256, bool)
+    ]"#
+);
+
+abigen!(
+    MockRegistry,
+    r#"[
+        function initialize() external
+        function setContent(uint256 contentId, bool exists, uint256 types) external
+        function setAuthorized(uint256 contentId, address caller, bool authorized) external
+        function isAuthorized(uint256 contentId, address caller) external view returns (bool)
+    ]"#
+);
+
+pub type Client = SignerMiddleware<Provider<Http>, LocalWallet>;
+
+/// The channel the integration tests push on
+pub const CHANNEL_ID: [u8; 32] = [7u8; 32];
+
+/// The validation mode asking the content registry about each validator
+pub const VALIDATION_MODE_REGISTRY: u8 = 0;
+
+/// The struct type signed by the validators for a push
+const VALIDATE_CONSUMPTION_TYPE: &str = "ValidateConsumption(address user,bytes32 channelId,uint256 addedConsumption,uint256 deadline,uint256 nonce)";
+
+/// Serializes the tests of a binary, as they all send from the same key
+static SERIAL: Mutex<()> = Mutex::const_new(());
+
+/// Hold the lock serializing the tests of a binary
+pub async fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().await
+}
+
+/// Connect to the deployed contract as its owner, or `None` if the environment isn't set
+pub async fn connect() -> eyre::Result<Option<(Arc<Client>, Consumption<Client>)>> {
+    dotenv::dotenv().ok();
+    let (Ok(rpc_url), Ok(priv_key_path), Ok(contract_address)) = (
+        std::env::var("RPC_URL"),
+        std::env::var("PRIV_KEY_PATH"),
+        std::env::var("STYLUS_CONTRACT_ADDRESS"),
+    ) else {
+        eprintln!("RPC_URL, PRIV_KEY_PATH or STYLUS_CONTRACT_ADDRESS unset, skipping");
+        return Ok(None);
+    };
+
+    let provider = Provider::<Http>::try_from(rpc_url)?;
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let private_key = std::fs::read_to_string(priv_key_path)?;
+    let wallet = private_key
+        .trim()
+        .parse::<LocalWallet>()?
+        .with_chain_id(chain_id);
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+    let contract = Consumption::new(contract_address.parse::<Address>()?, client.clone());
+    Ok(Some((client, contract)))
+}
+
+/// Connect to the deployed mock registry, or `None` if `MOCK_REGISTRY_ADDRESS` isn't set
+pub fn mock_registry(client: &Arc<Client>) -> eyre::Result<Option<MockRegistry<Client>>> {
+    let Ok(registry_address) = std::env::var("MOCK_REGISTRY_ADDRESS") else {
+        eprintln!("MOCK_REGISTRY_ADDRESS unset, skipping");
+        return Ok(None);
+    };
+    let registry = MockRegistry::new(registry_address.parse::<Address>()?, client.clone());
+    Ok(Some(registry))
+}
+
+/// A deadline an hour ahead of the latest block
+pub async fn deadline(client: &Client) -> eyre::Result<U256> {
+    let block = client
+        .get_block(client.get_block_number().await?)
+        .await?
+        .ok_or(eyre!("no latest block"))?;
+    Ok(block.timestamp + 3600)
+}
+
+/// Sign a `pushCcu` of a user with the client's key, through the contract's own digest
+pub async fn sign_push(
+    client: &Client,
+    contract: &Consumption<Client>,
+    user: Address,
+    added_consumption: U256,
+    deadline: U256,
+) -> eyre::Result<(u8, [u8; 32], [u8; 32])> {
+    sign_push_with(client.signer(), contract, user, added_consumption, deadline).await
+}
+
+/// Sign a `pushCcu` of a user with a given validator key, through the contract's own digest
+pub async fn sign_push_with(
+    validator: &LocalWallet,
+    contract: &Consumption<Client>,
+    user: Address,
+    added_consumption: U256,
+    deadline: U256,
+) -> eyre::Result<(u8, [u8; 32], [u8; 32])> {
+    let digest = contract
+        .push_digest(user, CHANNEL_ID, added_consumption, deadline)
+        .call()
+        .await?;
+    sign_digest(validator, H256::from(digest))
+}
+
+/// Sign a `pushCcu` of a user bound to a given nonce (e.g. the later entries of a batch), with a given validator key
+/// The digest is built here, as the contract's `pushDigest` only binds the current nonce
+pub async fn sign_push_at_nonce(
+    validator: &LocalWallet,
+    contract: &Consumption<Client>,
+    user: Address,
+    added_consumption: U256,
+    deadline: U256,
+    nonce: U256,
+) -> eyre::Result<(u8, [u8; 32], [u8; 32])> {
+    let domain_separator = contract.domain_separator().call().await?;
+    let struct_hash = keccak256(ethers::abi::encode(&[
+        Token::FixedBytes(keccak256(VALIDATE_CONSUMPTION_TYPE).to_vec()),
+        Token::Address(user),
+        Token::FixedBytes(CHANNEL_ID.to_vec()),
+        Token::Uint(added_consumption),
+        Token::Uint(deadline),
+        Token::Uint(nonce),
+    ]));
+    let mut preimage = vec![0x19, 0x01];
+    preimage.extend_from_slice(&domain_separator);
+    preimage.extend_from_slice(&struct_hash);
+    sign_digest(validator, H256::from(keccak256(preimage)))
+}
+
+/// Sign a digest, returning its `v`, `r` and `s`
+fn sign_digest(validator: &LocalWallet, digest: H256) -> eyre::Result<(u8, [u8; 32], [u8; 32])> {
+    let signature = validator.sign_hash(digest)?;
+    let (mut r, mut s) = ([0u8; 32], [0u8; 32]);
+    signature.r.to_big_endian(&mut r);
+    signature.s.to_big_endian(&mut s);
+    Ok((signature.v as u8, r, s))
+}
+
+/// Deploy a contract forwarding any call to `target`, but reverting on a plain eth transfer
+/// (so it can push and claim as a user, while its withdrawals always fail)
+pub async fn deploy_reverting_forwarder(client: &Client, target: Address) -> eyre::Result<Address> {
+    // Runtime: revert on empty calldata, else call `target` with the calldata and bubble its result up
+    let runtime = format!(
+        "3615603d57366000600037600060003660006000\
+         73{}\
+         5af13d600060003e6038573d6000fd5b3d6000f35b60006000fd",
+        hex::encode(target)
+    );
+    // Init code: copy the 67 bytes runtime (right after these 12 bytes) to memory, and return it
+    let init_code = hex::decode(format!("6043600c60003960436000f3{runtime}"))?;
+    let receipt = client
+        .send_transaction(TransactionRequest::new().data(Bytes::from(init_code)), None)
+        .await?
+        .await?
+        .ok_or(eyre!("no deployment receipt"))?;
+    receipt.contract_address.ok_or(eyre!("no deployed address"))
+}
@@ -0,0 +1,61 @@
+//! Gas benchmark of the season end resets: `resetUserConsumptionBatch` against one `resetUserConsumption` per user
+//! Run with `cargo test --test gas_reset -- --nocapture` against a deployment, the figures being printed
+
+mod common;
+
+use common::connect;
+use ethers::types::{Address, U256};
+
+/// Users reset on each side of the benchmark
+const USERS: usize = 20;
+
+#[tokio::test]
+async fn batch_reset_costs_less_than_single_resets() -> eyre::Result<()> {
+    let Some((_, contract)) = connect().await? else {
+        return Ok(());
+    };
+
+    // Fresh users with a consumption, and their per user slots written
+    let users: Vec<Address> = (0..2 * USERS).map(|_| Address::random()).collect();
+    for user in &users {
+        contract
+            .set_user_consumption(*user, U256::from(100))
+            .send()
+            .await?
+            .await?;
+    }
+    let (batched, single) = users.split_at(USERS);
+
+    let receipt = contract
+        .reset_user_consumption_batch(batched.to_vec())
+        .send()
+        .await?
+        .await?
+        .expect("no batch reset receipt");
+    let batch_gas = receipt.gas_used.unwrap_or_default();
+
+    let mut single_gas = U256::zero();
+    for user in single {
+        let receipt = contract
+            .reset_user_consumption(*user)
+            .send()
+            .await?
+            .await?
+            .expect("no reset receipt");
+        single_gas += receipt.gas_used.unwrap_or_default();
+    }
+
+    for user in &users {
+        assert_eq!(
+            contract.get_user_consumption(*user).call().await?,
+            U256::zero()
+        );
+    }
+    println!(
+        "reset of {USERS} users: batch {batch_gas} gas ({} per user), single resets {single_gas} gas ({} per user)",
+        batch_gas / USERS,
+        single_gas / USERS
+    );
+    assert!(batch_gas < single_gas);
+    Ok(())
+}
@@ -0,0 +1,196 @@
+//! Pushes checked against a content registry (the `mock-registry` build): an authorized validator is credited,
+//! an unauthorized one silently rejected, and a batch only asks the registry once per signer
+
+mod common;
+
+use std::sync::Arc;
+
+use common::{
+    connect, deadline, mock_registry, serial, sign_push_at_nonce, sign_push_with, Client,
+    Consumption, MockRegistry, CHANNEL_ID, VALIDATION_MODE_REGISTRY,
+};
+use ethers::{
+    core::rand::thread_rng,
+    providers::Middleware,
+    signers::{LocalWallet, Signer},
+    types::{
+        Address, CallFrame, GethDebugBuiltInTracerType, GethDebugTracerType,
+        GethDebugTracingOptions, GethTrace, GethTraceFrame, NameOrAddress, U256,
+    },
+    utils::id,
+};
+
+/// Entries of the batch test, all signed by the same validator
+const BATCH_SIZE: usize = 5;
+
+/// The client, the contract, the registry and the authorized validator
+type Setup = (
+    Arc<Client>,
+    Consumption<Client>,
+    MockRegistry<Client>,
+    LocalWallet,
+);
+
+/// Point the contract to the mock registry, with a fresh validator authorized on the pushed content
+async fn setup() -> eyre::Result<Option<Setup>> {
+    let Some((client, contract)) = connect().await? else {
+        return Ok(None);
+    };
+    let Some(registry) = mock_registry(&client)? else {
+        return Ok(None);
+    };
+
+    let content_id = contract.get_nutty_content_id().call().await?;
+    registry.initialize().send().await?.await?;
+    registry
+        .set_content(content_id, true, U256::zero())
+        .send()
+        .await?
+        .await?;
+    contract
+        .set_validation_mode(
+            VALIDATION_MODE_REGISTRY,
+            Address::zero(),
+            registry.address(),
+        )
+        .send()
+        .await?
+        .await?;
+
+    let validator = LocalWallet::new(&mut thread_rng());
+    registry
+        .set_authorized(content_id, validator.address(), true)
+        .send()
+        .await?
+        .await?;
+    Ok(Some((client, contract, registry, validator)))
+}
+
+#[tokio::test]
+async fn registry_authorized_validator_is_credited() -> eyre::Result<()> {
+    let _serial = serial().await;
+    let Some((client, contract, _, validator)) = setup().await? else {
+        return Ok(());
+    };
+    let user = client.address();
+    let added = U256::from(10);
+    let before = contract.get_user_consumption(user).call().await?;
+
+    let deadline = deadline(&client).await?;
+    let (v, r, s) = sign_push_with(&validator, &contract, user, added, deadline).await?;
+    let push = contract.push_ccu_checked(CHANNEL_ID, added, deadline, v, r, s);
+    assert_eq!(push.call().await?, (true, before + added));
+    push.send().await?.await?;
+
+    assert_eq!(
+        contract.get_user_consumption(user).call().await?,
+        before + added
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn unauthorized_validator_is_silently_rejected() -> eyre::Result<()> {
+    let _serial = serial().await;
+    let Some((client, contract, _, _)) = setup().await? else {
+        return Ok(());
+    };
+    let user = client.address();
+    let added = U256::from(10);
+    let before = contract.get_user_consumption(user).call().await?;
+    let nonce = contract.get_nonce(user).call().await?;
+
+    // A key the registry never authorized
+    let intruder = LocalWallet::new(&mut thread_rng());
+    let deadline = deadline(&client).await?;
+    let (v, r, s) = sign_push_with(&intruder, &contract, user, added, deadline).await?;
+    assert_eq!(
+        contract
+            .push_ccu_checked(CHANNEL_ID, added, deadline, v, r, s)
+            .call()
+            .await?,
+        (false, before)
+    );
+
+    // The push doesn't revert, but credits nothing and leaves the nonce for a valid signature
+    let receipt = contract
+        .push_ccu(CHANNEL_ID, added, deadline, v, r, s)
+        .send()
+        .await?
+        .await?;
+    assert_eq!(receipt.and_then(|receipt| receipt.status), Some(1.into()));
+    assert_eq!(contract.get_user_consumption(user).call().await?, before);
+    assert_eq!(contract.get_nonce(user).call().await?, nonce);
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch_asks_the_registry_once_per_signer() -> eyre::Result<()> {
+    let _serial = serial().await;
+    let Some((client, contract, registry, validator)) = setup().await? else {
+        return Ok(());
+    };
+    let user = client.address();
+    let added = U256::from(10);
+    let before = contract.get_user_consumption(user).call().await?;
+
+    // Every entry consumes a nonce, so each one is signed for the next
+    let nonce = contract.get_nonce(user).call().await?;
+    let deadline = deadline(&client).await?;
+    let (mut vs, mut rs, mut ss) = (vec![], vec![], vec![]);
+    for i in 0..BATCH_SIZE {
+        let nonce = nonce + U256::from(i);
+        let (v, r, s) =
+            sign_push_at_nonce(&validator, &contract, user, added, deadline, nonce).await?;
+        vs.push(v);
+        rs.push(r);
+        ss.push(s);
+    }
+    let pending = contract
+        .push_ccu_batch(
+            vec![CHANNEL_ID; BATCH_SIZE],
+            vec![added; BATCH_SIZE],
+            vec![deadline; BATCH_SIZE],
+            vs,
+            rs,
+            ss,
+            true,
+        )
+        .send()
+        .await?;
+    let tx_hash = pending.tx_hash();
+    pending.await?;
+    assert_eq!(
+        contract.get_user_consumption(user).call().await?,
+        before + added * U256::from(BATCH_SIZE)
+    );
+
+    // The registry calls are static ones, so they're counted from the call trace rather than by the registry
+    let options = GethDebugTracingOptions {
+        tracer: Some(GethDebugTracerType::BuiltInTracer(
+            GethDebugBuiltInTracerType::CallTracer,
+        )),
+        ..Default::default()
+    };
+    let GethTrace::Known(GethTraceFrame::CallTracer(frame)) =
+        client.debug_trace_transaction(tx_hash, options).await?
+    else {
+        eyre::bail!("unexpected trace format");
+    };
+    assert_eq!(count_is_authorized_calls(&frame, registry.address()), 1);
+    Ok(())
+}
+
+/// Count the `isAuthorized` calls to the registry in a call trace, nested calls included
+fn count_is_authorized_calls(frame: &CallFrame, registry: Address) -> usize {
+    let selector = id("isAuthorized(uint256,address)");
+    let is_authorized =
+        frame.to == Some(NameOrAddress::Address(registry)) && frame.input.starts_with(&selector);
+    let nested: usize = frame
+        .calls
+        .iter()
+        .flatten()
+        .map(|call| count_is_authorized_calls(call, registry))
+        .sum();
+    usize::from(is_authorized) + nested
+}
@@ -0,0 +1,74 @@
+//! The eth reward withdrawals are pull payments: a recipient reverting on receive only fails its own withdrawal
+
+mod common;
+
+use common::{connect, deadline, deploy_reverting_forwarder, sign_push, CHANNEL_ID};
+use ethers::{
+    providers::Middleware,
+    types::{TransactionRequest, U256},
+};
+
+#[tokio::test]
+async fn reverting_recipient_only_blocks_its_own_withdrawal() -> eyre::Result<()> {
+    let Some((client, contract)) = connect().await? else {
+        return Ok(());
+    };
+    let owner = client.address();
+    let added = U256::from(10);
+
+    // The owner pushes as a validator, every consumption unit accruing 1 wei
+    contract.add_validator(owner).send().await?.await?;
+    let content_id = contract.get_nutty_content_id().call().await?;
+    contract
+        .set_content_reward_rate(content_id, U256::one())
+        .send()
+        .await?
+        .await?;
+    contract
+        .fund_rewards()
+        .value(U256::from(100))
+        .send()
+        .await?
+        .await?;
+
+    // A contract user, pushing and claiming through a forwarder which reverts on receive
+    let forwarder = deploy_reverting_forwarder(&client, contract.address()).await?;
+    let deadline = deadline(&client).await?;
+    let (v, r, s) = sign_push(&client, &contract, forwarder, added, deadline).await?;
+    for call_data in [
+        contract
+            .push_ccu(CHANNEL_ID, added, deadline, v, r, s)
+            .calldata(),
+        contract.claim_rewards().calldata(),
+    ] {
+        let tx = TransactionRequest::new()
+            .to(forwarder)
+            .data(call_data.unwrap());
+        client.send_transaction(tx, None).await?.await?;
+    }
+    assert_eq!(contract.get_withdrawable(forwarder).call().await?, added);
+
+    // Its withdrawal fails, and leaves its balance untouched
+    let withdraw = TransactionRequest::new()
+        .to(forwarder)
+        .data(contract.withdraw().calldata().unwrap())
+        .gas(5_000_000);
+    let receipt = client.send_transaction(withdraw, None).await?.await?;
+    assert_eq!(receipt.and_then(|receipt| receipt.status), Some(0.into()));
+    assert_eq!(contract.get_withdrawable(forwarder).call().await?, added);
+
+    // While an eoa user still withdraws
+    let deadline = common::deadline(&client).await?;
+    let (v, r, s) = sign_push(&client, &contract, owner, added, deadline).await?;
+    contract
+        .push_ccu(CHANNEL_ID, added, deadline, v, r, s)
+        .send()
+        .await?
+        .await?;
+    contract.claim_rewards().send().await?.await?;
+    let withdrawable = contract.get_withdrawable(owner).call().await?;
+    assert!(withdrawable >= added);
+    contract.withdraw().send().await?.await?;
+    assert_eq!(contract.get_withdrawable(owner).call().await?, U256::zero());
+    Ok(())
+}